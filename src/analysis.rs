@@ -1,67 +1,181 @@
-use std::cmp::Reverse;
-use std::collections::BTreeSet;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::iter;
 
-use crate::nhlapi::{self, schedule::Game, standings::TeamRecord, teams::Team};
-use crate::simulation;
+use chrono::NaiveDate;
+use serde::{Serialize, Serializer};
+
+use crate::nhlapi::{
+    self,
+    schedule::{Date, Game},
+    standings::{Grouping, TeamRecord},
+    teams::{Conference, Team},
+    Backend,
+};
+use crate::simulation::{self, SortBasis, Tiebreaker};
+
+/// Number of wildcard spots in each conference.
+pub const WILDCARD_SPOTS: usize = 2;
+
+/// Number of teams of each conference that make the playoffs.
+pub const PLAYOFF_TEAMS: usize = 8;
+
+/// Number of upcoming games of the team shown in the schedule.
+pub const UPCOMING_GAMES: usize = 10;
 
 pub struct Api {
     pub teams: Vec<Team>,
-    pub past_standings: Vec<TeamRecord>,
+    /// Yesterday's standings, missing on the first day of a season or when the API fails.
+    pub past_standings: Option<Vec<TeamRecord>>,
     pub standings: Vec<TeamRecord>,
-    pub results: nhlapi::schedule::Date,
+    /// Yesterday's games, missing when the API fails.
+    pub results: Option<nhlapi::schedule::Date>,
     pub games: nhlapi::schedule::Date,
+    /// Official division leaders and wildcard groupings, when available.
+    pub wildcard: Option<Vec<Grouping>>,
 }
 
 impl Api {
-    pub fn download() -> Api {
-        let teams = nhlapi::teams::get().expect("error getting teams");
-        let past_standings = nhlapi::standings::yesterday().expect("error getting past standings");
-        let standings = nhlapi::standings::today().expect("error getting standings");
-        let results = nhlapi::schedule::yesterday().expect("error getting results");
-        let games = nhlapi::schedule::today().expect("error getting games");
+    /// Download the data as it was on `as_of`, normally today.
+    pub fn download(as_of: &NaiveDate) -> Api {
+        Api::download_from(Backend::StatsApi, as_of)
+    }
+
+    /// Like `download`, from the given NHL API.
+    pub fn download_from(backend: Backend, as_of: &NaiveDate) -> Api {
+        let yesterday = as_of.pred();
+        // The divisions of past seasons may differ from today's.
+        let teams = backend.teams(as_of).expect("error getting teams");
+        let past_standings = backend
+            .standings(&yesterday, &teams)
+            .map_err(|e| {
+                eprintln!(
+                    "warning: no standings for {}, skipping the comparison: {}",
+                    yesterday, e
+                )
+            })
+            .ok()
+            .filter(|records| !records.is_empty());
+        let standings = backend.standings(as_of, &teams).expect("error getting standings");
+        let results = backend
+            .schedule(&yesterday)
+            .map_err(|e| eprintln!("warning: no results for {}, skipping the recap: {}", yesterday, e))
+            .ok();
+        let games = backend.schedule(as_of).expect("error getting games");
+        // The seeding is computed from the standings if this is missing.
+        let wildcard = match backend {
+            Backend::StatsApi => nhlapi::standings::get_wildcard(as_of).ok(),
+            Backend::Web => None,
+        };
 
+        Api::from_parts(teams, past_standings, standings, results, games).with_wildcard(wildcard)
+    }
+
+    /// Build an `Api` from data that was already fetched, without touching the network.
+    pub fn from_parts(
+        teams: Vec<Team>,
+        past_standings: Option<Vec<TeamRecord>>,
+        standings: Vec<TeamRecord>,
+        results: Option<nhlapi::schedule::Date>,
+        games: nhlapi::schedule::Date,
+    ) -> Api {
         Api {
             teams,
             past_standings,
             standings,
             results,
             games,
+            wildcard: None,
         }
     }
 
+    pub fn with_wildcard(mut self, wildcard: Option<Vec<Grouping>>) -> Api {
+        self.wildcard = wildcard;
+        self
+    }
+
     pub fn get_team_by_abbrev(&self, abbrev: &str) -> &Team {
+        self.find_team_by_abbrev(abbrev).expect("team abbrev not found")
+    }
+
+    /// `get_team_by_abbrev`, or `None` for an unknown abbreviation.
+    pub fn find_team_by_abbrev(&self, abbrev: &str) -> Option<&Team> {
         let abbrev = abbrev.to_ascii_uppercase();
-        self.teams
-            .iter()
-            .find(|t| t.abbrev == abbrev)
-            .expect("team abbrev not found")
+        self.teams.iter().find(|t| t.abbrev == abbrev)
     }
 
     pub fn get_team_by_id(&self, team_id: u32) -> &Team {
         self.teams.iter().find(|t| t.id == team_id).expect("team id not found")
     }
 
-    pub fn get_points(&self, team_id: u32, past: bool) -> u32 {
+    /// Whether the playoffs have started, going by yesterday's and today's games.
+    pub fn is_playoffs(&self) -> bool {
+        self.results
+            .iter()
+            .chain(Some(&self.games))
+            .flat_map(|date| &date.games)
+            .any(|game| game.is_playoffs())
+    }
+
+    /// Today's standings, or yesterday's if `past` and they are available.
+    pub fn records(&self, past: bool) -> Option<&[TeamRecord]> {
         if !past {
-            self.standings
-                .iter()
-                .find(|t| t.team.id == team_id)
-                .expect("team id not found")
-                .points
+            Some(&self.standings)
         } else {
-            self.past_standings
-                .iter()
-                .find(|t| t.team.id == team_id)
-                .expect("team id not found")
-                .points
+            self.past_standings.as_deref()
         }
     }
+
+    /// Record of a team in today's standings, or in yesterday's if `past`.
+    /// `None` if the team is not in the standings, or if `past` and
+    /// yesterday's standings are unavailable.
+    pub fn record_for(&self, team_id: u32, past: bool) -> Option<&TeamRecord> {
+        self.records(past)?.iter().find(|r| r.team.id == team_id)
+    }
+
+    pub fn get_points(&self, team_id: u32, past: bool) -> Option<u32> {
+        self.record_for(team_id, past).map(|r| r.points)
+    }
+}
+
+/// Optional parts of the analysis.
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    /// Also seed the other conference.
+    pub other_conference: bool,
+    /// Ids of the teams whose games are always shown, even outside of the conference.
+    pub rivals: BTreeSet<u32>,
+    /// Leave out the conference games between teams whose fate is already decided.
+    pub relevant_only: bool,
+    /// Leave out the conference games between teams more than this many
+    /// points from the team in the standings.
+    pub relevance_points: Option<u32>,
+    /// Parameters of the simulations.
+    pub params: simulation::Params,
+    /// Upcoming games of the team are must-win when its playoffs odds swing by
+    /// at least this much between a win and a loss. `None` skips the
+    /// simulations altogether.
+    pub must_win_swing: Option<f64>,
+    /// Number of tonight's games around the league to rank by their weight on
+    /// the playoffs races, every game being simulated. 0 skips them.
+    pub around_the_league: usize,
+    /// Simulate the team's odds after a win and after a loss in its game
+    /// tonight.
+    pub win_loss_odds: bool,
+    /// Pick the home team as the ideal loser of the games between two other
+    /// teams of the conference instead of simulating them, for when only the
+    /// seeding and the standings matter.
+    pub skip_simulations: bool,
 }
 
 pub struct Analyzer<'a> {
     api: &'a Api,
     my_team: &'a Team,
     own_conference_team_ids: BTreeSet<u32>,
+    /// Upcoming games of the team, for the must-win games.
+    schedule: &'a [Date],
+    options: Options,
 }
 
 impl Analyzer<'_> {
@@ -76,9 +190,25 @@ impl Analyzer<'_> {
             api,
             my_team,
             own_conference_team_ids,
+            schedule: &[],
+            options: Options::default(),
         }
     }
 
+    pub fn with_options(mut self, options: Options) -> Self {
+        self.options = options;
+        self
+    }
+}
+
+impl<'a> Analyzer<'a> {
+    pub fn with_schedule(mut self, schedule: &'a [Date]) -> Self {
+        self.schedule = schedule;
+        self
+    }
+}
+
+impl Analyzer<'_> {
     pub fn perform(&self) -> Analysis {
         let mut my_game = None;
         let mut games = vec![];
@@ -96,7 +226,7 @@ impl Analyzer<'_> {
             }
         }
 
-        for game in &self.api.results.games {
+        for game in self.api.results.iter().flat_map(|date| &date.games) {
             let m = MatchupPre::create(self, game, true);
             if m.is_relevant(self) {
                 if m.is_my_team_involed {
@@ -107,65 +237,395 @@ impl Analyzer<'_> {
             }
         }
 
-        let mut own_division_seed = vec![];
-        let mut other_division_seed = vec![];
-        let mut wildcard_seed = vec![];
-        for record in &self.api.standings {
-            if self.own_conference_team_ids.contains(&record.team.id) {
-                let team = self.api.get_team_by_id(record.team.id);
-
-                if team.division.id == self.my_team.division.id {
-                    if own_division_seed.len() < 3 {
-                        own_division_seed.push(Seed {
-                            seed: own_division_seed.len() as u32 + 1,
-                            record: record,
-                        });
-                    } else {
-                        wildcard_seed.push(Seed {
-                            seed: wildcard_seed.len() as u32 + 1,
-                            record: record,
-                        })
-                    }
-                } else {
-                    if other_division_seed.len() < 3 {
-                        other_division_seed.push(Seed {
-                            seed: other_division_seed.len() as u32 + 1,
-                            record: record,
-                        });
-                    } else {
-                        wildcard_seed.push(Seed {
-                            seed: wildcard_seed.len() as u32 + 1,
-                            record: record,
-                        })
-                    }
-                }
-            }
-        }
+        // The API may list the games in any order, keep the post stable.
+        self.sort_matchups(&mut games);
+        self.sort_matchups(&mut results);
+
+        let mut own = self.seed_conference(self.my_team.conference.id, self.my_team.division.id);
+
+        let other_conference = if self.options.other_conference {
+            self.api
+                .standings
+                .iter()
+                .map(|r| self.api.get_team_by_id(r.team.id))
+                .find(|t| t.conference.id != self.my_team.conference.id)
+                .map(|t| self.seed_conference(t.conference.id, t.division.id))
+        } else {
+            None
+        };
 
-        let mut tops = vec![&own_division_seed[0], &other_division_seed[0]];
-        tops.sort_unstable_by_key(|s| Reverse(s.record.points));
+        let points_from_spots = self.points_from_spots(&own);
 
-        let playoffs = vec![
-            PlayoffMatchup::new(&tops[0].record, &wildcard_seed[1].record),
-            PlayoffMatchup::new(&tops[1].record, &wildcard_seed[0].record),
-            PlayoffMatchup::new(&own_division_seed[1].record, &own_division_seed[2].record),
-            PlayoffMatchup::new(&other_division_seed[1].record, &other_division_seed[2].record),
-        ];
+        let must_win = match self.options.must_win_swing {
+            Some(threshold) => self.must_win_games(threshold),
+            None => BTreeMap::new(),
+        };
+        let key_games = self.key_games(self.options.around_the_league);
 
         Analysis {
             my_team: self.my_team,
+            params: &self.options.params,
             my_game: my_game,
             games: games,
             my_result: my_result,
             results: results,
-            own_division_seed,
-            other_division_seed,
+            results_available: self.api.results.is_some(),
+            own_division_seed: own.division_seeds.remove(0),
+            other_division_seeds: own.division_seeds,
+            wildcard_seed: own.wildcard_seed,
+            wildcard_spots: own.wildcard_spots,
+            playoffs: own.playoffs,
+            other_conference,
+            must_win,
+            key_games,
+            points_from_spots,
+        }
+    }
+
+    /// Points the team is back of each playoffs spot of its conference: the
+    /// points of the team holding the spot, minus the team's own. For the
+    /// spot the team holds, this is minus its lead on the next team in line
+    /// for it.
+    fn points_from_spots(&self, seeds: &ConferenceSeeds) -> BTreeMap<PlayoffSpot, i32> {
+        let own_division_seed = &seeds.division_seeds[0];
+        let my_record = match own_division_seed
+            .iter()
+            .chain(&seeds.wildcard_seed)
+            .find(|s| s.record.team.id == self.my_team.id)
+        {
+            Some(seed) => seed.record,
+            None => return BTreeMap::new(),
+        };
+        let points_back = |holder: &Seed, next: Option<&Seed>| {
+            if holder.record.team.id == self.my_team.id {
+                next.map(|next| next.record.points as i32 - my_record.points as i32)
+                    .unwrap_or(0)
+            } else {
+                holder.record.points as i32 - my_record.points as i32
+            }
+        };
+
+        let mut points = BTreeMap::new();
+        for (index, holder) in own_division_seed.iter().enumerate() {
+            // The first team in line for the last spot of the division is its
+            // best team in the wildcard race.
+            let next = own_division_seed.get(index + 1).or_else(|| {
+                seeds
+                    .wildcard_seed
+                    .iter()
+                    .find(|s| self.api.get_team_by_id(s.record.team.id).division.id == self.my_team.division.id)
+            });
+            points.insert(PlayoffSpot::Division(holder.seed), points_back(holder, next));
+        }
+        for (index, holder) in seeds.wildcard_seed.iter().take(seeds.wildcard_spots).enumerate() {
+            let next = seeds.wildcard_seed.get(index + 1);
+            points.insert(PlayoffSpot::Wildcard(holder.seed), points_back(holder, next));
+        }
+        points
+    }
+
+    /// The `count` games of tonight that weigh the most on the playoffs races
+    /// of the whole league, with their weight, see `simulation::game_importance`.
+    fn key_games(&self, count: usize) -> Vec<(&Game, f64)> {
+        if count == 0 {
+            return vec![];
+        }
+        let mut key_games: Vec<_> = self
+            .api
+            .games
+            .games
+            .iter()
+            .filter(|g| !g.is_final())
+            .map(|g| (g, simulation::game_importance(self.api, g, &self.options.params)))
+            .collect();
+        key_games.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+        key_games.truncate(count);
+        key_games
+    }
+
+    /// Upcoming games of the team whose result swings its playoffs odds by at
+    /// least `threshold`, with the swing, keyed by game pk.
+    fn must_win_games(&self, threshold: f64) -> BTreeMap<u64, f64> {
+        self.schedule
+            .iter()
+            .flat_map(|date| &date.games)
+            .take(UPCOMING_GAMES)
+            .map(|game| {
+                let swing = simulation::odds_swing(self.api, self.my_team, game, &self.options.params);
+                (game.game_pk, swing)
+            })
+            .filter(|&(_, swing)| swing >= threshold)
+            .collect()
+    }
+
+    /// Sort matchups by game time, then by the abbreviation of the home team.
+    fn sort_matchups(&self, matchups: &mut [Matchup]) {
+        matchups.sort_by(|a, b| {
+            let home_abbrev = |m: &Matchup| &self.api.get_team_by_id(m.game.home_team().id).abbrev;
+            a.game
+                .game_date
+                .cmp(&b.game.game_date)
+                .then_with(|| home_abbrev(a).cmp(home_abbrev(b)))
+        });
+    }
+
+    /// Seed the teams of a conference, starting with the given division.
+    fn seed_conference(&self, conference_id: u32, division_id: u32) -> ConferenceSeeds<'_> {
+        let format = PlayoffFormat::of_conference(&self.api.teams, conference_id);
+        let official = if self.ranked_officially() {
+            self.official_seeds(conference_id, division_id, format)
+        } else {
+            None
+        };
+        official.unwrap_or_else(|| self.computed_seeds(conference_id, division_id, format))
+    }
+
+    /// Seeds of a conference according to the NHL's wildcard standings.
+    fn official_seeds<'s>(
+        &'s self,
+        conference_id: u32,
+        division_id: u32,
+        format: PlayoffFormat,
+    ) -> Option<ConferenceSeeds<'s>> {
+        let mut divisions = vec![];
+        let mut wildcard = None;
+        for grouping in self.api.wildcard.as_ref()? {
+            if grouping.conference.as_ref().map(|c| c.id) != Some(conference_id) {
+                continue;
+            }
+            if grouping.is_wildcard() {
+                wildcard = Some(grouping);
+            } else if grouping.is_division_leaders() {
+                if grouping.division.as_ref().map(|d| d.id) == Some(division_id) {
+                    divisions.insert(0, grouping);
+                } else {
+                    divisions.push(grouping);
+                }
+            }
+        }
+
+        let seeds = |grouping: &'s Grouping| -> Vec<Seed<'s>> {
+            grouping
+                .team_records
+                .iter()
+                .enumerate()
+                .map(|(index, record)| Seed {
+                    seed: index as u32 + 1,
+                    record,
+                })
+                .collect()
+        };
+        let division_seeds: Vec<_> = divisions.into_iter().map(seeds).collect();
+        let wildcard_seed = seeds(wildcard?);
+
+        if division_seeds.len() != format.divisions
+            || division_seeds.iter().any(|seeds| seeds.len() < format.division_spots)
+            || wildcard_seed.len() < format.wildcard_spots
+        {
+            return None;
+        }
+
+        Some(self.make_conference_seeds(format, division_seeds, wildcard_seed))
+    }
+
+    /// Whether the teams are ranked the way the NHL does this season, by
+    /// points and the official tiebreaker, as in the API's standings.
+    fn ranked_officially(&self) -> bool {
+        let official = Tiebreaker::official(&nhlapi::Season::of(&self.api.games.date));
+        self.options.params.sort_basis == SortBasis::Points && self.tiebreaker() == official
+    }
+
+    fn tiebreaker(&self) -> Tiebreaker {
+        self.options.params.tiebreaker_on(&self.api.games.date)
+    }
+
+    /// Seeds of a conference computed from the league standings, in the
+    /// order of the API when ranked officially.
+    fn computed_seeds(&self, conference_id: u32, division_id: u32, format: PlayoffFormat) -> ConferenceSeeds<'_> {
+        let (basis, tiebreaker) = (self.options.params.sort_basis, self.tiebreaker());
+        let mut standings: Vec<&TeamRecord> = self.api.standings.iter().collect();
+        if !self.ranked_officially() {
+            standings.sort_by(|a, b| cmp_standings_by(basis, tiebreaker, a, b));
+        }
+
+        // The given division comes first, the others in the order of their best team.
+        let mut division_ids = vec![division_id];
+        let mut division_seeds = vec![vec![]];
+        let mut wildcard_seed = vec![];
+        for record in standings {
+            let team = self.api.get_team_by_id(record.team.id);
+            if team.conference.id != conference_id {
+                continue;
+            }
+            let index = match division_ids.iter().position(|&id| id == team.division.id) {
+                Some(index) => index,
+                None => {
+                    division_ids.push(team.division.id);
+                    division_seeds.push(vec![]);
+                    division_ids.len() - 1
+                }
+            };
+            let seeds = if division_seeds[index].len() < format.division_spots {
+                &mut division_seeds[index]
+            } else {
+                &mut wildcard_seed
+            };
+            seeds.push(Seed {
+                seed: seeds.len() as u32 + 1,
+                record,
+            });
+        }
+
+        self.make_conference_seeds(format, division_seeds, wildcard_seed)
+    }
+
+    fn make_conference_seeds<'s>(
+        &'s self,
+        format: PlayoffFormat,
+        division_seeds: Vec<Vec<Seed<'s>>>,
+        wildcard_seed: Vec<Seed<'s>>,
+    ) -> ConferenceSeeds<'s> {
+        let (basis, tiebreaker) = (self.options.params.sort_basis, self.tiebreaker());
+        let records = |seeds: &[Seed<'s>]| seeds.iter().map(|s| s.record).collect::<Vec<_>>();
+        let playoffs = first_round(
+            format,
+            &division_seeds.iter().map(|seeds| records(seeds)).collect::<Vec<_>>(),
+            &records(&wildcard_seed),
+            |a, b| cmp_standings_by(basis, tiebreaker, a, b),
+            |record| {
+                let name = &self.api.get_team_by_id(record.team.id).division.name;
+                name.chars().next().unwrap_or('D')
+            },
+        )
+        .into_iter()
+        .map(|(high, low)| PlayoffMatchup::new(high, low))
+        .collect();
+
+        ConferenceSeeds {
+            conference: &self.api.get_team_by_id(division_seeds[0][0].record.team.id).conference,
+            division_seeds,
             wildcard_seed,
+            wildcard_spots: format.wildcard_spots,
             playoffs,
         }
     }
 }
 
+/// How the playoffs spots of a conference are handed out: first to the top
+/// teams of each division, then to the best of the others as wildcards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlayoffFormat {
+    /// Number of divisions in the conference.
+    pub divisions: usize,
+    /// Spots that go to the top teams of each division.
+    pub division_spots: usize,
+    pub wildcard_spots: usize,
+}
+
+impl PlayoffFormat {
+    /// Format of a conference of `divisions` divisions. With 2 divisions, the
+    /// top 3 of each and 2 wildcards make the playoffs. With more divisions,
+    /// each gets fewer spots and the wildcards make up the rest.
+    pub fn with_divisions(divisions: usize) -> PlayoffFormat {
+        let division_spots = (PLAYOFF_TEAMS - WILDCARD_SPOTS) / divisions.max(1);
+        PlayoffFormat {
+            divisions,
+            division_spots,
+            wildcard_spots: PLAYOFF_TEAMS - divisions * division_spots,
+        }
+    }
+
+    /// Format of a conference, going by the divisions of its teams.
+    pub fn of_conference(teams: &[Team], conference_id: u32) -> PlayoffFormat {
+        let divisions: BTreeSet<u32> = teams
+            .iter()
+            .filter(|t| t.conference.id == conference_id)
+            .map(|t| t.division.id)
+            .collect();
+        PlayoffFormat::with_divisions(divisions.len())
+    }
+}
+
+/// First round matchups of a conference, high seed first, from the teams
+/// holding the spots of each division and the wildcard standings, best first.
+/// `cmp` orders two teams, the better one first, and `letter` names the
+/// division of a team. Empty unless every spot of `format` is filled.
+pub fn first_round<T: Copy>(
+    format: PlayoffFormat,
+    division_seeds: &[Vec<T>],
+    wildcard_seed: &[T],
+    cmp: impl Fn(T, T) -> Ordering,
+    letter: impl Fn(T) -> char,
+) -> Vec<((T, BracketSeed), (T, BracketSeed))> {
+    if division_seeds.len() != format.divisions
+        || division_seeds.iter().any(|seeds| seeds.len() < format.division_spots)
+        || wildcard_seed.len() < format.wildcard_spots
+    {
+        return vec![];
+    }
+
+    if format == PlayoffFormat::with_divisions(2) {
+        let division = |index: usize, rank: usize| {
+            let team = division_seeds[index][rank];
+            (team, BracketSeed::Division(letter(team), rank as u32 + 1))
+        };
+        let wildcard = |rank: usize| (wildcard_seed[rank], BracketSeed::Wildcard(rank as u32 + 1));
+
+        // The better division winner faces the second wildcard, the other
+        // one the first wildcard, and the 2nd and 3rd of each division
+        // face each other.
+        let (best, other) = match cmp(division_seeds[0][0], division_seeds[1][0]) {
+            Ordering::Greater => (1, 0),
+            _ => (0, 1),
+        };
+        vec![
+            (division(best, 0), wildcard(1)),
+            (division(other, 0), wildcard(0)),
+            (division(0, 1), division(0, 2)),
+            (division(1, 1), division(1, 2)),
+        ]
+    } else {
+        // Other formats are seeded across the conference, the division
+        // leaders first: 1st against 8th, 2nd against 7th, and so on.
+        let mut leaders: Vec<T> = division_seeds
+            .iter()
+            .filter_map(|seeds| seeds.first())
+            .cloned()
+            .collect();
+        leaders.sort_by(|&a, &b| cmp(a, b));
+        let mut others: Vec<T> = division_seeds
+            .iter()
+            .flat_map(|seeds| seeds.iter().skip(1))
+            .chain(wildcard_seed.iter().take(format.wildcard_spots))
+            .cloned()
+            .collect();
+        others.sort_by(|&a, &b| cmp(a, b));
+        let ranked: Vec<T> = leaders.into_iter().chain(others).collect();
+        let seed = |index: usize| (ranked[index], BracketSeed::Conference(index as u32 + 1));
+        (0..ranked.len() / 2)
+            .map(|index| (seed(index), seed(ranked.len() - 1 - index)))
+            .collect()
+    }
+}
+
+/// Order two teams the way the NHL breaks ties in the standings: points, then
+/// regulation and overtime wins, then goal differential. The team id is the
+/// last resort, so that the order never depends on the input.
+pub fn cmp_standings(a: &TeamRecord, b: &TeamRecord) -> Ordering {
+    cmp_standings_by(SortBasis::Points, Tiebreaker::Row, a, b)
+}
+
+/// `cmp_standings`, ranking the teams by `basis` and breaking the ties with
+/// the wins counted by `tiebreaker`.
+pub fn cmp_standings_by(basis: SortBasis, tiebreaker: Tiebreaker, a: &TeamRecord, b: &TeamRecord) -> Ordering {
+    let goal_diff = |r: &TeamRecord| i64::from(r.goals_scored) - i64::from(r.goals_against);
+    basis
+        .cmp((a.points, a.games_played), (b.points, b.games_played))
+        .then_with(|| tiebreaker.wins(b).cmp(&tiebreaker.wins(a)))
+        .then_with(|| goal_diff(b).cmp(&goal_diff(a)))
+        .then_with(|| a.team.id.cmp(&b.team.id))
+}
+
 #[derive(Debug)]
 pub struct Seed<'a> {
     pub seed: u32,
@@ -176,25 +636,295 @@ pub struct Seed<'a> {
 pub struct PlayoffMatchup<'a> {
     pub high_team: &'a TeamRecord,
     pub low_team: &'a TeamRecord,
+    pub high_seed: BracketSeed,
+    pub low_seed: BracketSeed,
 }
 
 impl PlayoffMatchup<'_> {
-    fn new<'a>(high_team: &'a TeamRecord, low_team: &'a TeamRecord) -> PlayoffMatchup<'a> {
-        PlayoffMatchup { high_team, low_team }
+    fn new<'a>(high: (&'a TeamRecord, BracketSeed), low: (&'a TeamRecord, BracketSeed)) -> PlayoffMatchup<'a> {
+        PlayoffMatchup {
+            high_team: high.0,
+            low_team: low.0,
+            high_seed: high.1,
+            low_seed: low.1,
+        }
+    }
+
+    /// The matchup with owned copies of both teams, for the JSON bracket.
+    pub fn to_bracket(&self, api: &Api) -> BracketMatchup {
+        BracketMatchup {
+            high: BracketTeam::new(api, self.high_team, self.high_seed),
+            low: BracketTeam::new(api, self.low_team, self.low_seed),
+        }
     }
 }
 
+/// Seed of a team in the playoffs bracket, labeled the way the NHL does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BracketSeed {
+    /// Rank in a division, under the first letter of its name: `A1` is the
+    /// winner of the Atlantic division.
+    Division(char, u32),
+    /// `WC1` and `WC2`.
+    Wildcard(u32),
+    /// Rank in the conference, for formats seeded across the conference.
+    Conference(u32),
+}
+
+impl fmt::Display for BracketSeed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BracketSeed::Division(letter, rank) => write!(f, "{}{}", letter, rank),
+            BracketSeed::Wildcard(rank) => write!(f, "WC{}", rank),
+            BracketSeed::Conference(rank) => write!(f, "{}", rank),
+        }
+    }
+}
+
+impl BracketSeed {
+    /// Rank of the team within its division, the wildcards or the conference.
+    pub fn rank(&self) -> u32 {
+        match *self {
+            BracketSeed::Division(_, rank) | BracketSeed::Wildcard(rank) | BracketSeed::Conference(rank) => rank,
+        }
+    }
+}
+
+impl Serialize for BracketSeed {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Team of a playoffs matchup in the JSON bracket, owned so that the bracket
+/// can be written on its own.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BracketTeam {
+    pub id: u32,
+    pub abbrev: String,
+    /// Rank of the team in its seed, the 2 of `WC2`.
+    pub seed: u32,
+    /// Seed as labeled by the NHL, such as `A1` or `WC2`.
+    pub label: BracketSeed,
+    pub points: u32,
+}
+
+impl BracketTeam {
+    fn new(api: &Api, record: &TeamRecord, seed: BracketSeed) -> BracketTeam {
+        BracketTeam {
+            id: record.team.id,
+            abbrev: api.get_team_by_id(record.team.id).abbrev.clone(),
+            seed: seed.rank(),
+            label: seed,
+            points: record.points,
+        }
+    }
+}
+
+/// First round matchup in the JSON bracket: the high seed faces the low seed.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BracketMatchup {
+    pub high: BracketTeam,
+    pub low: BracketTeam,
+}
+
+/// First round of a conference, as written by `--bracket-json`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Bracket {
+    pub conference: String,
+    pub matchups: Vec<BracketMatchup>,
+    /// Pairs of indices into `matchups` whose winners face each other in the
+    /// second round.
+    pub second_round: Vec<(usize, usize)>,
+}
+
+impl Bracket {
+    fn new(api: &Api, conference: &Conference, playoffs: &[PlayoffMatchup]) -> Bracket {
+        let mut second_round = vec![];
+        for (index, pm) in playoffs.iter().enumerate() {
+            match pm.high_seed {
+                // A division winner's matchup meets the 2 vs 3 of its division.
+                BracketSeed::Division(letter, 1) => {
+                    if let Some(other) = playoffs
+                        .iter()
+                        .position(|o| o.high_seed == BracketSeed::Division(letter, 2))
+                    {
+                        second_round.push((index, other));
+                    }
+                }
+                // 1 vs 8 meets 4 vs 5, and 2 vs 7 meets 3 vs 6.
+                BracketSeed::Conference(_) if index < playoffs.len() / 2 => {
+                    second_round.push((index, playoffs.len() - 1 - index))
+                }
+                _ => {}
+            }
+        }
+        Bracket {
+            conference: conference.name.clone(),
+            matchups: playoffs.iter().map(|pm| pm.to_bracket(api)).collect(),
+            second_round,
+        }
+    }
+}
+
+/// Division and wildcard seeds of a conference.
+#[derive(Debug)]
+pub struct ConferenceSeeds<'a> {
+    pub conference: &'a Conference,
+    /// Seeds of each division, the one given to seed the conference first.
+    pub division_seeds: Vec<Vec<Seed<'a>>>,
+    pub wildcard_seed: Vec<Seed<'a>>,
+    pub wildcard_spots: usize,
+    pub playoffs: Vec<PlayoffMatchup<'a>>,
+}
+
 #[derive(Debug)]
 pub struct Analysis<'a> {
     pub my_team: &'a Team,
+    pub params: &'a simulation::Params,
     pub my_result: Option<Matchup<'a>>,
     pub results: Vec<Matchup<'a>>,
+    /// Whether yesterday's games could be downloaded at all.
+    pub results_available: bool,
     pub my_game: Option<Matchup<'a>>,
     pub games: Vec<Matchup<'a>>,
     pub own_division_seed: Vec<Seed<'a>>,
-    pub other_division_seed: Vec<Seed<'a>>,
+    pub other_division_seeds: Vec<Vec<Seed<'a>>>,
     pub wildcard_seed: Vec<Seed<'a>>,
+    pub wildcard_spots: usize,
     pub playoffs: Vec<PlayoffMatchup<'a>>,
+    pub other_conference: Option<ConferenceSeeds<'a>>,
+    /// Upcoming games of the team that are must-win, with the swing of its
+    /// playoffs odds between a win and a loss, keyed by game pk.
+    pub must_win: BTreeMap<u64, f64>,
+    /// Tonight's games that weigh the most on the playoffs races around the
+    /// league, most important first, with their weight.
+    pub key_games: Vec<(&'a Game, f64)>,
+    /// Points the team is back of each playoffs spot of its conference,
+    /// negative for the spots it is ahead of.
+    pub points_from_spots: BTreeMap<PlayoffSpot, i32>,
+}
+
+/// A playoffs spot of a conference, from the team's point of view.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PlayoffSpot {
+    /// Nth of the team's own division.
+    Division(u32),
+    /// Nth wildcard.
+    Wildcard(u32),
+}
+
+/// Where a team stands in the playoffs race.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlayoffPosition {
+    /// In one of the spots of its division, the top 3 in the usual format.
+    DivisionTop3,
+    /// In one of the wildcard spots.
+    Wildcard,
+    /// Out of the playoffs, but close enough to the last wildcard spot.
+    InHunt,
+    /// Too far from the last wildcard spot.
+    OutOfRace,
+}
+
+/// Games in hand of a team on a rival. Negative when the rival is the one
+/// with games in hand.
+#[derive(Debug, Clone, Copy)]
+pub struct GamesInHand<'a> {
+    pub rival: &'a TeamRecord,
+    pub games: i32,
+}
+
+impl<'a> Analysis<'a> {
+    /// Games in hand of a team on the teams right ahead and behind it in its
+    /// race, either the top of its division or the wildcard standings.
+    pub fn games_in_hand(&self, team_id: u32) -> Vec<GamesInHand<'a>> {
+        let races = iter::once(&self.own_division_seed)
+            .chain(&self.other_division_seeds)
+            .chain(iter::once(&self.wildcard_seed));
+        for seeds in races {
+            if let Some(index) = seeds.iter().position(|s| s.record.team.id == team_id) {
+                let games_played = seeds[index].record.games_played as i32;
+                let ahead = index.checked_sub(1).map(|i| &seeds[i]);
+                return ahead
+                    .into_iter()
+                    .chain(seeds.get(index + 1))
+                    .map(|s| GamesInHand {
+                        rival: s.record,
+                        games: s.record.games_played as i32 - games_played,
+                    })
+                    .collect();
+            }
+        }
+        vec![]
+    }
+}
+
+impl Analysis<'_> {
+    /// First round of the team's conference, then of the other conference if
+    /// it was seeded, for the JSON bracket.
+    pub fn brackets(&self, api: &Api) -> Vec<Bracket> {
+        let mut brackets = vec![Bracket::new(api, &self.my_team.conference, &self.playoffs)];
+        if let Some(other) = &self.other_conference {
+            brackets.push(Bracket::new(api, other.conference, &other.playoffs));
+        }
+        brackets
+    }
+
+    /// Points of a team above the playoffs line, or below it when negative.
+    /// Teams in a playoffs spot are compared to the first team out, and the
+    /// others to the last wildcard. Returns `None` if the team is not in the
+    /// conference.
+    pub fn points_vs_line(&self, team_id: u32) -> Option<i32> {
+        let record = self
+            .own_division_seed
+            .iter()
+            .chain(self.other_division_seeds.iter().flatten())
+            .chain(&self.wildcard_seed)
+            .find(|s| s.record.team.id == team_id)?
+            .record;
+        let line = match self.playoff_position(team_id, 0)? {
+            PlayoffPosition::DivisionTop3 | PlayoffPosition::Wildcard => self.wildcard_seed.get(self.wildcard_spots)?,
+            PlayoffPosition::InHunt | PlayoffPosition::OutOfRace => self.wildcard_seed.get(self.wildcard_spots - 1)?,
+        };
+        Some(record.points as i32 - line.record.points as i32)
+    }
+
+    /// Team holding a playoffs spot of the conference.
+    pub fn spot_holder(&self, spot: PlayoffSpot) -> Option<&TeamRecord> {
+        let seed = match spot {
+            PlayoffSpot::Division(n) => self.own_division_seed.get(n as usize - 1),
+            PlayoffSpot::Wildcard(n) if n as usize <= self.wildcard_spots => self.wildcard_seed.get(n as usize - 1),
+            PlayoffSpot::Wildcard(_) => None,
+        };
+        seed.map(|s| s.record)
+    }
+
+    /// Classify the position of a team of the conference in the playoffs race.
+    /// Teams at most `hunt_points` points behind the last wildcard spot are
+    /// still in the hunt. Returns `None` if the team is not in the conference.
+    pub fn playoff_position(&self, team_id: u32, hunt_points: u32) -> Option<PlayoffPosition> {
+        let in_seeds = |seeds: &[Seed]| seeds.iter().position(|s| s.record.team.id == team_id);
+
+        if in_seeds(&self.own_division_seed).is_some()
+            || self.other_division_seeds.iter().any(|seeds| in_seeds(seeds).is_some())
+        {
+            return Some(PlayoffPosition::DivisionTop3);
+        }
+
+        let index = in_seeds(&self.wildcard_seed)?;
+        if index < self.wildcard_spots {
+            return Some(PlayoffPosition::Wildcard);
+        }
+
+        let cut = self.wildcard_seed[self.wildcard_spots - 1].record.points;
+        let points = self.wildcard_seed[index].record.points;
+        if cut.saturating_sub(points) <= hunt_points {
+            Some(PlayoffPosition::InHunt)
+        } else {
+            Some(PlayoffPosition::OutOfRace)
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -204,6 +934,12 @@ pub struct Matchup<'a> {
     pub is_my_team_involed: bool,
     pub ideal_loser: &'a nhlapi::Team,
     pub ideal_loser_in_conference: bool,
+    /// Playoffs odds of the team after each team loses, when the ideal loser
+    /// was picked by simulating both results.
+    pub loser_odds: Option<simulation::LoserOdds>,
+    /// Playoffs odds of the team after a win and after a regulation loss, for
+    /// its own game tonight with `Options::win_loss_odds`.
+    pub win_loss_odds: Option<(f64, f64)>,
 }
 
 impl Matchup<'_> {
@@ -217,15 +953,47 @@ impl Matchup<'_> {
         }
     }
 
-    pub fn get_mood(&self) -> &str {
+    /// How good the result is for us. A result is only good rather than
+    /// great when the ideal loser lost in overtime, because it still got a
+    /// point in the standings of our conference.
+    pub fn mood(&self) -> Mood {
         if self.game.loser().id == self.ideal_loser.id {
             if self.game.overtime() && self.ideal_loser_in_conference {
-                "Good"
+                Mood::Good
             } else {
-                "Great"
+                Mood::Great
             }
         } else {
-            "Bad"
+            Mood::Bad
+        }
+    }
+
+    pub fn get_mood(&self) -> &str {
+        self.mood().text()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mood {
+    Great,
+    Good,
+    Bad,
+}
+
+impl Mood {
+    pub fn text(&self) -> &'static str {
+        match self {
+            Mood::Great => "Great",
+            Mood::Good => "Good",
+            Mood::Bad => "Bad",
+        }
+    }
+
+    pub fn emoji(&self) -> &'static str {
+        match self {
+            Mood::Great => "✅",
+            Mood::Good => "🟡",
+            Mood::Bad => "❌",
         }
     }
 }
@@ -247,15 +1015,38 @@ impl<'m> MatchupPre<'m> {
     }
 
     pub fn is_relevant(&self, a: &Analyzer) -> bool {
-        self.is_my_team_involed
-            || a.own_conference_team_ids.contains(&self.game.home_team().id)
-            || a.own_conference_team_ids.contains(&self.game.away_team().id)
+        let teams = [self.game.home_team().id, self.game.away_team().id];
+        if self.is_my_team_involed || teams.iter().any(|id| a.options.rivals.contains(id)) {
+            return true;
+        }
+        if !teams.iter().any(|id| a.own_conference_team_ids.contains(id)) {
+            return false;
+        }
+        if let Some(radius) = a.options.relevance_points {
+            let points = |id| a.api.get_points(id, false).unwrap_or(0) as i32;
+            let my_points = points(a.my_team.id);
+            let close = teams
+                .iter()
+                .any(|&id| a.own_conference_team_ids.contains(&id) && (points(id) - my_points).abs() <= radius as i32);
+            if !close {
+                return false;
+            }
+        }
+        if a.options.relevant_only {
+            teams.iter().any(|&id| {
+                let team = a.api.get_team_by_id(id);
+                simulation::clinch_status(a.api, team, &a.api.standings) == simulation::Clinch::Undecided
+            })
+        } else {
+            true
+        }
     }
 
     pub fn pick_winner(self, a: &'m Analyzer) -> Matchup<'m> {
         let home_team = self.game.home_team();
         let away_team = self.game.away_team();
 
+        let mut loser_odds = None;
         let ideal_loser = if self.is_my_team_involed {
             if a.my_team.id == home_team.id {
                 away_team
@@ -272,20 +1063,43 @@ impl<'m> MatchupPre<'m> {
             && !a.own_conference_team_ids.contains(&home_team.id)
         {
             away_team
-        } else {
-            if self.is_result {
-                simulation::pick_ideal_loser(a.api, a.my_team, &a.api.past_standings, self.game)
+        } else if !a.own_conference_team_ids.contains(&home_team.id)
+            && !a.own_conference_team_ids.contains(&away_team.id)
+        {
+            // Only games with a rival make it here, and the rival should lose.
+            if a.options.rivals.contains(&home_team.id) {
+                home_team
             } else {
-                simulation::pick_ideal_loser(a.api, a.my_team, &a.api.standings, self.game)
+                away_team
             }
+        } else if a.options.skip_simulations {
+            home_team
+        } else {
+            let odds = simulation::loser_odds(a.api, a.my_team, self.is_result, self.game, &a.options.params);
+            loser_odds = Some(odds);
+            odds.ideal_loser(self.game)
         };
 
+        let win_loss_odds =
+            if self.is_my_team_involed && !self.is_result && !self.game.is_final() && a.options.win_loss_odds {
+                Some(simulation::win_loss_odds(
+                    a.api,
+                    a.my_team,
+                    self.game,
+                    &a.options.params,
+                ))
+            } else {
+                None
+            };
+
         Matchup {
             game: self.game,
             is_result: self.is_result,
             is_my_team_involed: self.is_my_team_involed,
             ideal_loser,
             ideal_loser_in_conference: a.own_conference_team_ids.contains(&ideal_loser.id),
+            loser_odds,
+            win_loss_odds,
         }
     }
 }
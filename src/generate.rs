@@ -1,15 +1,212 @@
+use std::collections::BTreeMap;
 use std::iter;
 
-use crate::analysis::{Analysis, Api, Matchup, PlayoffMatchup, Seed};
+use ordinal::Ordinal;
+use serde::{Deserialize, Serialize};
+
+use crate::analysis::{
+    self, Analysis, Api, ConferenceSeeds, Matchup, Mood, PlayoffMatchup, PlayoffPosition, PlayoffSpot, Seed,
+};
 use crate::markdown::*;
-use crate::nhlapi::{self, schedule::Date, schedule::Game, standings::TeamRecord, teams::Team};
+use crate::nhlapi::{
+    self,
+    game::{Decisions, Starters},
+    schedule::Date,
+    schedule::Game,
+    standings::TeamRecord,
+    teams::{NameStyle, Team},
+};
 use crate::simulation;
 
+/// Part of the post, in the order given by `Options::sections`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PostSection {
+    /// Playoffs odds of the team, and the projections that go with them.
+    Odds,
+    LastNight,
+    /// Last night's results in a single table, in place of `LastNight`'s
+    /// split between our race and the games outside of town. Not shown by
+    /// default.
+    Scoreboard,
+    Standings,
+    /// Playoffs matchups if the season ended today.
+    Bracket,
+    Tonight,
+    Schedule,
+    /// Standings and matchups of the other conference, when it was seeded.
+    /// Shown right after the team's own with `both_conferences`.
+    OtherConference,
+    /// Playoffs odds of the whole conference, with `odds_appendix`.
+    OddsAppendix,
+    Disclaimer,
+}
+
+/// How the teams are named in the post, for the platform it is published on.
+/// The HTML rendering drops the flair links on its own.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TeamRenderStyle {
+    /// `MTL` with the flair of the team's subreddit, for reddit.
+    #[default]
+    RedditFlair,
+    /// `MTL`
+    PlainAbbrev,
+    /// `Montréal Canadiens`
+    FullName,
+    /// `MTL` linking to the team's subreddit, for the platforms without
+    /// flairs, such as Discord.
+    MarkdownLink,
+}
+
+impl TeamRenderStyle {
+    pub fn render(self, team: &Team) -> String {
+        match self {
+            TeamRenderStyle::RedditFlair => team.display(NameStyle::Flair),
+            TeamRenderStyle::PlainAbbrev => team.display(NameStyle::Abbrev),
+            TeamRenderStyle::FullName => team.display(NameStyle::Full),
+            // A team without a subreddit has nothing to link to.
+            TeamRenderStyle::MarkdownLink if team.subreddit.is_empty() => team.display(NameStyle::Abbrev),
+            TeamRenderStyle::MarkdownLink => format!("[{}](https://www.reddit.com/r/{})", team.abbrev, team.subreddit),
+        }
+    }
+}
+
+impl PostSection {
+    /// The sections shown by default, in order.
+    pub fn all() -> Vec<PostSection> {
+        vec![
+            PostSection::Odds,
+            PostSection::LastNight,
+            PostSection::Standings,
+            PostSection::Bracket,
+            PostSection::Tonight,
+            PostSection::Schedule,
+            PostSection::OtherConference,
+            PostSection::OddsAppendix,
+            PostSection::Disclaimer,
+        ]
+    }
+}
+
+/// Optional parts of the generated post.
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// Append the playoffs odds of every team in the conference to the post.
+    pub odds_appendix: bool,
+    /// Largest number of points behind the last wildcard spot for which a
+    /// team is still considered in the hunt.
+    pub hunt_points: u32,
+    /// Show the outcome of results with emoji instead of words.
+    pub mood_emoji: bool,
+    /// Tell how many of the remaining games must be won to reach these odds.
+    pub target_odds: Option<f64>,
+    /// Preview the next game after the schedule.
+    pub next_game_preview: bool,
+    /// List the three stars of the team's last game.
+    pub three_stars: bool,
+    /// Name the starting goalies of the team's next game, once published.
+    pub starting_goalies: bool,
+    /// Compare the simulated odds with a projection at the current points pace.
+    pub pace_baseline: bool,
+    /// Tell how the odds were computed under the disclaimer: the number of
+    /// seasons simulated, the model parameters and the date of the data.
+    pub odds_footer: bool,
+    /// List the most likely first round opponents under the playoffs matchups.
+    pub likely_opponents: bool,
+    /// Prefix the title and the team's name with the team's emoji.
+    pub team_emoji: bool,
+    /// Show the simulated final points of the team after its odds.
+    pub projected_points: bool,
+    /// List how many points the team is back of each playoffs spot under the
+    /// standings.
+    pub points_from_spots: bool,
+    /// Label the teams of the playoffs matchups with their bracket seed, such
+    /// as `A1` or `WC2`.
+    pub bracket_seeds: bool,
+    /// Show the season series of the team against each team of the standings.
+    /// Needs the past schedule.
+    pub season_series: bool,
+    /// Pad the columns of the tables so the raw markdown lines up.
+    pub pretty_tables: bool,
+    /// Number of teams shown below the wildcard line, the team itself aside.
+    pub chasers_shown: usize,
+    /// Usual points of the last playoffs spot, by conference name. The P-82
+    /// column of the conference's teams is flagged against it.
+    pub playoff_line_points: BTreeMap<String, u32>,
+    /// Sections of the post, in order. The title and headline always come first.
+    pub sections: Vec<PostSection>,
+    /// Show the standings and matchups of both conferences, each under its own
+    /// header. Needs the analysis to seed the other conference.
+    pub both_conferences: bool,
+    /// How the teams are named.
+    pub team_style: TeamRenderStyle,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            odds_appendix: false,
+            hunt_points: 6,
+            mood_emoji: false,
+            target_odds: None,
+            next_game_preview: false,
+            three_stars: false,
+            starting_goalies: false,
+            pace_baseline: false,
+            odds_footer: false,
+            likely_opponents: false,
+            team_emoji: false,
+            projected_points: false,
+            points_from_spots: false,
+            bracket_seeds: false,
+            season_series: false,
+            pretty_tables: false,
+            chasers_shown: 2,
+            playoff_line_points: BTreeMap::new(),
+            sections: PostSection::all(),
+            both_conferences: false,
+            team_style: TeamRenderStyle::RedditFlair,
+        }
+    }
+}
+
 pub struct MarkdownGenerator<'a> {
     api: &'a Api,
     an: &'a Analysis<'a>,
     schedule: &'a [Date],
+    /// Whether the schedule could be fetched, it is left out of the post otherwise.
+    schedule_available: bool,
+    /// Games of the team played so far this season, for the season series.
+    past_schedule: &'a [Date],
+    /// Decisions of the team's last game, only fetched for that game.
+    decisions: Option<&'a Decisions>,
+    /// Starting goalies of the team's next game, only fetched for that game.
+    starters: Option<&'a Starters>,
     team: &'a Team,
+    options: Options,
+}
+
+impl<'a> MarkdownGenerator<'a> {
+    pub fn with_past_schedule(mut self, past_schedule: &'a [Date]) -> Self {
+        self.past_schedule = past_schedule;
+        self
+    }
+
+    pub fn with_decisions(mut self, decisions: &'a Decisions) -> Self {
+        self.decisions = Some(decisions);
+        self
+    }
+
+    pub fn with_starters(mut self, starters: &'a Starters) -> Self {
+        self.starters = Some(starters);
+        self
+    }
+
+    pub fn with_schedule_available(mut self, schedule_available: bool) -> Self {
+        self.schedule_available = schedule_available;
+        self
+    }
 }
 
 impl MarkdownGenerator<'_> {
@@ -18,13 +215,35 @@ impl MarkdownGenerator<'_> {
             api,
             an,
             schedule,
+            schedule_available: true,
+            past_schedule: &[],
+            decisions: None,
+            starters: None,
             team,
+            options: Options::default(),
         }
     }
 
+    pub fn with_options(mut self, options: Options) -> Self {
+        self.options = options;
+        self
+    }
+
     fn fmt_team(&self, team: &nhlapi::Team) -> String {
-        let team = self.api.get_team_by_id(team.id);
-        format!("[](/r/{}){}", team.subreddit, team.abbrev)
+        let name = self.options.team_style.render(self.api.get_team_by_id(team.id));
+        match self.emoji() {
+            Some(emoji) if team.id == self.team.id => format!("{} {}", emoji, name),
+            _ => name,
+        }
+    }
+
+    /// Emoji of the team, if the post uses it.
+    fn emoji(&self) -> Option<&'static str> {
+        if self.options.team_emoji {
+            self.team.theme().map(|theme| theme.emoji)
+        } else {
+            None
+        }
     }
 
     fn fmt_vs(&self, home_team: &nhlapi::Team, away_team: &nhlapi::Team) -> String {
@@ -35,6 +254,37 @@ impl MarkdownGenerator<'_> {
         format!("{} ({})", self.fmt_team(&record.team), record.conference_rank)
     }
 
+    /// Streak of the team, flagged when the team is hot or cold.
+    fn fmt_streak(&self, record: &TeamRecord) -> String {
+        let streak = record.streak().unwrap_or_default();
+        if record.is_hot() {
+            format!("{} 🔥", streak)
+        } else if record.is_cold() {
+            format!("{} 🧊", streak)
+        } else {
+            streak
+        }
+    }
+
+    /// Points pace of the team, flagged against the playoffs line of its
+    /// conference: above it, within `PLAYOFF_LINE_MARGIN` points below it, or further.
+    fn fmt_pace(&self, record: &TeamRecord) -> String {
+        let conference = &self.api.get_team_by_id(record.team.id).conference.name;
+        let line = match self.options.playoff_line_points.get(conference) {
+            Some(&line) => line as f64,
+            None => return record.point_82(),
+        };
+        let pace = record.pace_82().round();
+        let flag = if pace >= line {
+            "✅"
+        } else if pace >= line - PLAYOFF_LINE_MARGIN as f64 {
+            "🟡"
+        } else {
+            "❌"
+        };
+        format!("{} {}", record.point_82(), flag)
+    }
+
     fn fmt_ot(&self, game: &Game) -> &str {
         if game.shootout() {
             "(SO)"
@@ -45,179 +295,964 @@ impl MarkdownGenerator<'_> {
         }
     }
 
-    fn make_result_table<'a>(&self, matchups: impl Iterator<Item = &'a Matchup<'a>>) -> Table {
-        let mut table = Table::new(&["Game", "Score", "Outcome"]);
+    fn fmt_mood(&self, mood: Mood) -> &str {
+        if self.options.mood_emoji {
+            mood.emoji()
+        } else {
+            mood.text()
+        }
+    }
+
+    fn make_result_table<'a>(&self, matchups: impl Iterator<Item = &'a Matchup<'a>>) -> Result<Table, TableError> {
+        let rows = matchups.map(|m| self.make_result_row(m));
+        Table::from_rows(&["Game", "Score", "Result"], rows)
+    }
+
+    fn make_result_row(&self, m: &Matchup) -> Vec<String> {
+        let (winner_score, loser_score) = if m.game.teams.home.score > m.game.teams.away.score {
+            (m.game.teams.home.score, m.game.teams.away.score)
+        } else {
+            (m.game.teams.away.score, m.game.teams.home.score)
+        };
+
+        vec![
+            self.fmt_vs(m.game.home_team(), m.game.away_team()),
+            // Trimmed when the game ended in regulation, a trailing space
+            // would break the bold of the scoreboard.
+            format!(
+                "{}-{} {} {}",
+                loser_score,
+                winner_score,
+                self.fmt_team(m.game.winner()),
+                self.fmt_ot(&m.game),
+            )
+            .trim_end()
+            .to_string(),
+            self.fmt_mood(m.mood()).to_string(),
+        ]
+    }
+
+    /// Every relevant result of last night in a single table, by game time,
+    /// with the team's own game in bold.
+    fn make_scoreboard_table(&self) -> Result<Table, TableError> {
+        let mut matchups: Vec<&Matchup> = self.an.my_result.iter().chain(&self.an.results).collect();
+        matchups.sort_by_key(|m| m.game.game_date);
+        let mut table = Table::new(&["Game", "Score", "Result"]);
         for m in matchups {
-            let (winner_score, loser_score) = if m.game.teams.home.score > m.game.teams.away.score {
-                (m.game.teams.home.score, m.game.teams.away.score)
+            table.add_row_styled(self.make_result_row(m), m.is_my_team_involed)?;
+        }
+        Ok(table)
+    }
+
+    fn make_game_table<'a>(&self, games: impl Iterator<Item = &'a Matchup<'a>>) -> Result<Table, TableError> {
+        let tz = self.team.timezone();
+        let rows = games.map(|m| {
+            vec![
+                self.fmt_vs(m.game.home_team(), m.game.away_team()),
+                self.fmt_team(m.cheer_for()),
+                m.game.local_time(&tz),
+            ]
+        });
+        Table::from_rows(
+            &["Game", "Cheer for", &format!("Time ({})", self.team.timezone_code())],
+            rows,
+        )
+    }
+
+    fn fmt_series(&self, game: &Game) -> String {
+        game.series_summary
+            .as_ref()
+            .map(|summary| summary.series_status_short.clone())
+            .unwrap_or_default()
+    }
+
+    fn make_series_result_table<'a>(&self, games: impl Iterator<Item = &'a Game>) -> Result<Table, TableError> {
+        let rows = games.map(|game| {
+            let (winner_score, loser_score) = if game.teams.home.score > game.teams.away.score {
+                (game.teams.home.score, game.teams.away.score)
             } else {
-                (m.game.teams.away.score, m.game.teams.home.score)
+                (game.teams.away.score, game.teams.home.score)
             };
 
-            table.add(&[
-                self.fmt_vs(m.game.home_team(), m.game.away_team()),
+            vec![
+                self.fmt_vs(game.home_team(), game.away_team()),
                 format!(
                     "{}-{} {} {}",
                     loser_score,
                     winner_score,
-                    self.fmt_team(m.game.winner()),
-                    self.fmt_ot(&m.game),
+                    self.fmt_team(game.winner()),
+                    self.fmt_ot(game),
                 ),
-                m.get_mood().to_string(),
-            ]);
-        }
-        table
+                self.fmt_series(game),
+            ]
+        });
+        Table::from_rows(&["Game", "Score", "Series"], rows)
     }
 
-    fn make_game_table<'a>(&self, games: impl Iterator<Item = &'a Matchup<'a>>) -> Table {
-        let mut table = Table::new(&["Game", "Cheer for", &format!("Time ({})", self.team.timezone_code())]);
+    fn make_series_game_table<'a>(&self, games: impl Iterator<Item = &'a Game>) -> Result<Table, TableError> {
         let tz = self.team.timezone();
-
-        for m in games {
-            table.add(&[
-                self.fmt_vs(m.game.home_team(), m.game.away_team()),
-                self.fmt_team(m.cheer_for()),
-                m.game.local_time(&tz),
-            ]);
-        }
-        table
+        let rows = games.map(|game| {
+            vec![
+                self.fmt_vs(game.home_team(), game.away_team()),
+                game.series_summary
+                    .as_ref()
+                    .map(|summary| format!("Game {}", summary.game_number))
+                    .unwrap_or_default(),
+                self.fmt_series(game),
+                game.local_time(&tz),
+            ]
+        });
+        Table::from_rows(
+            &["Game", "", "Series", &format!("Time ({})", self.team.timezone_code())],
+            rows,
+        )
     }
 
-    fn make_standings_table(&self, seeds: &[Seed], wildcard: bool) -> Table {
-        let mut table = Table::new(&["Place", "Team", "GP", "Record", "Points", "ROW", "L10", "P%", "P-82"]);
+    /// Table of standings, with a line drawn under the first `spots` teams
+    /// when some teams are below the cut. Only the first `chasers_shown` teams
+    /// below the line are shown, in italics and with their points from the cut.
+    pub fn make_standings_table(&self, seeds: &[Seed], spots: Option<usize>) -> Result<Table, TableError> {
+        let tiebreaker = self.an.params.tiebreaker_on(&self.api.games.date);
+        let mut headers = vec![
+            "Place",
+            "Team",
+            "GP",
+            "Record",
+            "Points",
+            tiebreaker.header(),
+            "L10",
+            "Streak",
+            "P%",
+            "P-82",
+        ];
+        if self.options.season_series {
+            headers.push("vs");
+        }
+        let mut table = Table::new(&headers);
+        // Points of the last team above the line, which the chasers are compared to.
+        let cut_points = spots
+            .filter(|&spots| spots > 0)
+            .and_then(|spots| seeds.get(spots - 1))
+            .map(|seed| seed.record.points);
+        let mut line_drawn = false;
         for (index, seed) in seeds.iter().enumerate() {
             let record = &seed.record;
+            let is_my_team = record.team.id == self.an.my_team.id;
+            let chaser = spots.map(|spots| index >= spots).unwrap_or(false);
+            if chaser && !is_my_team && index >= spots.unwrap_or(0) + self.options.chasers_shown {
+                continue;
+            }
 
-            if index == 2 && wildcard {
-                table.add(&["-", "-", "-", "-", "-", "-", "-", "-", "-"]);
+            if chaser && !line_drawn {
+                table.add(vec!["-"; headers.len()])?;
+                line_drawn = true;
             }
 
-            table.add(&[
+            let points = match cut_points {
+                Some(cut_points) if chaser => {
+                    format!("{} ({})", record.points, record.points as i32 - cut_points as i32)
+                }
+                _ => format!("{}", record.points),
+            };
+            let mut row = vec![
                 format!("{}", seed.seed),
                 self.fmt_team(&record.team),
                 format!("{}", record.games_played),
                 record.format(),
-                format!("{}", record.points),
-                format!("{}", record.row),
+                points,
+                format!("{}", tiebreaker.wins(record)),
                 record.last10().unwrap_or("".into()),
+                self.fmt_streak(record),
                 record.point_percent(),
-                record.point_82(),
-            ]);
+                self.fmt_pace(record),
+            ];
+            if self.options.season_series {
+                row.push(self.fmt_season_series(record.team.id));
+            }
+            if chaser && !is_my_team {
+                table.add_row_italic(&row)?;
+            } else {
+                table.add_row_styled(&row, is_my_team)?;
+            }
         }
-        table
+        Ok(table)
     }
 
-    fn make_playoffs_table(&self, playoffs: &[PlayoffMatchup]) -> Table {
+    fn make_playoffs_table(&self, playoffs: &[PlayoffMatchup]) -> Result<Table, TableError> {
         let mut table = Table::new(&["High seed", "", "Low seed"]);
         for pm in playoffs {
-            table.add(&[
-                self.fmt_seed(&pm.high_team),
-                "vs".to_string(),
-                self.fmt_seed(&pm.low_team),
-            ]);
-        }
-        table
-    }
-
-    fn make_schedule_table(&self) -> Table {
-        let mut table = Table::new(&[
-            "Away",
-            "",
-            "Home",
-            "Date",
-            &format!("Time ({})", self.team.timezone_code()),
-        ]);
+            let is_my_team = pm.high_team.team.id == self.an.my_team.id || pm.low_team.team.id == self.an.my_team.id;
+            let (high, low) = if self.options.bracket_seeds {
+                (
+                    format!("{} {}", pm.high_seed, self.fmt_seed(&pm.high_team)),
+                    format!("{} {}", pm.low_seed, self.fmt_seed(&pm.low_team)),
+                )
+            } else {
+                (self.fmt_seed(&pm.high_team), self.fmt_seed(&pm.low_team))
+            };
+            table.add_row_styled(&[high, "vs".to_string(), low], is_my_team)?;
+        }
+        Ok(table)
+    }
+
+    fn make_schedule_table(&self) -> Result<Table, TableError> {
         let tz = self.team.timezone();
+        let rows = self
+            .schedule
+            .iter()
+            .map(|x| &x.games)
+            .flatten()
+            .take(analysis::UPCOMING_GAMES)
+            .map(|game| {
+                let date = if self.an.must_win.contains_key(&game.game_pk) {
+                    format!("⭐ {}", game.local_date(&tz))
+                } else {
+                    game.local_date(&tz)
+                };
+                vec![
+                    self.fmt_team(game.away_team()),
+                    format!("at"),
+                    self.fmt_team(game.home_team()),
+                    date,
+                    game.local_time(&tz),
+                ]
+            });
+        Table::from_rows(
+            &[
+                "Away",
+                "",
+                "Home",
+                "Date",
+                &format!("Time ({})", self.team.timezone_code()),
+            ],
+            rows,
+        )
+    }
 
-        for game in self.schedule.iter().map(|x| &x.games).flatten().take(10) {
-            table.add(&[
-                self.fmt_team(game.away_team()),
-                format!("at"),
-                self.fmt_team(game.home_team()),
-                game.local_date(&tz),
-                game.local_time(&tz),
-            ]);
+    /// Table of the playoffs odds of every team in the conference.
+    pub fn make_odds_table(&self) -> Result<Table, TableError> {
+        let mut table = Table::new(&["Team", "GP", "Points", "Odds"]);
+        for (team_id, odds) in
+            simulation::odds_for_conference(self.api, self.an.my_team, false, self.an.params).unwrap_or_default()
+        {
+            let record = self.api.record_for(team_id, false).expect("team id not found");
+            table.add_row_styled(
+                &[
+                    self.fmt_team(&record.team),
+                    format!("{}", record.games_played),
+                    format!("{}", record.points),
+                    format!("{:.1}%", odds * 100.0),
+                ],
+                team_id == self.an.my_team.id,
+            )?;
         }
-        table
+        Ok(table)
     }
 
-    pub fn markdown(&self) -> Document {
-        let mut doc = Document::new();
-        doc.add(H1::new("Playoffs race!"));
+    /// Wins, regulation losses and overtime losses of the team against
+    /// `opponent_id` so far this season, from the past schedule.
+    fn season_series(&self, opponent_id: u32) -> (u32, u32, u32) {
+        let my_team_id = self.an.my_team.id;
+        let (mut wins, mut losses, mut ot) = (0, 0, 0);
+        for past_game in self.past_schedule.iter().flat_map(|x| &x.games) {
+            let teams = [past_game.home_team().id, past_game.away_team().id];
+            if !teams.contains(&my_team_id) || !teams.contains(&opponent_id) {
+                continue;
+            }
+            if past_game.winner().id == my_team_id {
+                wins += 1;
+            } else if past_game.overtime() {
+                ot += 1;
+            } else {
+                losses += 1;
+            }
+        }
+        (wins, losses, ot)
+    }
 
-        let today_odds = simulation::odds_for_team(self.api, self.an.my_team, false);
+    /// Season series of the team against `opponent_id`, `—` if they haven't
+    /// played yet, and blank for the team itself.
+    fn fmt_season_series(&self, opponent_id: u32) -> String {
+        if opponent_id == self.an.my_team.id {
+            return String::new();
+        }
+        match self.season_series(opponent_id) {
+            (0, 0, 0) => "—".to_string(),
+            (wins, losses, ot) => format!("{}-{}-{}", wins, losses, ot),
+        }
+    }
 
-        doc.add(Paragraph::new(format!(
-            "Playoffs odds today: {:.1}%",
-            today_odds * 100.0
-        )));
+    /// Paragraph about the next game: the opponent's record, the season series
+    /// so far and where the game is played.
+    pub fn make_next_game_preview(&self) -> Option<String> {
+        let game = self.schedule.iter().flat_map(|x| &x.games).next()?;
+        let my_team_id = self.an.my_team.id;
+        let opponent = if game.home_team().id == my_team_id {
+            game.away_team()
+        } else {
+            game.home_team()
+        };
+        let record = self.api.record_for(opponent.id, false).expect("team id not found");
+        let tz = self.team.timezone();
+        let venue = &self.api.get_team_by_id(game.home_team().id).venue.name;
 
-        //
-        // Last night
-        //
-        doc.add(H2::new("Last night's race"));
+        let (wins, losses, ot) = self.season_series(opponent.id);
+        let series = if wins + losses + ot == 0 {
+            "First meeting of the season.".to_string()
+        } else {
+            format!("Season series so far: {}-{}-{}.", wins, losses, ot)
+        };
 
-        doc.add(List::from(&["Our race:"]));
-        if let Some(my_result) = &self.an.my_result {
-            doc.add(self.make_result_table(iter::once(my_result)));
+        let mut preview = format!(
+            "Next game: {}, {} at {} ({}), {}. {} is {} with {} points. {}",
+            self.fmt_vs(game.home_team(), game.away_team()),
+            game.local_date(&tz),
+            game.local_time(&tz),
+            self.team.timezone_code(),
+            venue,
+            self.fmt_team(opponent),
+            record.format(),
+            record.points,
+            series
+        );
+        if let Some(starters) = self.make_starters(game) {
+            preview.push(' ');
+            preview.push_str(&starters);
+        }
+        Some(preview)
+    }
+
+    /// Starting goalies of `game`, the team's first, if they were fetched and
+    /// published.
+    fn make_starters(&self, game: &Game) -> Option<String> {
+        if !self.options.starting_goalies {
+            return None;
+        }
+        let starters = self.starters.filter(|s| s.game_pk == game.game_pk)?;
+        let (ours, theirs) = if game.home_team().id == self.an.my_team.id {
+            (&starters.home, &starters.away)
         } else {
-            doc.add(Paragraph::new("Nothing"));
+            (&starters.away, &starters.home)
+        };
+        let label = if starters.confirmed { "Starters" } else { "Probable" };
+        Some(format!("{}: {} vs {}.", label, ours.full_name, theirs.full_name))
+    }
+
+    fn make_likely_opponents(&self) -> List {
+        let opponents = simulation::first_round_opponents(self.api, self.an.my_team, self.an.params);
+        if opponents.is_empty() {
+            return List::from(&["None, the playoffs are out of reach."]);
         }
+        opponents
+            .iter()
+            .take(3)
+            .map(|&(team_id, odds)| {
+                format!(
+                    "{}: {:.1}%",
+                    self.options.team_style.render(self.api.get_team_by_id(team_id)),
+                    odds * 100.0
+                )
+            })
+            .into()
+    }
 
-        doc.add(List::from(&["Outside of town"]));
-        if self.an.results.is_empty() {
-            doc.add(Paragraph::new("Nothing"));
+    fn make_pace_baseline(&self) -> String {
+        let projection = simulation::points_pace_projection(self.api, self.an.my_team);
+        format!(
+            "Naive pace says: {} ({})",
+            if projection.makes_playoffs { "IN" } else { "OUT" },
+            Ordinal(projection.rank)
+        )
+    }
+
+    fn make_games_in_hand(&self) -> Option<String> {
+        let games = |n: i32| {
+            if n.abs() == 1 {
+                "1 game".to_string()
+            } else {
+                format!("{} games", n.abs())
+            }
+        };
+        let sentences: Vec<_> = self
+            .an
+            .games_in_hand(self.an.my_team.id)
+            .into_iter()
+            .map(|g| {
+                let rival = &self.api.get_team_by_id(g.rival.team.id).abbrev;
+                if g.games > 0 {
+                    format!("You have {} in hand on {}.", games(g.games), rival)
+                } else if g.games < 0 {
+                    format!("{} has {} in hand on you.", rival, games(g.games))
+                } else {
+                    format!("No games in hand on {}.", rival)
+                }
+            })
+            .collect();
+        if sentences.is_empty() {
+            None
         } else {
-            doc.add(self.make_result_table(self.an.results.iter()));
+            Some(sentences.join(" "))
         }
+    }
 
-        //
-        // Standings
-        //
-        doc.add(H2::new("Standings"));
-        doc.add(self.make_standings_table(&self.an.own_division_seed, false));
-        doc.add(self.make_standings_table(&self.an.other_division_seed, false));
-        doc.add(self.make_standings_table(&self.an.wildcard_seed, true));
+    /// Points the team is back of each playoffs spot, such as `3rd in
+    /// division: -2 (you hold it); 1st WC: +1; 2nd WC: +4.`
+    fn make_points_from_spots(&self) -> Option<String> {
+        let parts: Vec<_> = self
+            .an
+            .points_from_spots
+            .iter()
+            .map(|(&spot, &points)| {
+                let name = match spot {
+                    PlayoffSpot::Division(n) => format!("{} in division", Ordinal(n)),
+                    PlayoffSpot::Wildcard(n) => format!("{} WC", Ordinal(n)),
+                };
+                let holder = self.an.spot_holder(spot).map(|r| r.team.id);
+                if holder == Some(self.an.my_team.id) {
+                    format!("{}: {:+} (you hold it)", name, points)
+                } else {
+                    format!("{}: {:+}", name, points)
+                }
+            })
+            .collect();
+        if parts.is_empty() {
+            None
+        } else {
+            Some(format!("{}.", parts.join("; ")))
+        }
+    }
 
-        //
-        // Playoffs matchups
-        //
-        doc.add(H2::new("Playoffs matchups"));
-        doc.add(self.make_playoffs_table(&self.an.playoffs));
+    /// Score of the team's game if it is being played, such as
+    /// `LIVE: 2-1 MTL, 2nd period`.
+    pub fn make_live_block(&self) -> Option<String> {
+        let game = self.an.my_game.as_ref()?.game;
+        if !game.is_live() {
+            return None;
+        }
 
-        //
-        // Tonight
-        //
-        doc.add(H2::new("Tonight's race"));
+        let (home, away) = (&game.teams.home, &game.teams.away);
+        let score = if home.score == away.score {
+            format!("{}-{}", home.score, away.score)
+        } else {
+            let (leader, trailer) = if home.score > away.score {
+                (home, away)
+            } else {
+                (away, home)
+            };
+            format!(
+                "{}-{} {}",
+                leader.score,
+                trailer.score,
+                self.api.get_team_by_id(leader.team.id).display(NameStyle::Abbrev)
+            )
+        };
 
-        doc.add(List::from(&["Our race:"]));
-        if let Some(my_game) = &self.an.my_game {
-            doc.add(self.make_game_table(iter::once(my_game)));
+        let period = game.linescore.current_period;
+        let state = if game.shootout() {
+            "shootout".to_string()
+        } else if game.linescore.intermission_info.in_intermission {
+            format!("{} intermission", Ordinal(period))
+        } else if game.overtime() {
+            "overtime".to_string()
         } else {
-            doc.add(Paragraph::new("Nothing"));
+            format!("{} period", Ordinal(period))
+        };
+        Some(format!("LIVE: {}, {}", score, state))
+    }
+
+    fn make_three_stars(&self) -> Option<String> {
+        if !self.options.three_stars {
+            return None;
+        }
+        let [first, second, third] = self.decisions?.three_stars()?;
+        Some(format!(
+            "Three stars: 1. {}, 2. {}, 3. {}",
+            first.full_name, second.full_name, third.full_name
+        ))
+    }
+
+    /// Legend of the must-win games starred in the schedule, if any.
+    fn make_must_win_legend(&self) -> Option<String> {
+        let biggest = self
+            .an
+            .must_win
+            .values()
+            .cloned()
+            .fold(None, |max: Option<f64>, swing| {
+                Some(max.map_or(swing, |max| max.max(swing)))
+            })?;
+        Some(format!(
+            "⭐ Must-win games: a win instead of a loss is worth up to {:+.1}% in playoffs odds.",
+            biggest * 100.0
+        ))
+    }
+
+    fn make_wins_needed(&self, target_odds: f64) -> Option<String> {
+        let record = self
+            .api
+            .record_for(self.an.my_team.id, false)
+            .expect("team id not found");
+        let remaining = simulation::remaining_games(record);
+        if remaining == 0 {
+            return None;
         }
 
-        doc.add(List::from(&["Outside of town"]));
-        if self.an.games.is_empty() {
-            doc.add(Paragraph::new("Nothing"));
+        let percent = target_odds * 100.0;
+        Some(
+            match simulation::wins_needed(self.api, self.an.my_team, target_odds, self.an.params) {
+                Some(0) => format!(
+                    "Even losing the last {} games leaves you {:.0}%+ to make it.",
+                    remaining, percent
+                ),
+                Some(wins) => format!(
+                    "Win {} of your last {} and you're {:.0}%+ to make it.",
+                    wins, remaining, percent
+                ),
+                None => format!(
+                    "Even winning the last {} games won't get you to {:.0}% odds.",
+                    remaining, percent
+                ),
+            },
+        )
+    }
+
+    /// One line summary of the race for the team, for the title of the post.
+    pub fn make_summary_line(&self) -> String {
+        let team = self.an.my_team;
+        let odds = simulation::odds_for_team(self.api, team, false, self.an.params).unwrap_or_default();
+        let record = self.api.record_for(team.id, false).expect("team id not found");
+        format!(
+            "{}: {:.0}% to make playoffs ({:+} on the line, {} GP left)",
+            team.abbrev,
+            odds * 100.0,
+            self.an.points_vs_line(team.id).unwrap_or(0),
+            simulation::remaining_games(record)
+        )
+    }
+
+    fn headline(&self) -> &str {
+        match self.an.playoff_position(self.an.my_team.id, self.options.hunt_points) {
+            Some(PlayoffPosition::DivisionTop3) => "In the driver's seat.",
+            Some(PlayoffPosition::Wildcard) => "Holding on to a wildcard spot.",
+            Some(PlayoffPosition::InHunt) => "Fighting for a spot.",
+            Some(PlayoffPosition::OutOfRace) | None => "Long odds ahead.",
+        }
+    }
+
+    /// Post for the playoffs, once the race is over: the series instead of the odds.
+    fn make_playoffs_markdown(&self) -> Result<Document, TableError> {
+        let mut doc = Document::new();
+        doc.set_pretty(self.options.pretty_tables);
+        doc.add(H1::new("Playoffs!"));
+        doc.add(Paragraph::new("Regular season complete."));
+
+        doc.add(H2::new("Last night's games"));
+        match &self.api.results {
+            Some(results) if results.games.iter().any(|g| g.is_playoffs()) => {
+                doc.add(self.make_series_result_table(results.games.iter().filter(|g| g.is_playoffs()))?);
+            }
+            Some(_) => doc.add(nothing()),
+            None => doc.add(Paragraph::new("Last night's results are not available.")),
+        }
+
+        doc.add(H2::new("Tonight's games"));
+        if self.api.games.games.iter().any(|g| g.is_playoffs()) {
+            doc.add(self.make_series_game_table(self.api.games.games.iter().filter(|g| g.is_playoffs()))?);
         } else {
-            doc.add(self.make_game_table(self.an.games.iter()));
+            doc.add(nothing());
+        }
+
+        Ok(doc)
+    }
+
+    fn make_odds_section(&self) -> Section {
+        let mut section = Section::untitled();
+        let (today_odds, method) = simulation::odds_and_method(self.api, self.an.my_team, false, self.an.params)
+            .unwrap_or((0.0, simulation::OddsMethod::Analytic));
+        let mut notes = vec![];
+        // Without yesterday's standings there is nothing to compare to.
+        if let Some(past_odds) = simulation::odds_for_team(self.api, self.an.my_team, true, self.an.params) {
+            notes.push(format!("{:+.1} since yesterday", (today_odds - past_odds) * 100.0));
+        }
+        notes.push(method.text().to_string());
+
+        section.add(Paragraph::new(format!(
+            "Playoffs odds today: {:.1}% ({})",
+            today_odds * 100.0,
+            notes.join(", ")
+        )));
+        if self.options.projected_points {
+            let stats = simulation::expected_points(self.api, self.an.my_team, self.an.params);
+            section.add(Paragraph::new(format!(
+                "Projected final: {:.1} ± {:.1} pts",
+                stats.mean, stats.std_dev
+            )));
+        }
+        if self.options.pace_baseline {
+            section.add(Paragraph::new(self.make_pace_baseline()));
         }
+        section
+    }
 
-        //
-        // Schedule
-        //
-        doc.add(H2::new("Upcoming schedule"));
-        doc.add(self.make_schedule_table());
+    fn make_last_night_section(&self) -> Result<Section, TableError> {
+        let mut section = Section::new("Last night's race");
+        if !self.an.results_available {
+            section.add(Paragraph::new("Last night's results are not available."));
+            return Ok(section);
+        }
+
+        section.add(List::from(&["Our race:"]));
+        match &self.an.my_result {
+            Some(my_result) => {
+                section.add(self.make_result_table(iter::once(my_result))?);
+                section.add_opt(self.make_three_stars().map(Paragraph::new));
+            }
+            None => section.add(nothing()),
+        }
+
+        section.add(List::from(&["Outside of town"]));
+        section.add_or(non_empty(self.make_result_table(self.an.results.iter())?), nothing());
+        Ok(section)
+    }
+
+    /// The other conference, when it is shown next to the team's own.
+    fn both_conferences(&self) -> Option<&ConferenceSeeds<'_>> {
+        match &self.an.other_conference {
+            Some(other) if self.options.both_conferences => Some(other),
+            _ => None,
+        }
+    }
+
+    fn make_scoreboard_section(&self) -> Result<Section, TableError> {
+        let mut section = Section::new("Last night's scoreboard");
+        if !self.an.results_available {
+            section.add(Paragraph::new("Last night's results are not available."));
+        } else if self.an.my_result.is_none() && self.an.results.is_empty() {
+            section.add(nothing());
+        } else {
+            section.add(self.make_scoreboard_table()?);
+        }
+        Ok(section)
+    }
+
+    fn make_standings_section(&self) -> Result<Section, TableError> {
+        let mut section = match self.both_conferences() {
+            Some(_) => Section::new(format!("{} Conference", self.team.conference.name)),
+            None => Section::new("Standings"),
+        };
+        section.add(self.make_standings_table(&self.an.own_division_seed, None)?);
+        for seeds in &self.an.other_division_seeds {
+            section.add(self.make_standings_table(seeds, None)?);
+        }
+        section.add(self.make_standings_table(&self.an.wildcard_seed, Some(self.an.wildcard_spots))?);
+        section.add_opt(self.make_games_in_hand().map(Paragraph::new));
+        if self.options.points_from_spots {
+            section.add_opt(self.make_points_from_spots().map(Paragraph::new));
+        }
+        Ok(section)
+    }
 
-        //
-        // Disclaimer
-        //
-        doc.add(HR);
-        doc.add(H3::new("Disclaimer"));
-        doc.add(Paragraph::new(DISCLAIMER));
+    fn make_bracket_section(&self) -> Result<Section, TableError> {
+        // Both conferences are shown under their own header instead.
+        let mut section = match self.both_conferences() {
+            Some(_) => Section::untitled(),
+            None => Section::new("Playoffs matchups"),
+        };
+        section.add(self.make_playoffs_table(&self.an.playoffs)?);
+        if self.options.likely_opponents {
+            section.add(Paragraph::new("Most likely first round opponents:"));
+            section.add(self.make_likely_opponents());
+        }
+        Ok(section)
+    }
+
+    /// Standings and matchups of the other conference, if it was seeded.
+    fn make_other_conference_section(&self) -> Result<Option<Section>, TableError> {
+        let other = match &self.an.other_conference {
+            Some(other) => other,
+            None => return Ok(None),
+        };
+        let mut section = match self.both_conferences() {
+            Some(_) => Section::new(format!("{} Conference", other.conference.name)),
+            None => Section::new(format!("Appendix: {} Conference", other.conference.name)),
+        };
+        for seeds in &other.division_seeds {
+            section.add(self.make_standings_table(seeds, None)?);
+        }
+        section.add(self.make_standings_table(&other.wildcard_seed, Some(other.wildcard_spots))?);
+        section.add(self.make_playoffs_table(&other.playoffs)?);
+        Ok(Some(section))
+    }
+
+    fn make_tonight_section(&self) -> Result<Section, TableError> {
+        let mut section = Section::new("Tonight's race");
+
+        section.add(List::from(&["Our race:"]));
+        match &self.an.my_game {
+            Some(my_game) => {
+                section.add(self.make_game_table(iter::once(my_game))?);
+                section.add_opt(my_game.win_loss_odds.map(|(win, loss)| {
+                    Paragraph::new(format!(
+                        "Playoffs odds after tonight's game: **Win: {:.1}% / Loss: {:.1}%**",
+                        win * 100.0,
+                        loss * 100.0
+                    ))
+                }));
+                section.add_opt(self.make_starters(my_game.game).map(Paragraph::new));
+                section.add_opt(self.make_live_block().map(Paragraph::new));
+            }
+            None => section.add(nothing()),
+        }
+
+        section.add(List::from(&["Outside of town"]));
+        section.add_or(non_empty(self.make_game_table(self.an.games.iter())?), nothing());
+
+        if !self.an.key_games.is_empty() {
+            section.add(List::from(&["Around the league:"]));
+            section.add(self.make_key_games());
+        }
+        Ok(section)
+    }
+
+    /// How the team to cheer for was picked in every out of town game of last
+    /// night and tonight, with the odds of both results when they were
+    /// simulated, such as `CAR@PIT: PIT loss → 62.1%, CAR loss → 61.8% ⇒ cheer CAR`.
+    pub fn make_explanations(&self) -> Vec<String> {
+        self.an
+            .results
+            .iter()
+            .chain(&self.an.games)
+            .map(|m| {
+                let abbrev = |team: &nhlapi::Team| &self.api.get_team_by_id(team.id).abbrev;
+                let (home, away) = (abbrev(m.game.home_team()), abbrev(m.game.away_team()));
+                let odds = match m.loser_odds {
+                    Some(odds) => format!(
+                        "{} loss → {:.1}%, {} loss → {:.1}%",
+                        home,
+                        odds.home_loses * 100.0,
+                        away,
+                        odds.away_loses * 100.0
+                    ),
+                    None => "not simulated".to_string(),
+                };
+                format!("{}@{}: {} ⇒ cheer {}", away, home, odds, abbrev(m.cheer_for()))
+            })
+            .collect()
+    }
+
+    /// Tonight's games that weigh the most on the playoffs races of the league.
+    fn make_key_games(&self) -> List {
+        self.an
+            .key_games
+            .iter()
+            .map(|(game, importance)| {
+                format!(
+                    "{} at {}: {:.1}% of playoffs odds at stake",
+                    self.fmt_team(game.away_team()),
+                    self.fmt_team(game.home_team()),
+                    importance * 100.0
+                )
+            })
+            .into()
+    }
+
+    fn make_schedule_section(&self) -> Result<Section, TableError> {
+        let mut section = Section::new("Upcoming schedule");
+        if self.schedule_available {
+            let games = self
+                .schedule
+                .iter()
+                .flat_map(|d| &d.games)
+                .take(analysis::UPCOMING_GAMES);
+            section.add_opt(nhlapi::schedule::format_date_range(games, &self.team.timezone()).map(Paragraph::new));
+            section.add_or(non_empty(self.make_schedule_table()?), nothing());
+            section.add_opt(self.make_must_win_legend().map(Paragraph::new));
+        } else {
+            section.add(Paragraph::new("The upcoming schedule is not available."));
+        }
+        if self.options.next_game_preview {
+            section.add_opt(self.make_next_game_preview().map(Paragraph::new));
+        }
+        section.add_opt(
+            self.options
+                .target_odds
+                .and_then(|odds| self.make_wins_needed(odds))
+                .map(Paragraph::new),
+        );
+        Ok(section)
+    }
+
+    fn make_odds_appendix_section(&self) -> Result<Section, TableError> {
+        let mut section = Section::new("Appendix: conference playoffs odds");
+        section.add(self.make_odds_table()?);
+        Ok(section)
+    }
+
+    fn make_disclaimer_section(&self) -> Section {
+        let mut section = Section::untitled();
+        section.add(HR);
+        section.add(H3::new("Disclaimer"));
+        section.add(Paragraph::new(DISCLAIMER));
+        if self.options.odds_footer {
+            section.add(Paragraph::new(self.make_odds_footer()));
+        }
+        section
+    }
+
+    /// How the team's odds were computed, so that readers can reproduce them.
+    pub fn make_odds_footer(&self) -> String {
+        let params = self.an.params;
+        let as_of = self.api.games.date.format("%b %-d");
+        match simulation::odds_method(self.api, self.an.my_team, &self.api.standings, params) {
+            simulation::OddsMethod::Analytic => format!(
+                "Odds from the clinch status and the points pace as of {}, without simulations.",
+                as_of
+            ),
+            simulation::OddsMethod::Simulation => {
+                let rates = if params.form_weight <= 0.0 {
+                    "season-long win/OT/loss rates".to_string()
+                } else if params.form_weight >= 1.0 {
+                    "win/OT/loss rates of the last 10 games".to_string()
+                } else {
+                    format!(
+                        "win/OT/loss rates weighing the last 10 games at {:.0}%",
+                        params.form_weight * 100.0
+                    )
+                };
+                let mut notes = vec![];
+                if params.streak_weight > 0.0 {
+                    notes.push(format!("a streak weight of {}", params.streak_weight));
+                }
+                if params.prior_games > 0.0 {
+                    notes.push(format!(
+                        "{} games at the league average added to every record",
+                        params.prior_games
+                    ));
+                }
+                if params.back_to_back_penalty > 0.0 {
+                    notes.push(format!(
+                        "a back-to-back penalty of {:.0}%",
+                        params.back_to_back_penalty * 100.0
+                    ));
+                }
+                match params.pinned.len() {
+                    0 => {}
+                    1 => notes.push("1 pinned result".to_string()),
+                    n => notes.push(format!("{} pinned results", n)),
+                }
+                let notes = if notes.is_empty() {
+                    String::new()
+                } else {
+                    format!(", with {}", notes.join(" and "))
+                };
+                format!(
+                    "Simulated {} seasons using {} as of {}{}.",
+                    fmt_thousands(params.times()),
+                    rates,
+                    as_of,
+                    notes
+                )
+            }
+        }
+    }
+
+    pub fn markdown(&self) -> Result<Document, TableError> {
+        if self.api.is_playoffs() {
+            return self.make_playoffs_markdown();
+        }
+
+        let mut doc = Document::new();
+        doc.set_pretty(self.options.pretty_tables);
+        match self.emoji() {
+            Some(emoji) => doc.add(H1::new(format!("{} Playoffs race!", emoji))),
+            None => doc.add(H1::new("Playoffs race!")),
+        }
+        doc.add(Paragraph::new(self.headline()));
+
+        // With both conferences, the other one goes right after the team's own.
+        let sections = &self.options.sections;
+        let beside = match self.both_conferences() {
+            Some(_) if sections.contains(&PostSection::Bracket) => Some(PostSection::Bracket),
+            Some(_) => Some(PostSection::Standings),
+            None => None,
+        };
+        for &kind in sections {
+            match kind {
+                PostSection::Odds => doc.add_section(self.make_odds_section()),
+                PostSection::LastNight => doc.add_section(self.make_last_night_section()?),
+                PostSection::Scoreboard => doc.add_section(self.make_scoreboard_section()?),
+                PostSection::Standings => doc.add_section(self.make_standings_section()?),
+                PostSection::Bracket => doc.add_section(self.make_bracket_section()?),
+                PostSection::Tonight => doc.add_section(self.make_tonight_section()?),
+                PostSection::Schedule => doc.add_section(self.make_schedule_section()?),
+                PostSection::OtherConference if beside.is_none() => {
+                    if let Some(section) = self.make_other_conference_section()? {
+                        doc.add_section(section);
+                    }
+                }
+                PostSection::OtherConference => {}
+                PostSection::OddsAppendix if self.options.odds_appendix => {
+                    doc.add_section(self.make_odds_appendix_section()?)
+                }
+                PostSection::OddsAppendix => {}
+                PostSection::Disclaimer => doc.add_section(self.make_disclaimer_section()),
+            }
+            if Some(kind) == beside && sections.contains(&PostSection::OtherConference) {
+                if let Some(section) = self.make_other_conference_section()? {
+                    doc.add_section(section);
+                }
+            }
+        }
+
+        Ok(doc)
+    }
+}
+
+/// Table of the playoffs odds of every team in the league, as computed by
+/// `simulation::odds_for_league`.
+pub fn make_league_odds_table(api: &Api, odds: &[(u32, f64)]) -> Result<Table, TableError> {
+    let rows = odds.iter().map(|&(team_id, odds)| {
+        let team = api.get_team_by_id(team_id);
+        let record = api.record_for(team_id, false).expect("team id not found");
+        vec![
+            team.display(NameStyle::Abbrev),
+            team.conference.name.clone(),
+            format!("{}", record.games_played),
+            format!("{}", record.points),
+            format!("{:.1}%", odds * 100.0),
+        ]
+    });
+    Table::from_rows(&["Team", "Conference", "GP", "Points", "Odds"], rows)
+}
+
+/// Points below the playoffs line for which a team's pace is still close.
+const PLAYOFF_LINE_MARGIN: u32 = 4;
+
+/// A count with commas between the thousands, such as `50,000`.
+fn fmt_thousands(count: u32) -> String {
+    let digits = count.to_string();
+    let mut out = String::new();
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(digit);
+    }
+    out
+}
+
+/// Placeholder for a part of the post with nothing to show.
+fn nothing() -> Paragraph {
+    Paragraph::new("Nothing")
+}
 
-        doc
+/// `table` unless it has no rows, so that an empty table is not rendered as
+/// a header alone.
+fn non_empty(table: Table) -> Option<Table> {
+    if table.is_empty() {
+        None
+    } else {
+        Some(table)
     }
 }
 
@@ -0,0 +1,8 @@
+#![allow(dead_code)]
+
+pub mod analysis;
+pub mod generate;
+pub mod markdown;
+pub mod nhlapi;
+pub mod simulation;
+pub mod state;
@@ -1,21 +1,28 @@
-#![allow(dead_code)]
-
-mod analysis;
-mod generate;
-mod markdown;
-mod nhlapi;
-mod simulation;
-
-use std::fs::File;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io;
 use std::io::prelude::*;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
 
 use chrono::{Datelike, Local, NaiveDate, TimeZone};
-use failure::Error;
+use failure::{bail, format_err, Error};
 use ordinal::Ordinal;
 use serde::{Deserialize, Serialize};
 
-use analysis::{Analyzer, Api};
-use generate::MarkdownGenerator;
+use playoffsbot::analysis::{self, Analyzer, Api};
+use playoffsbot::generate::{self, MarkdownGenerator};
+use playoffsbot::nhlapi::{
+    self,
+    teams::{NameStyle, Team},
+};
+use playoffsbot::simulation::{self, Event};
+use playoffsbot::state::{self, State};
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Config {
@@ -27,6 +34,679 @@ pub struct Config {
     playoffs: Vec<String>,
     #[serde(default)]
     test: bool,
+    /// Don't post when the markdown fails validation, instead of only
+    /// logging its issues.
+    #[serde(default)]
+    strict: bool,
+    /// NHL API to download the data from, `statsapi` or `web`. The three
+    /// stars and the season bounds still come from statsapi.
+    #[serde(default)]
+    backend: nhlapi::Backend,
+    #[serde(default)]
+    odds_appendix: bool,
+    #[serde(default)]
+    show_other_conference: bool,
+    /// Show both conferences under their own headers instead of the own conference only.
+    #[serde(default)]
+    both_conferences: bool,
+    /// Number of teams processed at the same time. Posting to reddit is
+    /// still done by one team at a time.
+    #[serde(default = "default_team_threads")]
+    team_threads: usize,
+    /// File where the summary of every run is written as JSON, for monitoring.
+    #[serde(default)]
+    summary_file: Option<PathBuf>,
+    /// Usual points of the last playoffs spot by conference name, such as
+    /// `{"Eastern": 96}`, to flag the points pace of the teams against.
+    #[serde(default)]
+    playoff_line_points: BTreeMap<String, u32>,
+    /// Sections of the post, in order.
+    #[serde(default = "default_sections")]
+    sections: Vec<generate::PostSection>,
+    /// Teams whose games are always shown, even outside of the conference.
+    #[serde(default)]
+    rivals: Vec<String>,
+    #[serde(default)]
+    relevant_only: bool,
+    /// Only show the conference games involving a team within this many
+    /// points of the team, rivals aside.
+    #[serde(default)]
+    relevance_points: Option<u32>,
+    #[serde(default = "default_hunt_points")]
+    hunt_points: u32,
+    /// Number of teams shown below the wildcard line in the standings.
+    #[serde(default = "default_chasers_shown")]
+    chasers_shown: usize,
+    #[serde(default)]
+    mood_emoji: bool,
+    #[serde(default)]
+    form_weight: f64,
+    /// How much a winning or losing streak of 3 games or more sways the simulation.
+    #[serde(default)]
+    streak_weight: f64,
+    /// Games at the league's average rates added to every team's record in
+    /// the simulation, to temper the odds early in the season. 0 for none.
+    #[serde(default)]
+    prior_games: f64,
+    /// Seed of the simulations, to get the same odds every run on the same
+    /// data. Fresh random numbers every run if missing.
+    #[serde(default)]
+    seed: Option<u64>,
+    /// How much less likely a team is to win on the second night of a
+    /// back-to-back, from 0 (no effect) to 1.
+    #[serde(default)]
+    back_to_back_penalty: f64,
+    /// Whether the odds are simulated, computed analytically, or chosen
+    /// depending on how decided the race is.
+    #[serde(default)]
+    odds_mode: simulation::OddsMode,
+    /// Whether the simulations cover the own conference only or the whole
+    /// league. The playoffs odds are the same either way.
+    #[serde(default)]
+    simulation_scope: simulation::SimulationScope,
+    /// Whether the standings are ranked by points or by points percentage,
+    /// for the seasons where the teams play uneven numbers of games.
+    #[serde(default)]
+    sort_basis: simulation::SortBasis,
+    /// Wins that break ties in points, `rw` or `row`. The NHL's rule of the
+    /// season if missing.
+    #[serde(default)]
+    tiebreaker: Option<simulation::Tiebreaker>,
+    /// Results forced on some games by game pk, for the games that the
+    /// schedule does not know are decided yet.
+    #[serde(default)]
+    pinned_results: BTreeMap<u64, simulation::PinnedResult>,
+    /// Swing of the playoffs odds between a win and a loss from which an
+    /// upcoming game is starred as a must-win.
+    #[serde(default)]
+    must_win_swing: Option<f64>,
+    /// Number of tonight's games around the league to highlight for their
+    /// weight on the playoffs races. Every game of the night is simulated, 0
+    /// leaves them out.
+    #[serde(default)]
+    around_the_league: usize,
+    /// Show the team's playoffs odds after a win and after a loss in its game
+    /// tonight, simulating both.
+    #[serde(default)]
+    win_loss_odds: bool,
+    /// Playoffs odds for which the post tells how many wins are needed.
+    #[serde(default)]
+    target_odds: Option<f64>,
+    #[serde(default)]
+    next_game_preview: bool,
+    #[serde(default)]
+    three_stars: bool,
+    /// Name the starting goalies of the team's next game in the preview and
+    /// under tonight's game, once the teams publish them.
+    #[serde(default)]
+    starting_goalies: bool,
+    #[serde(default)]
+    pace_baseline: bool,
+    /// Tell under the disclaimer how the odds were computed: the number of
+    /// seasons simulated, the model parameters and the date of the data.
+    #[serde(default)]
+    odds_footer: bool,
+    #[serde(default)]
+    likely_opponents: bool,
+    #[serde(default)]
+    team_emoji: bool,
+    #[serde(default)]
+    projected_points: bool,
+    /// List how many points the team is back of each playoffs spot.
+    #[serde(default)]
+    points_from_spots: bool,
+    /// Label the teams of the playoffs matchups with their bracket seed, such
+    /// as `A1` for a division winner or `WC2` for the second wildcard. Set
+    /// `show_other_conference` or `both_conferences` for the full bracket.
+    #[serde(default)]
+    bracket_seeds: bool,
+    /// Show the season series against each team in the standings, which
+    /// fetches the team's games played so far this season.
+    #[serde(default)]
+    season_series: bool,
+    /// Pad the columns of the tables so the markdown is easier to review in a
+    /// text editor. Reddit ignores the extra spaces.
+    #[serde(default)]
+    pretty_tables: bool,
+    /// How the teams are named: `reddit_flair`, or `plain_abbrev`,
+    /// `full_name` or `markdown_link` for the posts published off reddit.
+    #[serde(default)]
+    team_style: generate::TeamRenderStyle,
+    /// Directory where a copy of every generated post is kept, test mode or not.
+    #[serde(default)]
+    archive_dir: Option<PathBuf>,
+    /// File where the hash of the last post of every team is kept for `--since-last-run`.
+    #[serde(default = "default_state_file")]
+    state_file: PathBuf,
+    /// Skip the teams that have clinched a playoffs spot or have been eliminated.
+    #[serde(default)]
+    skip_if_decided: bool,
+    /// Games left in the season for which a team is considered decided anyway.
+    #[serde(default)]
+    decided_buffer_games: u32,
+    /// Smallest change of the playoffs odds since the last post for a new one to be made.
+    #[serde(default)]
+    min_odds_delta: Option<f64>,
+    /// Title of the posts, where `{date}` and `{summary}` are replaced by the
+    /// date and the summary line of the race.
+    #[serde(default = "default_title_template")]
+    title_template: String,
+    #[serde(default)]
+    update_style: UpdateStyle,
+    /// Post or comment id to reply to for every team, when `update_style` is
+    /// `comment`, or a list of them to reply to each.
+    #[serde(default, deserialize_with = "one_or_many")]
+    comment_parents: BTreeMap<String, Vec<String>>,
+    /// Where the posts go when `test` is off.
+    #[serde(default)]
+    post_target: PostTarget,
+    /// Subreddit that receives every post when `post_target` is `staging`.
+    #[serde(default)]
+    test_subreddit: Option<String>,
+    /// Subreddit of a team by abbreviation, instead of its usual one, both to
+    /// post its thread and for its flair.
+    #[serde(default)]
+    subreddit_overrides: BTreeMap<String, String>,
+    /// Subreddits that each receive the post of a team by abbreviation,
+    /// instead of the team's subreddit alone, such as `{"MTL": ["habs", "habsstats"]}`.
+    #[serde(default)]
+    subreddits: BTreeMap<String, Vec<String>>,
+}
+
+/// Map of either one string or a list of strings by key, as lists.
+fn one_or_many<'de, D>(deserializer: D) -> Result<BTreeMap<String, Vec<String>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    let map = BTreeMap::<String, OneOrMany>::deserialize(deserializer)?;
+    Ok(map
+        .into_iter()
+        .map(|(key, value)| match value {
+            OneOrMany::One(one) => (key, vec![one]),
+            OneOrMany::Many(many) => (key, many),
+        })
+        .collect())
+}
+
+/// How the daily post is published on reddit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum UpdateStyle {
+    /// Submit a new self-post every day.
+    #[default]
+    Post,
+    /// Reply to the thread or comment configured in `comment_parents`.
+    Comment,
+}
+
+/// Where the posts go, to check the reddit integration without posting to
+/// the teams' subreddits.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum PostTarget {
+    /// The subreddit of each team.
+    #[default]
+    Live,
+    /// The `test_subreddit` of the config, for every team.
+    Staging,
+    /// Log in to reddit, but only print what would be submitted.
+    Log,
+}
+
+impl Config {
+    /// Filled template of the configuration, written by `playoffsbot init`.
+    pub fn example() -> Config {
+        Config {
+            client_id: "reddit app client id".into(),
+            client_secret: "reddit app client secret".into(),
+            username: "reddit username".into(),
+            password: "reddit password".into(),
+            user_agent: env!("CARGO_PKG_NAME").into(),
+            playoffs: vec!["MTL".into()],
+            test: true,
+            strict: false,
+            backend: nhlapi::Backend::StatsApi,
+            odds_appendix: false,
+            show_other_conference: false,
+            both_conferences: false,
+            team_threads: default_team_threads(),
+            summary_file: None,
+            playoff_line_points: BTreeMap::new(),
+            sections: default_sections(),
+            rivals: vec![],
+            relevant_only: false,
+            relevance_points: None,
+            hunt_points: default_hunt_points(),
+            chasers_shown: default_chasers_shown(),
+            mood_emoji: false,
+            form_weight: 0.0,
+            streak_weight: 0.0,
+            prior_games: 0.0,
+            seed: None,
+            back_to_back_penalty: 0.0,
+            odds_mode: simulation::OddsMode::Auto,
+            simulation_scope: simulation::SimulationScope::Conference,
+            sort_basis: simulation::SortBasis::Points,
+            tiebreaker: None,
+            pinned_results: BTreeMap::new(),
+            must_win_swing: None,
+            around_the_league: 0,
+            win_loss_odds: false,
+            target_odds: Some(0.9),
+            next_game_preview: false,
+            three_stars: false,
+            starting_goalies: false,
+            pace_baseline: false,
+            odds_footer: false,
+            likely_opponents: false,
+            team_emoji: false,
+            projected_points: false,
+            points_from_spots: false,
+            bracket_seeds: false,
+            season_series: false,
+            pretty_tables: false,
+            team_style: generate::TeamRenderStyle::RedditFlair,
+            archive_dir: Some(PathBuf::from("archive")),
+            state_file: default_state_file(),
+            skip_if_decided: false,
+            decided_buffer_games: 0,
+            min_odds_delta: None,
+            title_template: default_title_template(),
+            update_style: UpdateStyle::Post,
+            comment_parents: BTreeMap::new(),
+            post_target: PostTarget::Live,
+            test_subreddit: None,
+            subreddit_overrides: BTreeMap::new(),
+            subreddits: BTreeMap::new(),
+        }
+    }
+}
+
+/// Write the example configuration to `path`, unless the file already exists.
+fn init_config(path: &Path) -> Result<(), Error> {
+    let file = match OpenOptions::new().write(true).create_new(true).open(path) {
+        Ok(file) => file,
+        Err(ref e) if e.kind() == io::ErrorKind::AlreadyExists => bail!("{} already exists", path.display()),
+        Err(e) => return Err(e.into()),
+    };
+    serde_json::to_writer_pretty(file, &Config::example())?;
+    Ok(())
+}
+
+/// Reddit rejects blank user agents, and orca panics on the ones that are not
+/// valid header values.
+fn check_user_agent(user_agent: &str) -> Result<(), Error> {
+    if user_agent.trim().is_empty() {
+        bail!("user_agent must not be empty");
+    }
+    if user_agent.chars().any(|c| c.is_control()) {
+        bail!("user_agent must not contain control characters");
+    }
+    Ok(())
+}
+
+/// Subreddit that receives the post of `team`, according to `post_target`.
+fn post_subreddit<'a>(config: &'a Config, team: &'a Team) -> &'a str {
+    match (config.post_target, &config.test_subreddit) {
+        (PostTarget::Staging, Some(test_subreddit)) => test_subreddit,
+        _ => &team.subreddit,
+    }
+}
+
+/// Subreddits that receive the post of `team`: the ones configured in
+/// `subreddits`, or else `post_subreddit`. Staging only posts to the test subreddit.
+fn post_subreddits<'a>(config: &'a Config, team: &'a Team) -> Vec<&'a str> {
+    let configured = config
+        .subreddits
+        .iter()
+        .find(|(abbrev, _)| abbrev.eq_ignore_ascii_case(&team.abbrev));
+    match configured {
+        Some((_, subreddits)) if config.post_target != PostTarget::Staging => {
+            subreddits.iter().map(String::as_str).collect()
+        }
+        _ => vec![post_subreddit(config, team)],
+    }
+}
+
+/// Call `post` for every target, even after some of them fail, and return
+/// the `(target, post id)` of the targets that gave an id. Fails only when
+/// every target failed.
+fn post_to_each<F>(abbrev: &str, targets: &[&str], mut post: F) -> Result<Vec<(String, String)>, Error>
+where
+    F: FnMut(&str) -> Result<Option<String>, Error>,
+{
+    let mut post_ids = vec![];
+    let mut errors = vec![];
+    for &target in targets {
+        match post(target) {
+            Ok(post_id) => post_ids.extend(post_id.map(|id| (target.to_string(), id))),
+            Err(e) => {
+                eprintln!("{}: error posting to {}: {}", abbrev, target, e);
+                errors.push(format!("{}: {}", target, e));
+            }
+        }
+    }
+    if !targets.is_empty() && errors.len() == targets.len() {
+        bail!("every post failed: {}", errors.join("; "));
+    }
+    Ok(post_ids)
+}
+
+/// Check the abbreviations of the config that can only be checked against
+/// the teams of the league once they are downloaded.
+fn check_team_abbrevs(config: &Config, teams: &[Team]) -> Result<(), Error> {
+    let known = |abbrev: &str| teams.iter().any(|t| t.abbrev.eq_ignore_ascii_case(abbrev));
+    for abbrev in &config.rivals {
+        if !known(abbrev) {
+            bail!("unknown team {:?} in rivals", abbrev);
+        }
+    }
+    for (game_pk, result) in &config.pinned_results {
+        for abbrev in &[&result.winner, &result.loser] {
+            if !known(abbrev) {
+                bail!("unknown team {:?} in the pinned result of game {}", abbrev, game_pk);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Check the teams of the results given with `--whatif`, so that a typo fails
+/// the run before any team is processed.
+fn check_whatif_abbrevs(whatif: &[(String, Event)], teams: &[Team]) -> Result<(), Error> {
+    for (abbrev, _) in whatif {
+        if !teams.iter().any(|t| t.abbrev.eq_ignore_ascii_case(abbrev)) {
+            bail!("unknown team {:?} in --whatif", abbrev);
+        }
+    }
+    Ok(())
+}
+
+/// Replace the subreddit of the teams in `overrides`. Fails, leaving the teams
+/// as they were, if an abbreviation is not one of the teams.
+fn override_subreddits(teams: &mut [Team], overrides: &BTreeMap<String, String>) -> Result<(), Error> {
+    for abbrev in overrides.keys() {
+        if !teams.iter().any(|t| t.abbrev.eq_ignore_ascii_case(abbrev)) {
+            bail!("unknown team {:?} in subreddit_overrides", abbrev);
+        }
+    }
+    for (abbrev, subreddit) in overrides {
+        for team in teams.iter_mut().filter(|t| t.abbrev.eq_ignore_ascii_case(abbrev)) {
+            team.subreddit = subreddit.clone();
+        }
+    }
+    Ok(())
+}
+
+/// Create the reddit app and log in with the script credentials.
+fn reddit_app(config: &Config) -> Result<orca::App, Error> {
+    let mut reddit = orca::App::new(&config.user_agent, env!("CARGO_PKG_VERSION"), &config.username)?;
+    if let Err(e) = reddit.authorize_script(
+        &config.client_id,
+        &config.client_secret,
+        &config.username,
+        &config.password,
+    ) {
+        match e.downcast_ref::<orca::RedditError>() {
+            Some(orca::RedditError::AuthError) => {
+                bail!(
+                    "reddit rejected the credentials of {}, check config.json",
+                    config.username
+                )
+            }
+            _ => bail!("error reaching reddit to log in: {}", e),
+        }
+    }
+    Ok(reddit)
+}
+
+/// Reddit rejects comments longer than this many characters.
+const COMMENT_MAX_LEN: usize = 10_000;
+
+/// Fit a post in a comment of at most `max_len` characters. Whole sections are
+/// dropped from the end, where the least important ones are, keeping the
+/// disclaimer after the final rule. The text is cut as a last resort.
+fn fit_comment(md: &str, max_len: usize) -> String {
+    let (body, footer) = match md.rfind("\n---\n") {
+        Some(index) => md.split_at(index + 1),
+        None => (md, ""),
+    };
+
+    let mut starts: Vec<usize> = body.match_indices("\n## ").map(|(index, _)| index + 1).collect();
+    let len = |end: usize| body[..end].chars().count() + footer.chars().count();
+    let mut end = body.len();
+    while len(end) > max_len {
+        match starts.pop() {
+            Some(start) => end = start,
+            None => break,
+        }
+    }
+
+    let text = format!("{}{}", &body[..end], footer);
+    if text.chars().count() > max_len {
+        text.chars().take(max_len).collect()
+    } else {
+        text
+    }
+}
+
+fn default_team_threads() -> usize {
+    1
+}
+
+fn default_hunt_points() -> u32 {
+    generate::Options::default().hunt_points
+}
+
+fn default_sections() -> Vec<generate::PostSection> {
+    generate::PostSection::all()
+}
+
+fn default_chasers_shown() -> usize {
+    generate::Options::default().chasers_shown
+}
+
+fn default_title_template() -> String {
+    "Playoffs Race: {date}".to_string()
+}
+
+/// Fill the placeholders of a title template. The summary is only made when
+/// the template uses it, since it runs a simulation.
+fn fill_title<F>(template: &str, date: &NaiveDate, summary: F) -> String
+where
+    F: FnOnce() -> String,
+{
+    let date = format!("{} {}, {}", date.format("%B"), Ordinal(date.day()), date.format("%Y"));
+    let title = template.replace("{date}", &date);
+    if title.contains("{summary}") {
+        title.replace("{summary}", &summary())
+    } else {
+        title
+    }
+}
+
+/// Longest title reddit accepts, in characters.
+const TITLE_MAX_LEN: usize = 300;
+
+/// Cut a title to at most `max_len` characters, ending it with an ellipsis,
+/// since reddit rejects the whole submission when the title is too long.
+fn fit_title(title: String, max_len: usize) -> String {
+    if title.chars().count() <= max_len {
+        return title;
+    }
+    eprintln!(
+        "warning: title longer than {} characters, truncating: {}",
+        max_len, title
+    );
+    let mut fitted: String = title.chars().take(max_len.saturating_sub(1)).collect();
+    fitted.push('…');
+    fitted
+}
+
+fn default_state_file() -> PathBuf {
+    PathBuf::from("state.json")
+}
+
+#[derive(Debug, Default)]
+struct Args {
+    /// Generate the posts as if today were this date.
+    date: Option<NaiveDate>,
+    /// Write an example config.json instead of generating posts.
+    init: bool,
+    /// Print the playoffs odds of every team in the league instead of generating posts.
+    odds_table: bool,
+    /// Only generate the post for this team, instead of the teams in the config.
+    team: Option<String>,
+    /// Print the playoffs odds of every team in the conference.
+    verbose_sim: bool,
+    /// Print the playoffs odds if these results were to happen.
+    whatif: Vec<(String, Event)>,
+    /// Skip the teams whose post did not change since the last run.
+    since_last_run: bool,
+    /// Post even for the teams whose playoffs fate is decided.
+    force: bool,
+    /// Stay running and update the posts every this many minutes, instead of once.
+    watch: Option<u64>,
+    /// Print the time spent in each stage of the run, for every team.
+    profile: bool,
+    /// Print the odds behind the team to cheer for in every out of town game.
+    explain: bool,
+    /// Write the first round of the playoffs bracket to this file as JSON,
+    /// once the run is over, from the analysis of the last team analyzed in
+    /// the order of the config.
+    bracket_json: Option<PathBuf>,
+}
+
+/// Parse a list of results such as `MTL:W,MTL:OT,TOR:L`.
+fn parse_whatif(s: &str) -> Result<Vec<(String, Event)>, Error> {
+    s.split(',')
+        .map(|item| {
+            let mut parts = item.splitn(2, ':');
+            match (parts.next(), parts.next()) {
+                (Some(abbrev), Some(event)) if !abbrev.is_empty() => Ok((abbrev.to_string(), event.parse()?)),
+                _ => bail!("invalid result {:?}, expected TEAM:RESULT", item),
+            }
+        })
+        .collect()
+}
+
+fn parse_args<I>(args: I) -> Result<Args, Error>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut parsed = Args::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "init" => parsed.init = true,
+            "odds-table" => parsed.odds_table = true,
+            "--team" => {
+                parsed.team = Some(
+                    args.next()
+                        .ok_or_else(|| format_err!("--team requires a team abbreviation"))?,
+                )
+            }
+            "--date" => {
+                let date = args.next().ok_or_else(|| format_err!("--date requires a date"))?;
+                parsed.date = Some(
+                    NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+                        .map_err(|e| format_err!("invalid date {:?}, expected YYYY-MM-DD: {}", date, e))?,
+                );
+            }
+            "--verbose-sim" => parsed.verbose_sim = true,
+            "--since-last-run" => parsed.since_last_run = true,
+            "--force" => parsed.force = true,
+            "--profile" => parsed.profile = true,
+            "--explain" => parsed.explain = true,
+            "--bracket-json" => {
+                let path = args
+                    .next()
+                    .ok_or_else(|| format_err!("--bracket-json requires a path"))?;
+                parsed.bracket_json = Some(PathBuf::from(path));
+            }
+            "--once" => parsed.watch = None,
+            "--watch" => {
+                let minutes = args
+                    .next()
+                    .ok_or_else(|| format_err!("--watch requires a number of minutes"))?;
+                parsed.watch = match minutes.parse() {
+                    Ok(0) | Err(_) => bail!("invalid interval {:?}, expected a number of minutes", minutes),
+                    Ok(minutes) => Some(minutes),
+                };
+            }
+            "--whatif" => {
+                let results = args
+                    .next()
+                    .ok_or_else(|| format_err!("--whatif requires a list of results"))?;
+                parsed.whatif = parse_whatif(&results)?;
+            }
+            _ => bail!("unknown argument: {}", arg),
+        }
+    }
+    Ok(parsed)
+}
+
+/// Set on SIGINT, to stop watching once the current update is done.
+static STOP: AtomicBool = AtomicBool::new(false);
+
+#[cfg(unix)]
+fn handle_sigint() {
+    extern "C" fn on_sigint(_: libc::c_int) {
+        STOP.store(true, Ordering::SeqCst);
+        // A second ^C kills the bot right away.
+        unsafe {
+            libc::signal(libc::SIGINT, libc::SIG_DFL);
+        }
+    }
+    unsafe {
+        libc::signal(
+            libc::SIGINT,
+            on_sigint as extern "C" fn(libc::c_int) as libc::sighandler_t,
+        );
+    }
+}
+
+#[cfg(not(unix))]
+fn handle_sigint() {}
+
+/// Sleep for `duration`, waking up early on SIGINT. Returns whether to keep going.
+fn sleep_unless_stopped(duration: Duration) -> bool {
+    let deadline = Instant::now() + duration;
+    while !STOP.load(Ordering::SeqCst) {
+        let now = Instant::now();
+        if now >= deadline {
+            return true;
+        }
+        thread::sleep((deadline - now).min(Duration::from_secs(1)));
+    }
+    false
+}
+
+/// Time to wait before retrying a failed request.
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Call `f`, and call it once more after `RETRY_DELAY` if it fails.
+fn retry_once<T, E, F>(what: &str, delay: Duration, mut f: F) -> Result<T, E>
+where
+    E: fmt::Display,
+    F: FnMut() -> Result<T, E>,
+{
+    f().or_else(|e| {
+        eprintln!("error getting {}, retrying: {}", what, e);
+        thread::sleep(delay);
+        f()
+    })
+}
+
+fn archive_path(dir: &Path, abbrev: &str, date: &NaiveDate) -> PathBuf {
+    dir.join(format!("{}-{}.md", abbrev, date.format("%Y-%m-%d")))
 }
 
 fn get_season_year(today: &NaiveDate) -> i32 {
@@ -38,52 +718,661 @@ fn get_season_year(today: &NaiveDate) -> i32 {
 }
 
 fn main() -> Result<(), Error> {
-    let api = Api::download();
+    let args = parse_args(std::env::args().skip(1))?;
+
+    if args.init {
+        init_config(Path::new("config.json"))?;
+        println!("wrote config.json, fill in the reddit credentials before running the bot");
+        return Ok(());
+    }
 
     let config_file = File::open("config.json")?;
     let config: Config = serde_json::from_reader(config_file)?;
 
-    for abbrev in config.playoffs {
-        let team = api.get_team_by_abbrev(&abbrev);
-        let analyzer = Analyzer::new(&api, team);
-        let an = analyzer.perform();
+    check_user_agent(&config.user_agent)?;
+    if !(0.0..=1.0).contains(&config.form_weight) {
+        bail!("form_weight must be between 0 and 1");
+    }
+    if !(0.0..=1.0).contains(&config.streak_weight) {
+        bail!("streak_weight must be between 0 and 1");
+    }
+    if !(config.prior_games >= 0.0 && config.prior_games.is_finite()) {
+        bail!("prior_games must be 0 or more");
+    }
+    if !(0.0..=1.0).contains(&config.back_to_back_penalty) {
+        bail!("back_to_back_penalty must be between 0 and 1");
+    }
+    for (abbrev, subreddit) in &config.subreddit_overrides {
+        if subreddit.trim().is_empty() || subreddit.starts_with("r/") || subreddit.starts_with("/r/") {
+            bail!(
+                "invalid subreddit {:?} for {}, expected a name without r/",
+                subreddit,
+                abbrev
+            );
+        }
+    }
+    for (abbrev, subreddits) in &config.subreddits {
+        if subreddits.is_empty() {
+            bail!("no subreddits for {} in subreddits", abbrev);
+        }
+        for subreddit in subreddits {
+            if subreddit.trim().is_empty() || subreddit.starts_with("r/") || subreddit.starts_with("/r/") {
+                bail!(
+                    "invalid subreddit {:?} for {}, expected a name without r/",
+                    subreddit,
+                    abbrev
+                );
+            }
+        }
+    }
+    for (abbrev, parents) in &config.comment_parents {
+        if parents.is_empty() {
+            bail!("no comment parents for {} in comment_parents", abbrev);
+        }
+    }
+    for (game_pk, result) in &config.pinned_results {
+        if result.winner.eq_ignore_ascii_case(&result.loser) {
+            bail!("the pinned result of game {} has the same winner and loser", game_pk);
+        }
+    }
+    if let Some(min_odds_delta) = config.min_odds_delta {
+        if !(0.0..=1.0).contains(&min_odds_delta) {
+            bail!("min_odds_delta must be between 0 and 1");
+        }
+    }
+    if config.post_target == PostTarget::Staging {
+        if config
+            .test_subreddit
+            .as_deref()
+            .map_or(true, |sub| sub.trim().is_empty())
+        {
+            bail!("post_target \"staging\" needs a test_subreddit");
+        }
+        if config.update_style == UpdateStyle::Comment {
+            bail!("post_target \"staging\" only works with update_style \"post\"");
+        }
+    }
+    if let Some(must_win_swing) = config.must_win_swing {
+        if !(must_win_swing > 0.0 && must_win_swing <= 1.0) {
+            bail!("must_win_swing must be greater than 0 and at most 1");
+        }
+    }
+    if let Some(target_odds) = config.target_odds {
+        if !(target_odds > 0.0 && target_odds <= 1.0) {
+            bail!("target_odds must be greater than 0 and at most 1");
+        }
+    }
+
+    let params = simulation::Params {
+        form_weight: config.form_weight,
+        streak_weight: config.streak_weight,
+        prior_games: config.prior_games,
+        odds_mode: config.odds_mode,
+        back_to_back_penalty: config.back_to_back_penalty,
+        sort_basis: config.sort_basis,
+        tiebreaker: config.tiebreaker,
+        scope: config.simulation_scope,
+        pinned: config.pinned_results.clone(),
+        seed: config.seed,
+        times: None,
+    };
 
-        let today = Local::today().naive_local();
-        let season_end = Local.ymd(get_season_year(&today), 5, 1).naive_local();
+    if args.odds_table {
+        let today = args.date.unwrap_or_else(|| Local::today().naive_local());
+        let mut api = Api::download_from(config.backend, &today);
+        override_subreddits(&mut api.teams, &config.subreddit_overrides)?;
+        check_team_abbrevs(&config, &api.teams)?;
+        let odds = simulation::odds_for_league(&api, &params);
+        print!("{}", generate::make_league_odds_table(&api, &odds)?);
+        return Ok(());
+    }
 
-        let schedule = nhlapi::schedule::get_range(team.id, &today, &season_end)?;
+    let mut state = State::load(&config.state_file)?;
 
-        let gen = MarkdownGenerator::new(&api, &an, &schedule, &team);
-        let doc = gen.markdown();
+    let minutes = match args.watch {
+        Some(minutes) => minutes,
+        None => {
+            let summary = run_once(&args, &config, &params, &mut state)?;
+            if !summary.failed.is_empty() {
+                bail!("{} of the teams failed", summary.failed.len());
+            }
+            return Ok(());
+        }
+    };
 
-        if config.test {
-            let mut file = File::create(&format!("{}.md", team.abbrev))?;
-            write!(file, "{}", doc.as_str())?;
-        } else {
-            let mut reddit = orca::App::new("tankbot", "1.0", "sbstp")?;
-            reddit.authorize_script(
-                &config.client_id,
-                &config.client_secret,
-                &config.username,
-                &config.password,
-            )?;
-
-            let today = Local::today();
-            reddit.submit_self(
-                &team.subreddit,
-                &format!(
-                    "Playoffs Race: {} {}, {}",
-                    today.format("%B"),
-                    Ordinal(today.day()),
-                    today.format("%Y")
-                ),
-                doc.as_str(),
-                false,
-            )?;
+    handle_sigint();
+    loop {
+        // A failed update, even a panic while downloading, is retried on the next one.
+        let result = panic::catch_unwind(AssertUnwindSafe(|| run_once(&args, &config, &params, &mut state)));
+        match result {
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => eprintln!("error during the update, retrying in {} minutes: {}", minutes, e),
+            Err(_) => eprintln!("the update panicked, retrying in {} minutes", minutes),
+        }
+        if !sleep_unless_stopped(Duration::from_secs(minutes * 60)) {
+            eprintln!("interrupted, stopping");
+            return Ok(());
         }
     }
+}
 
-    Ok(())
+/// Generate, and post when needed, the post of every team once.
+fn run_once(args: &Args, config: &Config, params: &simulation::Params, state: &mut State) -> Result<RunSummary, Error> {
+    let started = Instant::now();
+    let today = args.date.unwrap_or_else(|| Local::today().naive_local());
+    let mut profile = Profile::default();
+    let mut api = profile.time("download", || Api::download_from(config.backend, &today));
+    override_subreddits(&mut api.teams, &config.subreddit_overrides)?;
+    check_team_abbrevs(config, &api.teams)?;
+    check_whatif_abbrevs(&args.whatif, &api.teams)?;
+    let season = match args.date {
+        Some(date) => nhlapi::seasons::get(&nhlapi::Season::of(&date)),
+        None => nhlapi::seasons::current(),
+    };
+    if let Err(e) = &season {
+        eprintln!("error getting current season, assuming it ends on May 1st: {}", e);
+    }
+    let run = RunData {
+        today,
+        api,
+        season: season.ok(),
+    };
+
+    let teams = match &args.team {
+        Some(abbrev) => vec![abbrev.clone()],
+        None => config.playoffs.clone(),
+    };
+    if args.profile {
+        eprint!("profile of the run:\n{}", profile);
+    }
+
+    let state = Mutex::new(state);
+    let outcomes = map_in_pool(&teams, config.team_threads, |abbrev| {
+        let mut report = TeamReport::default();
+        let outcome = run_team(args, config, params, &state, &run, abbrev, &mut report);
+        if args.profile {
+            eprint!("profile of {}:\n{}", abbrev, report.profile);
+        }
+        let outcome = match outcome {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                eprintln!("error posting for {}: {}", abbrev, e);
+                TeamOutcome::Failed(e.to_string())
+            }
+        };
+        (outcome, report.brackets)
+    });
+
+    let mut summary = RunSummary::default();
+    let mut last_brackets = None;
+    for (abbrev, (outcome, brackets)) in teams.iter().zip(outcomes) {
+        summary.add(abbrev, outcome);
+        last_brackets = brackets.or(last_brackets);
+    }
+    summary.runtime_secs = started.elapsed().as_secs_f64();
+
+    println!("{}", summary);
+    if let Some(summary_file) = &config.summary_file {
+        serde_json::to_writer_pretty(File::create(summary_file)?, &summary)?;
+    }
+    // Written once the teams are done, so the file doesn't depend on which
+    // thread finishes last.
+    if let (Some(path), Some(brackets)) = (&args.bracket_json, &last_brackets) {
+        serde_json::to_writer_pretty(File::create(path)?, brackets)?;
+    }
+    Ok(summary)
+}
+
+/// Call `f` on every item from at most `threads` threads at once, and return
+/// the results in the order of the items.
+fn map_in_pool<T, R, F>(items: &[T], threads: usize, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let threads = threads.clamp(1, items.len().max(1));
+    if threads == 1 {
+        return items.iter().map(f).collect();
+    }
+    let next = AtomicUsize::new(0);
+    let mut results: Vec<(usize, R)> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut results = vec![];
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        match items.get(index) {
+                            Some(item) => results.push((index, f(item))),
+                            None => return results,
+                        }
+                    }
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap_or_else(|e| panic::resume_unwind(e)))
+            .collect()
+    });
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+/// What the run of a team leaves behind besides its outcome.
+#[derive(Debug, Default)]
+struct TeamReport {
+    profile: Profile,
+    /// First round of the playoffs from the analysis of the team, for
+    /// `--bracket-json`.
+    brackets: Option<Vec<analysis::Bracket>>,
+}
+
+/// Time spent in each stage of a run, along with the simulations run during
+/// each, printed with `--profile`.
+#[derive(Debug, Default)]
+struct Profile {
+    stages: Vec<(&'static str, Duration, simulation::SimCount)>,
+}
+
+impl Profile {
+    /// Call `f` and record how long it took as the stage `name`.
+    fn time<T, F>(&mut self, name: &'static str, f: F) -> T
+    where
+        F: FnOnce() -> T,
+    {
+        let sims = simulation::sim_count();
+        let started = Instant::now();
+        let out = f();
+        self.stages
+            .push((name, started.elapsed(), simulation::sim_count() - sims));
+        out
+    }
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (name, elapsed, sims) in &self.stages {
+            write!(f, "  {}: {:.2}s", name, elapsed.as_secs_f64())?;
+            if sims.simulations > 0 {
+                write!(
+                    f,
+                    ", ran {} simulations totaling {} sim-seasons",
+                    sims.simulations,
+                    format_count(sims.seasons)
+                )?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// A large count in a short form, such as `1.2M`.
+fn format_count(count: u64) -> String {
+    match count {
+        0..=9_999 => count.to_string(),
+        10_000..=999_999 => format!("{:.1}k", count as f64 / 1e3),
+        _ => format!("{:.1}M", count as f64 / 1e6),
+    }
+}
+
+/// Data downloaded once per run, shared by the posts of every team.
+struct RunData {
+    today: NaiveDate,
+    api: Api,
+    /// Dates of the current season, if they could be fetched.
+    season: Option<nhlapi::seasons::Season>,
+}
+
+/// What became of a team's post during a run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TeamOutcome {
+    Posted,
+    /// Left out on purpose, for the given reason.
+    Skipped(String),
+    /// Left out because of an error.
+    Failed(String),
+}
+
+/// Outcome of every team in a run, printed and optionally saved as JSON
+/// once the run is over.
+#[derive(Debug, Default, Serialize)]
+struct RunSummary {
+    posted: Vec<String>,
+    /// Reason of each skipped team.
+    skipped: BTreeMap<String, String>,
+    /// Error of each failed team.
+    failed: BTreeMap<String, String>,
+    runtime_secs: f64,
+}
+
+impl RunSummary {
+    fn add(&mut self, abbrev: &str, outcome: TeamOutcome) {
+        match outcome {
+            TeamOutcome::Posted => self.posted.push(abbrev.to_string()),
+            TeamOutcome::Skipped(reason) => {
+                self.skipped.insert(abbrev.to_string(), reason);
+            }
+            TeamOutcome::Failed(error) => {
+                self.failed.insert(abbrev.to_string(), error);
+            }
+        }
+    }
+}
+
+impl fmt::Display for RunSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} posted, {} skipped, {} failed in {:.1}s",
+            self.posted.len(),
+            self.skipped.len(),
+            self.failed.len(),
+            self.runtime_secs
+        )?;
+        for (abbrev, reason) in &self.skipped {
+            write!(f, "\n  {} skipped: {}", abbrev, reason)?;
+        }
+        for (abbrev, error) in &self.failed {
+            write!(f, "\n  {} failed: {}", abbrev, error)?;
+        }
+        Ok(())
+    }
+}
+
+/// Generate, and post when needed, the post of one team.
+fn run_team(
+    args: &Args,
+    config: &Config,
+    params: &simulation::Params,
+    state: &Mutex<&mut State>,
+    run: &RunData,
+    abbrev: &str,
+    report: &mut TeamReport,
+) -> Result<TeamOutcome, Error> {
+    let RunData { today, api, season } = run;
+    // Watching only makes sense if unchanged posts are not made again.
+    let since_last_run = args.since_last_run || args.watch.is_some();
+
+    let team = api.get_team_by_abbrev(abbrev);
+    if config.skip_if_decided && !args.force {
+        let reason = match simulation::clinch_status_within(api, team, &api.standings, config.decided_buffer_games) {
+            simulation::Clinch::Clinched => Some("clinched a playoffs spot"),
+            simulation::Clinch::Eliminated => Some("eliminated from the playoffs"),
+            simulation::Clinch::Undecided => None,
+        };
+        if let Some(reason) = reason {
+            eprintln!("{}: {}, skipping (use --force to post anyway)", team.abbrev, reason);
+            return Ok(TeamOutcome::Skipped(reason.to_string()));
+        }
+    }
+    let season_end = match season {
+        Some(season) => season.regular_season_end_date,
+        None => Local.ymd(get_season_year(today), 5, 1).naive_local(),
+    };
+
+    // Without its schedule, the post is still worth making for the rest.
+    let schedule = report.profile.time("schedule", || {
+        retry_once("the schedule", RETRY_DELAY, || {
+            config.backend.schedule_range(team, today, &season_end)
+        })
+    });
+    let (schedule, schedule_available) = match schedule {
+        Ok(schedule) => (nhlapi::schedule::regular_season(schedule, &season_end), true),
+        Err(e) => {
+            eprintln!("error getting the schedule of {}, leaving it out: {}", team.abbrev, e);
+            (vec![], false)
+        }
+    };
+
+    let analyzer = Analyzer::new(api, team)
+        .with_options(analysis::Options {
+            other_conference: config.show_other_conference || config.both_conferences,
+            rivals: config
+                .rivals
+                .iter()
+                .filter_map(|abbrev| api.find_team_by_abbrev(abbrev))
+                .map(|team| team.id)
+                .collect(),
+            relevant_only: config.relevant_only,
+            relevance_points: config.relevance_points,
+            params: params.clone(),
+            must_win_swing: config.must_win_swing,
+            around_the_league: config.around_the_league,
+            win_loss_odds: config.win_loss_odds,
+            skip_simulations: false,
+        })
+        .with_schedule(&schedule);
+    // Seeding, and the simulations picking the ideal losers.
+    let an = report.profile.time("analysis", || analyzer.perform());
+    if args.bracket_json.is_some() {
+        report.brackets = Some(an.brackets(api));
+    }
+
+    // The season series needs every game played so far. Without them, the
+    // post is still made without the series.
+    let past_schedule = match season {
+        Some(season) if config.next_game_preview || config.season_series => {
+            let yesterday = today.pred();
+            let past = retry_once("the past schedule", RETRY_DELAY, || {
+                config
+                    .backend
+                    .schedule_range(team, &season.regular_season_start_date, &yesterday)
+            });
+            match past {
+                Ok(past) => nhlapi::schedule::regular_season(past, &yesterday),
+                Err(e) => {
+                    eprintln!(
+                        "error getting the past schedule of {}, leaving the season series out: {}",
+                        team.abbrev, e
+                    );
+                    vec![]
+                }
+            }
+        }
+        _ => vec![],
+    };
+
+    let decisions = match &an.my_result {
+        Some(my_result) if config.three_stars => match nhlapi::game::get_decisions(my_result.game.game_pk) {
+            Ok(decisions) => Some(decisions),
+            Err(e) => {
+                eprintln!("error getting the three stars of {}: {}", team.abbrev, e);
+                None
+            }
+        },
+        _ => None,
+    };
+
+    // Only the team's next game, tonight's if it plays tonight.
+    let next_game = an
+        .my_game
+        .as_ref()
+        .map(|m| m.game)
+        .or_else(|| schedule.iter().flat_map(|d| &d.games).next());
+    let starters = match next_game {
+        Some(game) if config.starting_goalies => match nhlapi::game::get_starters(game.game_pk) {
+            Ok(starters) => starters,
+            Err(e) => {
+                eprintln!("error getting the starting goalies of {}: {}", team.abbrev, e);
+                None
+            }
+        },
+        _ => None,
+    };
+
+    let gen = MarkdownGenerator::new(api, &an, &schedule, &team)
+        .with_options(generate::Options {
+            odds_appendix: config.odds_appendix,
+            hunt_points: config.hunt_points,
+            chasers_shown: config.chasers_shown,
+            mood_emoji: config.mood_emoji,
+            target_odds: config.target_odds,
+            next_game_preview: config.next_game_preview,
+            three_stars: config.three_stars,
+            starting_goalies: config.starting_goalies,
+            pace_baseline: config.pace_baseline,
+            odds_footer: config.odds_footer,
+            likely_opponents: config.likely_opponents,
+            team_emoji: config.team_emoji,
+            projected_points: config.projected_points,
+            points_from_spots: config.points_from_spots,
+            bracket_seeds: config.bracket_seeds,
+            season_series: config.season_series,
+            pretty_tables: config.pretty_tables,
+            team_style: config.team_style,
+            both_conferences: config.both_conferences,
+            playoff_line_points: config.playoff_line_points.clone(),
+            sections: config.sections.clone(),
+        })
+        .with_past_schedule(&past_schedule)
+        .with_schedule_available(schedule_available);
+    let gen = match &decisions {
+        Some(decisions) => gen.with_decisions(decisions),
+        None => gen,
+    };
+    let gen = match &starters {
+        Some(starters) => gen.with_starters(starters),
+        None => gen,
+    };
+
+    if args.verbose_sim {
+        println!("{}", team.display(NameStyle::Full));
+        println!();
+        match gen.make_odds_table() {
+            Ok(table) => print!("{}", table),
+            Err(e) => eprintln!("error making the odds table of {}: {}", team.abbrev, e),
+        }
+    }
+
+    if args.explain {
+        for line in gen.make_explanations() {
+            println!("{}: {}", team.abbrev, line);
+        }
+    }
+
+    if !args.whatif.is_empty() {
+        let results = args
+            .whatif
+            .iter()
+            .map(|(abbrev, event)| match api.find_team_by_abbrev(abbrev) {
+                Some(team) => Ok((team.id, *event)),
+                None => Err(format_err!("unknown team {:?} in --whatif", abbrev)),
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        println!(
+            "{}: {:.1}% today, {:.1}% after {:?}",
+            team.abbrev,
+            simulation::odds_for_team(api, team, false, an.params).unwrap_or_default() * 100.0,
+            simulation::odds_with_results(api, team, &results, an.params) * 100.0,
+            args.whatif,
+        );
+    }
+
+    // Most of the odds are simulated while generating the post.
+    let doc = report.profile.time("markdown", || gen.markdown())?;
+    let issues = doc.validate();
+    for issue in &issues {
+        eprintln!("{}: warning: {}", team.abbrev, issue);
+    }
+    if config.strict && !issues.is_empty() {
+        bail!("the post has {} issues, not posting it", issues.len());
+    }
+    let hash = state::hash_document(doc.as_str());
+
+    if since_last_run
+        && state
+            .lock()
+            .expect("state lock poisoned")
+            .is_unchanged(&team.abbrev, hash)
+    {
+        eprintln!("{}: post unchanged since the last run, skipping", team.abbrev);
+        return Ok(TeamOutcome::Skipped("post unchanged since the last run".to_string()));
+    }
+
+    // Only computed when needed, it's another full simulation.
+    let odds = report.profile.time("odds", || {
+        config
+            .min_odds_delta
+            .and_then(|_| simulation::odds_for_team(api, team, false, params))
+    });
+    if let (Some(odds), Some(min_odds_delta)) = (odds, config.min_odds_delta) {
+        if state
+            .lock()
+            .expect("state lock poisoned")
+            .odds_within(&team.abbrev, odds, min_odds_delta)
+        {
+            eprintln!(
+                "{}: odds at {:.1}% moved less than {:.1}% since the last post, skipping",
+                team.abbrev,
+                odds * 100.0,
+                min_odds_delta * 100.0
+            );
+            return Ok(TeamOutcome::Skipped(
+                "odds barely moved since the last post".to_string(),
+            ));
+        }
+    }
+
+    if let Some(archive_dir) = &config.archive_dir {
+        fs::create_dir_all(archive_dir)?;
+        let mut file = File::create(archive_path(archive_dir, &team.abbrev, today))?;
+        write!(file, "{}", doc.as_str())?;
+    }
+
+    // Posting and saving the state are done by one team at a time.
+    let mut state = state.lock().expect("state lock poisoned");
+    let post_ids = if config.test {
+        let mut file = File::create(&format!("{}.md", team.abbrev))?;
+        write!(file, "{}", doc.as_str())?;
+        vec![]
+    } else {
+        let reddit = reddit_app(config)?;
+
+        match config.update_style {
+            UpdateStyle::Post => {
+                let title = fit_title(
+                    fill_title(&config.title_template, today, || gen.make_summary_line()),
+                    TITLE_MAX_LEN,
+                );
+                post_to_each(&team.abbrev, &post_subreddits(config, team), |subreddit| {
+                    if config.post_target == PostTarget::Log {
+                        println!("submit_self to r/{} titled {:?}:", subreddit, title);
+                        print!("{}", doc.as_str());
+                        Ok(None)
+                    } else {
+                        let resp = reddit.submit_self(subreddit, &title, doc.as_str(), false)?;
+                        Ok(resp["json"]["data"]["name"].as_str().map(String::from))
+                    }
+                })?
+            }
+            UpdateStyle::Comment => {
+                let parents: Vec<&str> = config
+                    .comment_parents
+                    .get(&team.abbrev)
+                    .ok_or_else(|| format_err!("no comment parent configured for {}", team.abbrev))?
+                    .iter()
+                    .map(String::as_str)
+                    .collect();
+                let text = fit_comment(doc.as_str(), COMMENT_MAX_LEN);
+                post_to_each(&team.abbrev, &parents, |parent| {
+                    if config.post_target == PostTarget::Log {
+                        println!("comment on {}:", parent);
+                        print!("{}", text);
+                    } else {
+                        reddit.comment(&text, parent)?;
+                    }
+                    Ok(None)
+                })?
+            }
+        }
+    };
+
+    state.update_posts(&team.abbrev, hash, post_ids, odds);
+    state.save(&config.state_file)?;
+    Ok(TeamOutcome::Posted)
 }
 
 #[test]
@@ -91,3 +1380,381 @@ fn test_get_season_year() {
     assert_eq!(get_season_year(&Local.ymd(2019, 03, 15).naive_local()), 2019);
     assert_eq!(get_season_year(&Local.ymd(2018, 11, 15).naive_local()), 2019);
 }
+
+#[test]
+fn test_archive_path() {
+    assert_eq!(
+        archive_path(Path::new("archive"), "MTL", &NaiveDate::from_ymd(2019, 3, 5)),
+        Path::new("archive/MTL-2019-03-05.md")
+    );
+}
+
+#[test]
+fn test_parse_args() {
+    let args = parse_args(vec![
+        "--team".to_string(),
+        "MTL".to_string(),
+        "--verbose-sim".to_string(),
+        "--since-last-run".to_string(),
+        "--force".to_string(),
+        "--profile".to_string(),
+        "--explain".to_string(),
+        "--bracket-json".to_string(),
+        "bracket.json".to_string(),
+    ])
+    .unwrap();
+    assert_eq!(args.team, Some("MTL".to_string()));
+    assert!(args.verbose_sim);
+    assert!(args.since_last_run);
+    assert!(args.force);
+    assert!(args.profile);
+    assert!(args.explain);
+    assert_eq!(args.bracket_json, Some(PathBuf::from("bracket.json")));
+
+    assert!(parse_args(vec!["init".to_string()]).unwrap().init);
+    assert!(parse_args(vec!["odds-table".to_string()]).unwrap().odds_table);
+    let args = parse_args(vec!["--date".to_string(), "2019-03-15".to_string()]).unwrap();
+    assert_eq!(args.date, Some(NaiveDate::from_ymd(2019, 3, 15)));
+    assert!(parse_args(vec!["--date".to_string(), "03/15/2019".to_string()]).is_err());
+    let args = parse_args(vec!["--watch".to_string(), "30".to_string()]).unwrap();
+    assert_eq!(args.watch, Some(30));
+    let args = parse_args(vec!["--watch".to_string(), "30".to_string(), "--once".to_string()]).unwrap();
+    assert_eq!(args.watch, None);
+    assert!(parse_args(vec!["--watch".to_string(), "0".to_string()]).is_err());
+    assert!(parse_args(vec!["--watch".to_string()]).is_err());
+
+    assert!(parse_args(vec!["--team".to_string()]).is_err());
+    assert!(parse_args(vec!["--whatif".to_string()]).is_err());
+    assert!(parse_args(vec!["--bracket-json".to_string()]).is_err());
+    assert!(parse_args(vec!["--bogus".to_string()]).is_err());
+}
+
+#[test]
+fn test_config_example() {
+    let json = serde_json::to_string(&Config::example()).unwrap();
+    let config: Config = serde_json::from_str(&json).unwrap();
+    assert_eq!(config.playoffs, vec!["MTL".to_string()]);
+    assert_eq!(config.hunt_points, default_hunt_points());
+    assert_eq!(serde_json::to_string(&config).unwrap(), json);
+}
+
+#[test]
+fn test_update_style() {
+    let config: Config = serde_json::from_str(
+        r#"{"client_id": "", "client_secret": "", "username": "", "password": "", "user_agent": "",
+            "playoffs": [], "update_style": "comment", "comment_parents": {"MTL": "t3_abc"}}"#,
+    )
+    .unwrap();
+    assert_eq!(config.update_style, UpdateStyle::Comment);
+    assert_eq!(config.comment_parents["MTL"], vec!["t3_abc"]);
+    assert_eq!(Config::example().update_style, UpdateStyle::Post);
+    assert!(serde_json::from_str::<UpdateStyle>(r#""edit""#).is_err());
+}
+
+#[test]
+fn test_post_target() {
+    let config: Config = serde_json::from_str(
+        r#"{"client_id": "", "client_secret": "", "username": "", "password": "", "user_agent": "",
+            "playoffs": [], "post_target": "staging", "test_subreddit": "playoffsbot_test"}"#,
+    )
+    .unwrap();
+    assert_eq!(config.post_target, PostTarget::Staging);
+    assert_eq!(Config::example().post_target, PostTarget::Live);
+
+    let teams = nhlapi::teams::parse(include_str!("../tests/fixtures/teams.json")).unwrap();
+    let mtl = teams.iter().find(|t| t.abbrev == "MTL").unwrap();
+    assert_eq!(post_subreddit(&config, mtl), "playoffsbot_test");
+    assert_eq!(post_subreddit(&Config::example(), mtl), "habs");
+    let config = Config {
+        post_target: PostTarget::Log,
+        ..config
+    };
+    assert_eq!(post_subreddit(&config, mtl), "habs");
+}
+
+#[test]
+fn test_post_subreddits() {
+    let config: Config = serde_json::from_str(
+        r#"{"client_id": "", "client_secret": "", "username": "", "password": "", "user_agent": "",
+            "playoffs": [], "subreddits": {"mtl": ["habs", "habsstats"]},
+            "comment_parents": {"MTL": ["t3_abc", "t3_def"], "TOR": "t3_ghi"}}"#,
+    )
+    .unwrap();
+    assert_eq!(config.comment_parents["MTL"], vec!["t3_abc", "t3_def"]);
+    assert_eq!(config.comment_parents["TOR"], vec!["t3_ghi"]);
+
+    let teams = nhlapi::teams::parse(include_str!("../tests/fixtures/teams.json")).unwrap();
+    let team = |abbrev| teams.iter().find(|t| t.abbrev == abbrev).unwrap();
+    assert_eq!(post_subreddits(&config, team("MTL")), vec!["habs", "habsstats"]);
+    assert_eq!(post_subreddits(&config, team("TOR")), vec!["leafs"]);
+    let config = Config {
+        post_target: PostTarget::Staging,
+        test_subreddit: Some("playoffsbot_test".to_string()),
+        ..config
+    };
+    assert_eq!(post_subreddits(&config, team("MTL")), vec!["playoffsbot_test"]);
+}
+
+#[test]
+fn test_post_to_each() {
+    let mut posted = vec![];
+    let post_ids = post_to_each("MTL", &["habs", "down", "habsstats"], |subreddit| {
+        posted.push(subreddit.to_string());
+        match subreddit {
+            "down" => Err(format_err!("503")),
+            _ => Ok(Some(format!("t3_{}", subreddit))),
+        }
+    })
+    .unwrap();
+    // The failure doesn't keep the last subreddit from getting the post.
+    assert_eq!(posted, vec!["habs", "down", "habsstats"]);
+    assert_eq!(
+        post_ids,
+        vec![
+            ("habs".to_string(), "t3_habs".to_string()),
+            ("habsstats".to_string(), "t3_habsstats".to_string()),
+        ]
+    );
+
+    assert_eq!(post_to_each("MTL", &["habs"], |_| Ok(None)).unwrap(), vec![]);
+    let err = post_to_each("MTL", &["habs", "habsstats"], |_| Err(format_err!("503"))).unwrap_err();
+    assert_eq!(err.to_string(), "every post failed: habs: 503; habsstats: 503");
+}
+
+#[test]
+fn test_config_sections() {
+    let config = |sections| {
+        serde_json::from_str::<Config>(&format!(
+            r#"{{"client_id": "", "client_secret": "", "username": "", "password": "", "user_agent": "",
+                "playoffs": [], "sections": {}}}"#,
+            sections
+        ))
+    };
+    assert_eq!(
+        config(r#"["schedule", "last_night", "odds_appendix"]"#)
+            .unwrap()
+            .sections,
+        vec![
+            generate::PostSection::Schedule,
+            generate::PostSection::LastNight,
+            generate::PostSection::OddsAppendix
+        ]
+    );
+    let err = config(r#"["schedule", "bracket_odds"]"#).err().unwrap();
+    assert!(err.to_string().contains("unknown variant `bracket_odds`"));
+    assert_eq!(Config::example().sections, generate::PostSection::all());
+}
+
+#[test]
+fn test_run_summary() {
+    let mut summary = RunSummary::default();
+    summary.add("MTL", TeamOutcome::Posted);
+    summary.add("OTT", TeamOutcome::Skipped("eliminated from the playoffs".to_string()));
+    summary.add("TOR", TeamOutcome::Failed("timed out".to_string()));
+    summary.add("BOS", TeamOutcome::Posted);
+    summary.runtime_secs = 2.5;
+
+    assert_eq!(
+        summary.to_string(),
+        "2 posted, 1 skipped, 1 failed in 2.5s\n  OTT skipped: eliminated from the playoffs\n  TOR failed: timed out"
+    );
+    assert_eq!(
+        serde_json::to_string(&summary).unwrap(),
+        r#"{"posted":["MTL","BOS"],"skipped":{"OTT":"eliminated from the playoffs"},"failed":{"TOR":"timed out"},"runtime_secs":2.5}"#
+    );
+}
+
+#[test]
+fn test_fit_comment() {
+    let md = "# Race\nintro\n## Standings\ntable\n## Schedule\ngames\n---\n### Disclaimer\nfine print\n";
+    assert_eq!(fit_comment(md, 1000), md);
+    assert_eq!(
+        fit_comment(md, 62),
+        "# Race\nintro\n## Standings\ntable\n---\n### Disclaimer\nfine print\n"
+    );
+    assert_eq!(fit_comment(md, 61), "# Race\nintro\n---\n### Disclaimer\nfine print\n");
+    assert_eq!(fit_comment(md, 10), "# Race\nint");
+}
+
+#[test]
+fn test_fill_title() {
+    let date = NaiveDate::from_ymd(2019, 3, 15);
+    assert_eq!(
+        fill_title(&default_title_template(), &date, || unreachable!()),
+        "Playoffs Race: March 15th, 2019"
+    );
+    assert_eq!(
+        fill_title("{summary} | {date}", &date, || "MTL: 62% to make playoffs".to_string()),
+        "MTL: 62% to make playoffs | March 15th, 2019"
+    );
+}
+
+#[test]
+fn test_fit_title() {
+    assert_eq!(fit_title("Playoffs Race".to_string(), 13), "Playoffs Race");
+    assert_eq!(fit_title("Playoffs Race".to_string(), 9), "Playoffs…");
+
+    // Multibyte characters right at the limit are never split.
+    let title = format!("{}Montréal", "x".repeat(TITLE_MAX_LEN - 7));
+    let fitted = fit_title(title, TITLE_MAX_LEN);
+    assert_eq!(fitted.chars().count(), TITLE_MAX_LEN);
+    assert!(fitted.ends_with("Montré…"));
+    assert_eq!(fit_title("Québec".repeat(60), 5), "Québ…");
+}
+
+#[test]
+fn test_retry_once() {
+    let mut calls = 0;
+    let result: Result<u32, String> = retry_once("a number", Duration::from_millis(0), || {
+        calls += 1;
+        if calls < 2 {
+            Err("timed out".to_string())
+        } else {
+            Ok(42)
+        }
+    });
+    assert_eq!(result, Ok(42));
+    assert_eq!(calls, 2);
+
+    let mut calls = 0;
+    let result: Result<u32, String> = retry_once("a number", Duration::from_millis(0), || {
+        calls += 1;
+        Err(format!("attempt {}", calls))
+    });
+    assert_eq!(result, Err("attempt 2".to_string()));
+}
+
+#[test]
+fn test_check_user_agent() {
+    assert!(check_user_agent("playoffsbot").is_ok());
+    assert!(check_user_agent("").is_err());
+    assert!(check_user_agent("  ").is_err());
+    assert!(check_user_agent("playoffsbot\n").is_err());
+}
+
+#[test]
+fn test_parse_whatif() {
+    let results = parse_whatif("MTL:W,mtl:OT,TOR:L").unwrap();
+    assert_eq!(
+        results,
+        vec![
+            ("MTL".to_string(), Event::Win),
+            ("mtl".to_string(), Event::OtLoss),
+            ("TOR".to_string(), Event::Loss),
+        ]
+    );
+
+    assert!(parse_whatif("MTL").is_err());
+    assert!(parse_whatif("MTL:X").is_err());
+    assert!(parse_whatif(":W").is_err());
+}
+
+#[test]
+fn test_profile() {
+    assert_eq!(format_count(0), "0");
+    assert_eq!(format_count(9_999), "9999");
+    assert_eq!(format_count(50_000), "50.0k");
+    assert_eq!(format_count(1_200_000), "1.2M");
+
+    let mut profile = Profile::default();
+    assert_eq!(profile.time("schedule", || 42), 42);
+    assert_eq!(profile.stages.len(), 1);
+    assert_eq!(profile.stages[0].0, "schedule");
+
+    let profile = Profile {
+        stages: vec![
+            ("schedule", Duration::from_millis(250), simulation::SimCount::default()),
+            (
+                "analysis",
+                Duration::from_millis(3400),
+                simulation::SimCount {
+                    simulations: 24,
+                    seasons: 1_200_000,
+                },
+            ),
+        ],
+    };
+    assert_eq!(
+        profile.to_string(),
+        "  schedule: 0.25s\n  analysis: 3.40s, ran 24 simulations totaling 1.2M sim-seasons\n"
+    );
+}
+
+#[test]
+fn test_check_team_abbrevs() {
+    let teams = nhlapi::teams::parse(include_str!("../tests/fixtures/teams.json")).unwrap();
+    let mut config = Config::example();
+    assert!(check_team_abbrevs(&config, &teams).is_ok());
+    config.pinned_results.insert(
+        2018021048,
+        simulation::PinnedResult {
+            winner: "mtl".to_string(),
+            loser: "NYR".to_string(),
+            overtime: false,
+        },
+    );
+    assert!(check_team_abbrevs(&config, &teams).is_ok());
+    config.rivals = vec!["tor".to_string(), "BOT".to_string()];
+    assert_eq!(
+        check_team_abbrevs(&config, &teams).unwrap_err().to_string(),
+        "unknown team \"BOT\" in rivals"
+    );
+    config.rivals.pop();
+    config.pinned_results.get_mut(&2018021048).unwrap().loser = "NYX".to_string();
+    assert_eq!(
+        check_team_abbrevs(&config, &teams).unwrap_err().to_string(),
+        "unknown team \"NYX\" in the pinned result of game 2018021048"
+    );
+}
+
+#[test]
+fn test_check_whatif_abbrevs() {
+    let teams = nhlapi::teams::parse(include_str!("../tests/fixtures/teams.json")).unwrap();
+    assert!(check_whatif_abbrevs(&parse_whatif("mtl:W,TOR:L").unwrap(), &teams).is_ok());
+    assert_eq!(
+        check_whatif_abbrevs(&parse_whatif("MTL:W,XYZ:W").unwrap(), &teams)
+            .unwrap_err()
+            .to_string(),
+        "unknown team \"XYZ\" in --whatif"
+    );
+}
+
+#[test]
+fn test_override_subreddits() {
+    let mut teams = nhlapi::teams::parse(include_str!("../tests/fixtures/teams.json")).unwrap();
+    let mut overrides = BTreeMap::new();
+    overrides.insert("mtl".to_string(), "playoffsbot_habs".to_string());
+    override_subreddits(&mut teams, &overrides).unwrap();
+
+    let mtl = teams.iter().find(|t| t.abbrev == "MTL").unwrap();
+    assert_eq!(mtl.subreddit, "playoffsbot_habs");
+    assert_eq!(mtl.display(NameStyle::Flair), "[](/r/playoffsbot_habs)MTL");
+    assert_eq!(post_subreddit(&Config::example(), mtl), "playoffsbot_habs");
+    let tor = teams.iter().find(|t| t.abbrev == "TOR").unwrap();
+    assert_eq!(tor.subreddit, "leafs");
+
+    // An unknown team fails without overriding any of the others.
+    overrides.insert("QUE".to_string(), "nordiques".to_string());
+    overrides.insert("TOR".to_string(), "playoffsbot_leafs".to_string());
+    assert!(override_subreddits(&mut teams, &overrides).is_err());
+    assert_eq!(teams.iter().find(|t| t.abbrev == "TOR").unwrap().subreddit, "leafs");
+}
+
+#[test]
+fn test_map_in_pool() {
+    let running = AtomicUsize::new(0);
+    let most_running = AtomicUsize::new(0);
+    let items: Vec<u32> = (0..20).collect();
+    let doubled = map_in_pool(&items, 3, |n| {
+        let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+        most_running.fetch_max(now, Ordering::SeqCst);
+        thread::sleep(Duration::from_millis(5));
+        running.fetch_sub(1, Ordering::SeqCst);
+        n * 2
+    });
+    assert_eq!(doubled, (0..20).map(|n| n * 2).collect::<Vec<_>>());
+    assert!(most_running.load(Ordering::SeqCst) <= 3);
+
+    assert_eq!(map_in_pool(&items, 0, |n: &u32| n + 1)[19], 20);
+    assert!(map_in_pool(&Vec::<u32>::new(), 4, |n| *n).is_empty());
+}
@@ -1,27 +1,379 @@
 use std::fmt::{self, Display, Write};
-use std::iter::Extend;
+use std::iter::{self, Extend};
 
-pub trait Element: Display {}
+pub trait Element: Display + RenderHtml {
+    /// What `Document::validate` needs to know about the element.
+    fn kind(&self) -> ElementKind {
+        ElementKind::Other
+    }
+}
+
+/// Kind of an element, for `Document::validate`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ElementKind {
+    /// Header of `level` 1 to 3.
+    Header {
+        level: u8,
+        text: String,
+    },
+    /// Table of `columns` columns, rendered padded if `pretty`.
+    Table {
+        columns: usize,
+        pretty: bool,
+    },
+    Other,
+}
+
+/// Problem found in a rendered document by `Document::validate`. `section` is
+/// the text of the header above the problem, empty before the first header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Issue {
+    /// A line of a table renders with `cells` cells while the table has
+    /// `columns`, such as when a cell holds a `|`.
+    TableColumns {
+        section: String,
+        columns: usize,
+        cells: usize,
+    },
+    /// A header with nothing under it before the next header of its level.
+    EmptySection { section: String },
+    /// A flair link without a subreddit, `[](/r/)`.
+    EmptyFlair { section: String },
+}
+
+impl Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Issue::TableColumns {
+                section,
+                columns,
+                cells,
+            } => write!(
+                f,
+                "table with {} columns has a line of {} cells under {:?}",
+                columns, cells, section
+            ),
+            Issue::EmptySection { section } => write!(f, "section {:?} is empty", section),
+            Issue::EmptyFlair { section } => write!(f, "flair link without a subreddit under {:?}", section),
+        }
+    }
+}
+
+/// Rendering of an element as HTML, for publishing outside of reddit.
+pub trait RenderHtml {
+    fn render_html(&self) -> String;
+}
 
 pub struct Document {
     buff: String,
+    html: String,
+    /// End of each element in `buff`.
+    ends: Vec<usize>,
+    /// Kind of each element, see `validate`.
+    kinds: Vec<ElementKind>,
+    /// Render the tables padded, see `Table::set_pretty`.
+    pretty: bool,
 }
 
 impl Document {
     pub fn new() -> Document {
-        Document { buff: String::new() }
+        Document {
+            buff: String::new(),
+            html: String::new(),
+            ends: Vec::new(),
+            kinds: Vec::new(),
+            pretty: false,
+        }
+    }
+
+    /// Render the tables added from now on padded, so that the raw markdown
+    /// lines up in a text editor.
+    pub fn set_pretty(&mut self, pretty: bool) {
+        self.pretty = pretty;
     }
 
     pub fn add<E>(&mut self, elem: E)
     where
         E: Element,
     {
-        let _ = write!(self.buff, "{}", elem);
+        let _ = if self.pretty {
+            write!(self.buff, "{:#}", elem)
+        } else {
+            write!(self.buff, "{}", elem)
+        };
+        self.html.push_str(&elem.render_html());
+        self.ends.push(self.buff.len());
+        self.kinds.push(match elem.kind() {
+            ElementKind::Table { columns, pretty } => ElementKind::Table {
+                columns,
+                pretty: pretty || self.pretty,
+            },
+            kind => kind,
+        });
+    }
+
+    /// Add `elem` only if there is one.
+    pub fn add_opt<E>(&mut self, elem: Option<E>)
+    where
+        E: Element,
+    {
+        if let Some(elem) = elem {
+            self.add(elem);
+        }
+    }
+
+    /// Add every element of a section, each as an element of its own.
+    pub fn add_section(&mut self, section: Section) {
+        for elem in section.elements {
+            self.add(elem);
+        }
     }
 
     pub fn as_str(&self) -> &str {
         &self.buff[..]
     }
+
+    /// The document rendered as HTML.
+    pub fn as_html(&self) -> &str {
+        &self.html[..]
+    }
+
+    /// Length of the markdown, in bytes.
+    pub fn len(&self) -> usize {
+        self.buff.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buff.is_empty()
+    }
+
+    /// Number of elements added to the document.
+    pub fn element_count(&self) -> usize {
+        self.ends.len()
+    }
+
+    /// Look for what would garble the post once rendered: table lines that
+    /// don't have as many cells as their table has columns, headers with
+    /// nothing under them, and flair links without a subreddit.
+    pub fn validate(&self) -> Vec<Issue> {
+        let mut issues = vec![];
+        let mut section = String::new();
+        let mut start = 0;
+        for (index, (&end, kind)) in self.ends.iter().zip(&self.kinds).enumerate() {
+            let elem = &self.buff[start..end];
+            start = end;
+            match kind {
+                ElementKind::Header { level, text } => {
+                    section = text.clone();
+                    let empty = match self.kinds.get(index + 1) {
+                        Some(ElementKind::Header { level: next, .. }) => next <= level,
+                        Some(_) => false,
+                        None => true,
+                    };
+                    if empty {
+                        issues.push(Issue::EmptySection {
+                            section: section.clone(),
+                        });
+                    }
+                }
+                &ElementKind::Table { columns, pretty } => {
+                    for line in elem.lines().filter(|line| !line.is_empty()) {
+                        let pipes = count_pipes(line);
+                        // Padded lines have a pipe on each end.
+                        let cells = if pretty { pipes.saturating_sub(1) } else { pipes + 1 };
+                        if cells != columns {
+                            issues.push(Issue::TableColumns {
+                                section: section.clone(),
+                                columns,
+                                cells,
+                            });
+                        }
+                    }
+                }
+                ElementKind::Other => {}
+            }
+            if elem.contains("[](/r/)") {
+                issues.push(Issue::EmptyFlair {
+                    section: section.clone(),
+                });
+            }
+        }
+        issues
+    }
+
+    /// Split the markdown into chunks of at most `max_len` bytes. Chunks end
+    /// between elements when possible, and otherwise between lines, so that
+    /// tables are not cut in the middle of a row.
+    pub fn into_chunks(&self, max_len: usize) -> Vec<String> {
+        let mut chunks = vec![];
+        let mut chunk = String::new();
+        let mut start = 0;
+        for &end in &self.ends {
+            let elem = &self.buff[start..end];
+            start = end;
+            if chunk.len() + elem.len() <= max_len {
+                chunk.push_str(elem);
+                continue;
+            }
+            if !chunk.is_empty() {
+                chunks.push(chunk);
+                chunk = String::new();
+            }
+            if elem.len() <= max_len {
+                chunk.push_str(elem);
+                continue;
+            }
+            for line in elem.split_inclusive('\n') {
+                if chunk.len() + line.len() > max_len && !chunk.is_empty() {
+                    chunks.push(chunk);
+                    chunk = String::new();
+                }
+                let mut line = line;
+                while line.len() > max_len {
+                    let mut cut = max_len;
+                    while !line.is_char_boundary(cut) {
+                        cut -= 1;
+                    }
+                    chunks.push(line[..cut].to_string());
+                    line = &line[cut..];
+                }
+                chunk.push_str(line);
+            }
+        }
+        if !chunk.is_empty() {
+            chunks.push(chunk);
+        }
+        chunks
+    }
+}
+
+impl Default for Document {
+    fn default() -> Document {
+        Document::new()
+    }
+}
+
+/// A header and the elements under it, built on its own and then added to a
+/// document as a whole, or not at all.
+pub struct Section {
+    elements: Vec<Box<dyn Element>>,
+}
+
+impl Section {
+    /// Section under a `H2` header.
+    pub fn new<D>(title: D) -> Section
+    where
+        D: Display,
+    {
+        let mut section = Section::untitled();
+        section.add(H2::new(title));
+        section
+    }
+
+    /// Section without a header, continuing the one before it.
+    pub fn untitled() -> Section {
+        Section { elements: vec![] }
+    }
+
+    pub fn add<E>(&mut self, elem: E)
+    where
+        E: Element + 'static,
+    {
+        self.elements.push(Box::new(elem));
+    }
+
+    /// Add `elem` only if there is one.
+    pub fn add_opt<E>(&mut self, elem: Option<E>)
+    where
+        E: Element + 'static,
+    {
+        if let Some(elem) = elem {
+            self.add(elem);
+        }
+    }
+
+    /// Add `elem`, or `fallback` if there is none.
+    pub fn add_or<E, F>(&mut self, elem: Option<E>, fallback: F)
+    where
+        E: Element + 'static,
+        F: Element + 'static,
+    {
+        match elem {
+            Some(elem) => self.add(elem),
+            None => self.add(fallback),
+        }
+    }
+}
+
+impl<E> RenderHtml for Box<E>
+where
+    E: RenderHtml + ?Sized,
+{
+    fn render_html(&self) -> String {
+        (**self).render_html()
+    }
+}
+
+impl<E> Element for Box<E>
+where
+    E: Element + ?Sized,
+{
+    fn kind(&self) -> ElementKind {
+        (**self).kind()
+    }
+}
+
+/// Number of pipes in a table line, leaving out the escaped ones.
+fn count_pipes(line: &str) -> usize {
+    let mut count = 0;
+    let mut escaped = false;
+    for c in line.chars() {
+        if c == '|' && !escaped {
+            count += 1;
+        }
+        escaped = c == '\\' && !escaped;
+    }
+    count
+}
+
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Escape text for HTML and turn its markdown links into anchors. Links
+/// without text, such as the flair links, degrade to the text around them.
+fn inline_html(text: &str) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('[') {
+        let link = rest[start..].find("](").and_then(|mid| {
+            let mid = start + mid;
+            rest[mid..].find(')').map(|end| (mid, mid + end))
+        });
+        let (mid, end) = match link {
+            Some(link) => link,
+            None => break,
+        };
+        out.push_str(&escape_html(&rest[..start]));
+        let label = &rest[start + 1..mid];
+        if !label.is_empty() {
+            let url = &rest[mid + 2..end];
+            let _ = write!(out, "<a href=\"{}\">{}</a>", escape_html(url), escape_html(label));
+        }
+        rest = &rest[end + 1..];
+    }
+    out.push_str(&escape_html(rest));
+    out
 }
 
 // Elements
@@ -44,6 +396,12 @@ impl Display for Paragraph {
     }
 }
 
+impl RenderHtml for Paragraph {
+    fn render_html(&self) -> String {
+        format!("<p>{}</p>\n", inline_html(&self.0))
+    }
+}
+
 impl Element for Paragraph {}
 
 /// H1 header
@@ -64,7 +422,20 @@ impl Display for H1 {
     }
 }
 
-impl Element for H1 {}
+impl RenderHtml for H1 {
+    fn render_html(&self) -> String {
+        format!("<h1>{}</h1>\n", inline_html(&self.0))
+    }
+}
+
+impl Element for H1 {
+    fn kind(&self) -> ElementKind {
+        ElementKind::Header {
+            level: 1,
+            text: self.0.clone(),
+        }
+    }
+}
 
 /// H2 header
 pub struct H2(String);
@@ -84,7 +455,20 @@ impl Display for H2 {
     }
 }
 
-impl Element for H2 {}
+impl RenderHtml for H2 {
+    fn render_html(&self) -> String {
+        format!("<h2>{}</h2>\n", inline_html(&self.0))
+    }
+}
+
+impl Element for H2 {
+    fn kind(&self) -> ElementKind {
+        ElementKind::Header {
+            level: 2,
+            text: self.0.clone(),
+        }
+    }
+}
 
 /// H3 header
 pub struct H3(String);
@@ -104,7 +488,20 @@ impl Display for H3 {
     }
 }
 
-impl Element for H3 {}
+impl RenderHtml for H3 {
+    fn render_html(&self) -> String {
+        format!("<h3>{}</h3>\n", inline_html(&self.0))
+    }
+}
+
+impl Element for H3 {
+    fn kind(&self) -> ElementKind {
+        ElementKind::Header {
+            level: 3,
+            text: self.0.clone(),
+        }
+    }
+}
 
 /// List
 pub struct List(Vec<String>);
@@ -122,6 +519,12 @@ impl List {
     }
 }
 
+impl Default for List {
+    fn default() -> List {
+        List::new()
+    }
+}
+
 impl<D> Extend<D> for List
 where
     D: Display,
@@ -157,21 +560,49 @@ impl Display for List {
     }
 }
 
+impl RenderHtml for List {
+    fn render_html(&self) -> String {
+        let mut out = String::from("<ul>\n");
+        for item in self.0.iter() {
+            let _ = write!(out, "<li>{}</li>\n", inline_html(item));
+        }
+        out.push_str("</ul>\n");
+        out
+    }
+}
+
 impl Element for List {}
 
 /// Numbered List
-pub struct NumberedList(Vec<String>);
+pub struct NumberedList {
+    items: Vec<String>,
+    start: usize,
+}
 
 impl NumberedList {
     pub fn new() -> NumberedList {
-        NumberedList(Vec::new())
+        NumberedList::starting_at(1)
+    }
+
+    /// List whose first item is numbered `start`, to continue a ranking.
+    pub fn starting_at(start: usize) -> NumberedList {
+        NumberedList {
+            items: Vec::new(),
+            start,
+        }
     }
 
     pub fn add<D>(&mut self, item: D)
     where
         D: Display,
     {
-        self.0.push(item.to_string())
+        self.items.push(item.to_string())
+    }
+}
+
+impl Default for NumberedList {
+    fn default() -> NumberedList {
+        NumberedList::new()
     }
 }
 
@@ -191,19 +622,67 @@ where
 
 impl Display for NumberedList {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        for (index, item) in self.0.iter().enumerate() {
-            write!(f, "{}. {}\n", index + 1, item)?;
+        for (index, item) in self.items.iter().enumerate() {
+            write!(f, "{}. {}\n", index + self.start, item)?;
         }
         write!(f, "\n")
     }
 }
 
+impl RenderHtml for NumberedList {
+    fn render_html(&self) -> String {
+        let mut out = if self.start == 1 {
+            String::from("<ol>\n")
+        } else {
+            format!("<ol start=\"{}\">\n", self.start)
+        };
+        for item in self.items.iter() {
+            let _ = write!(out, "<li>{}</li>\n", inline_html(item));
+        }
+        out.push_str("</ol>\n");
+        out
+    }
+}
+
 impl Element for NumberedList {}
 
+/// Error returned when a row does not have as many cells as the table has headers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableError {
+    pub headers: usize,
+    pub cells: usize,
+}
+
+impl Display for TableError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "row has {} cells but the table has {} headers",
+            self.cells, self.headers
+        )
+    }
+}
+
+impl std::error::Error for TableError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RowStyle {
+    Normal,
+    Bold,
+    Italic,
+}
+
 /// Table
+///
+/// Rendered compact by default. In pretty mode, set with `set_pretty` or the
+/// alternate flag (`{:#}`), the columns are padded to the width of their
+/// widest cell. Reddit ignores the extra spaces.
 pub struct Table {
     headers: Vec<String>,
     rows: Vec<Vec<String>>,
+    /// Style of the cells of each row.
+    styles: Vec<RowStyle>,
+    pretty: bool,
 }
 
 impl Table {
@@ -215,58 +694,169 @@ impl Table {
         Table {
             headers: headers.into_iter().map(|h| h.to_string()).collect(),
             rows: vec![],
+            styles: vec![],
+            pretty: false,
+        }
+    }
+
+    /// Whether the table has no rows, only headers.
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    pub fn set_pretty(&mut self, pretty: bool) {
+        self.pretty = pretty;
+    }
+
+    /// Build a table from its headers and all of its rows at once.
+    pub fn from_rows<H, R, D, I>(headers: H, rows: R) -> Result<Table, TableError>
+    where
+        H: IntoIterator,
+        H::Item: Display,
+        R: IntoIterator<Item = I>,
+        D: Display,
+        I: IntoIterator<Item = D>,
+    {
+        let mut table = Table::new(headers);
+        for row in rows {
+            table.add(row)?;
         }
+        Ok(table)
+    }
+
+    pub fn add<D, I>(&mut self, row: I) -> Result<(), TableError>
+    where
+        D: Display,
+        I: IntoIterator<Item = D>,
+    {
+        self.add_row_styled(row, false)
+    }
+
+    /// Add a row, with its cells in bold if `emphasized` is true.
+    pub fn add_row_styled<D, I>(&mut self, row: I, emphasized: bool) -> Result<(), TableError>
+    where
+        D: Display,
+        I: IntoIterator<Item = D>,
+    {
+        self.push_row(row, if emphasized { RowStyle::Bold } else { RowStyle::Normal })
     }
 
-    pub fn add<D, I>(&mut self, row: I)
+    /// Add a row with its cells in italics, for rows of lesser importance.
+    pub fn add_row_italic<D, I>(&mut self, row: I) -> Result<(), TableError>
+    where
+        D: Display,
+        I: IntoIterator<Item = D>,
+    {
+        self.push_row(row, RowStyle::Italic)
+    }
+
+    fn push_row<D, I>(&mut self, row: I, style: RowStyle) -> Result<(), TableError>
     where
         D: Display,
         I: IntoIterator<Item = D>,
     {
         let row: Vec<_> = row.into_iter().map(|i| i.to_string()).collect();
         if row.len() != self.headers.len() {
-            panic!("number of rows is not the same as the number of headers");
+            return Err(TableError {
+                headers: self.headers.len(),
+                cells: row.len(),
+            });
         }
         self.rows.push(row);
+        self.styles.push(style);
+        Ok(())
     }
 }
 
 impl Display for Table {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        for (index, header) in self.headers.iter().enumerate() {
-            if index > 0 {
-                write!(f, "|{}", header)?;
-            } else {
-                write!(f, "{}", header)?;
+        let rows: Vec<Vec<String>> = self
+            .rows
+            .iter()
+            .zip(&self.styles)
+            .map(|(row, &style)| {
+                row.iter()
+                    .map(|item| match style {
+                        _ if item.is_empty() => String::new(),
+                        RowStyle::Normal => item.clone(),
+                        RowStyle::Bold => format!("**{}**", item),
+                        RowStyle::Italic => format!("*{}*", item),
+                    })
+                    .collect()
+            })
+            .collect();
+
+        if !(self.pretty || f.alternate()) {
+            write!(f, "{}\n", self.headers.join("|"))?;
+            write!(f, "{}\n", vec![":---:"; self.headers.len()].join("|"))?;
+            for row in &rows {
+                write!(f, "{}\n", row.join("|"))?;
             }
+            return write!(f, "\n");
         }
-        write!(f, "\n")?;
 
-        for (index, _) in self.headers.iter().enumerate() {
-            if index > 0 {
-                write!(f, "|:---:")?;
-            } else {
-                write!(f, ":---:")?;
+        // The separator needs at least 3 dashes between its colons.
+        let widths: Vec<usize> = (0..self.headers.len())
+            .map(|index| {
+                iter::once(&self.headers[index])
+                    .chain(rows.iter().map(|row| &row[index]))
+                    .map(|cell| cell.chars().count())
+                    .fold(5, usize::max)
+            })
+            .collect();
+        let write_row = |f: &mut fmt::Formatter, cells: &[String]| -> fmt::Result {
+            for (cell, &width) in cells.iter().zip(&widths) {
+                write!(f, "| {}{} ", cell, " ".repeat(width - cell.chars().count()))?;
             }
+            write!(f, "|\n")
+        };
+
+        write_row(f, &self.headers)?;
+        let separator: Vec<_> = widths
+            .iter()
+            .map(|&width| format!(":{}:", "-".repeat(width - 2)))
+            .collect();
+        write_row(f, &separator)?;
+        for row in &rows {
+            write_row(f, row)?;
         }
-        write!(f, "\n")?;
+        write!(f, "\n")
+    }
+}
 
-        for row in self.rows.iter() {
-            for (index, item) in row.iter().enumerate() {
-                if index > 0 {
-                    write!(f, "|{}", item)?;
-                } else {
-                    write!(f, "{}", item)?;
-                }
+impl RenderHtml for Table {
+    fn render_html(&self) -> String {
+        let mut out = String::from("<table>\n<thead>\n<tr>");
+        for header in self.headers.iter() {
+            let _ = write!(out, "<th>{}</th>", inline_html(header));
+        }
+        out.push_str("</tr>\n</thead>\n<tbody>\n");
+        for (row, &style) in self.rows.iter().zip(&self.styles) {
+            out.push_str("<tr>");
+            for item in row.iter() {
+                let _ = match style {
+                    RowStyle::Bold if !item.is_empty() => {
+                        write!(out, "<td><strong>{}</strong></td>", inline_html(item))
+                    }
+                    RowStyle::Italic if !item.is_empty() => write!(out, "<td><em>{}</em></td>", inline_html(item)),
+                    _ => write!(out, "<td>{}</td>", inline_html(item)),
+                };
             }
-            write!(f, "\n")?;
+            out.push_str("</tr>\n");
         }
-
-        write!(f, "\n")
+        out.push_str("</tbody>\n</table>\n");
+        out
     }
 }
 
-impl Element for Table {}
+impl Element for Table {
+    fn kind(&self) -> ElementKind {
+        ElementKind::Table {
+            columns: self.headers.len(),
+            pretty: self.pretty,
+        }
+    }
+}
 
 /// Code
 pub struct Code(String);
@@ -290,6 +880,12 @@ impl Display for Code {
     }
 }
 
+impl RenderHtml for Code {
+    fn render_html(&self) -> String {
+        format!("<pre><code>{}</code></pre>\n", escape_html(&self.0))
+    }
+}
+
 impl Element for Code {}
 
 pub struct HR;
@@ -300,8 +896,75 @@ impl Display for HR {
     }
 }
 
+impl RenderHtml for HR {
+    fn render_html(&self) -> String {
+        "<hr>\n".to_string()
+    }
+}
+
 impl Element for HR {}
 
+/// Content written to the document verbatim, both as markdown and as HTML,
+/// for what the other elements don't model, such as a spoiler or a widget.
+///
+/// Nothing is escaped nor wrapped: the caller is responsible for the content
+/// being valid, and for ending it with the blank line the other elements end
+/// with if needed.
+pub struct Raw(String);
+
+impl Raw {
+    pub fn new<D>(content: D) -> Raw
+    where
+        D: Display,
+    {
+        Raw(content.to_string())
+    }
+}
+
+impl Display for Raw {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl RenderHtml for Raw {
+    fn render_html(&self) -> String {
+        self.0.clone()
+    }
+}
+
+impl Element for Raw {}
+
+#[test]
+fn test_add_opt() {
+    let mut doc = Document::new();
+    doc.add_opt(Some(Paragraph::new("hello")));
+    doc.add_opt(None::<Paragraph>);
+    assert_eq!(doc.as_str(), "hello\n\n");
+    assert_eq!(doc.element_count(), 1);
+}
+
+#[test]
+fn test_section() {
+    let mut section = Section::new("Tonight");
+    section.add(List::from(&["Our race:"]));
+    section.add_opt(None::<Table>);
+    section.add_or(None::<Table>, Paragraph::new("Nothing"));
+    let mut doc = Document::new();
+    doc.add_section(section);
+    let mut more = Section::untitled();
+    more.add(HR);
+    doc.add_section(more);
+
+    assert_eq!(doc.as_str(), "## Tonight\n* Our race:\n\nNothing\n\n---\n");
+    assert_eq!(
+        doc.as_html(),
+        "<h2>Tonight</h2>\n<ul>\n<li>Our race:</li>\n</ul>\n<p>Nothing</p>\n<hr>\n"
+    );
+    // The elements of a section can still be split into chunks.
+    assert_eq!(doc.element_count(), 4);
+}
+
 #[test]
 fn test_h1() {
     let mut doc = Document::new();
@@ -341,19 +1004,270 @@ fn test_numbered_list() {
     assert_eq!(doc.as_str(), "1. hello\n2. world\n\n");
 }
 
+#[test]
+fn test_numbered_list_start() {
+    let mut doc = Document::new();
+    let mut list = NumberedList::starting_at(9);
+    list.extend(&["CBJ", "NYR", "PHI"]);
+    doc.add(list);
+    assert_eq!(doc.as_str(), "9. CBJ\n10. NYR\n11. PHI\n\n");
+
+    let mut list = NumberedList::starting_at(9);
+    list.add("CBJ");
+    assert_eq!(list.render_html(), "<ol start=\"9\">\n<li>CBJ</li>\n</ol>\n");
+}
+
 #[test]
 fn test_table_format() {
     let mut doc = Document::new();
     let mut table = Table::new(&["and", "T", "F"]);
-    table.add(&["T", "T", "F"]);
-    table.add(&["F", "F", "F"]);
+    table.add(&["T", "T", "F"]).unwrap();
+    table.add(&["F", "F", "F"]).unwrap();
     doc.add(table);
     assert_eq!(doc.as_str(), "and|T|F\n:---:|:---:|:---:\nT|T|F\nF|F|F\n\n");
 }
 
+#[test]
+fn test_table_emphasized_row() {
+    let mut doc = Document::new();
+    let mut table = Table::new(&["Team", "", "Points"]);
+    table.add(&["MTL", "", "81"]).unwrap();
+    table.add_row_styled(&["TOR", "", "90"], true).unwrap();
+    doc.add(table);
+    assert_eq!(
+        doc.as_str(),
+        "Team||Points\n:---:|:---:|:---:\nMTL||81\n**TOR**||**90**\n\n"
+    );
+}
+
+#[test]
+fn test_table_italic_row() {
+    let mut doc = Document::new();
+    let mut table = Table::new(&["Team", "", "Points"]);
+    table.add(&["MTL", "", "81"]).unwrap();
+    table.add_row_italic(&["OTT", "", "56"]).unwrap();
+    doc.add(table);
+    assert_eq!(
+        doc.as_str(),
+        "Team||Points\n:---:|:---:|:---:\nMTL||81\n*OTT*||*56*\n\n"
+    );
+    assert!(doc
+        .as_html()
+        .contains("<tr><td><em>OTT</em></td><td></td><td><em>56</em></td></tr>"));
+}
+
+#[test]
+fn test_table_pretty() {
+    let mut table = Table::new(&["Team", "", "Points"]);
+    table.add(&["MTL", "", "81"]).unwrap();
+    table.add_row_styled(&["Montréal", "", "90"], true).unwrap();
+    table.set_pretty(true);
+    let out = table.to_string();
+    assert_eq!(
+        out,
+        "| Team         |       | Points |\n\
+         | :----------: | :---: | :----: |\n\
+         | MTL          |       | 81     |\n\
+         | **Montréal** |       | **90** |\n\n"
+    );
+
+    // Every line has its pipes at the same columns.
+    let pipes = |line: &str| -> Vec<usize> {
+        line.chars()
+            .enumerate()
+            .filter(|&(_, c)| c == '|')
+            .map(|(i, _)| i)
+            .collect()
+    };
+    let lines: Vec<_> = out.lines().filter(|l| !l.is_empty()).collect();
+    assert!(lines.iter().all(|l| pipes(l) == pipes(lines[0])));
+
+    // The alternate flag renders pretty too, and documents pass it on.
+    table.set_pretty(false);
+    assert_eq!(format!("{:#}", table), out);
+    let mut doc = Document::new();
+    doc.set_pretty(true);
+    doc.add(table);
+    assert_eq!(doc.as_str(), out);
+}
+
+#[test]
+fn test_table_from_rows() {
+    let table = Table::from_rows(&["and", "T", "F"], vec![["T", "T", "F"], ["F", "F", "F"]]).unwrap();
+    assert_eq!(table.to_string(), "and|T|F\n:---:|:---:|:---:\nT|T|F\nF|F|F\n\n");
+}
+
+#[test]
+fn test_table_column_count() {
+    let err = Table::from_rows(&["and", "T", "F"], vec![vec!["T", "T"]])
+        .err()
+        .unwrap();
+    assert_eq!(err, TableError { headers: 3, cells: 2 });
+
+    let mut table = Table::new(&["and", "T", "F"]);
+    assert!(table.add(&["T", "T", "F", "F"]).is_err());
+    assert!(table.add(&["T", "T", "F"]).is_ok());
+}
+
+#[test]
+fn test_raw() {
+    let content = ">!**spoiler** <b>&</b>!<\n|a|\n";
+    let mut doc = Document::new();
+    doc.add(Raw::new(content));
+    assert_eq!(doc.as_str(), content);
+    assert_eq!(doc.as_html(), content);
+}
+
 #[test]
 fn test_code() {
     let mut doc = Document::new();
     doc.add(Code::new("let x = 3;\nlet y = x**2;\n"));
     assert_eq!(doc.as_str(), "\n    let x = 3;\n    let y = x**2;\n\n");
 }
+
+#[test]
+fn test_html_headers() {
+    let mut doc = Document::new();
+    doc.add(H1::new("hello"));
+    doc.add(H2::new("hello"));
+    doc.add(H3::new("<hello>"));
+    assert_eq!(
+        doc.as_html(),
+        "<h1>hello</h1>\n<h2>hello</h2>\n<h3>&lt;hello&gt;</h3>\n"
+    );
+}
+
+#[test]
+fn test_html_paragraph_links() {
+    let mut doc = Document::new();
+    doc.add(Paragraph::new(
+        "[](/r/habs)MTL & [the source](https://example.com/?a=1&b=2).",
+    ));
+    assert_eq!(
+        doc.as_html(),
+        "<p>MTL &amp; <a href=\"https://example.com/?a=1&amp;b=2\">the source</a>.</p>\n"
+    );
+}
+
+#[test]
+fn test_html_lists() {
+    let mut doc = Document::new();
+    doc.add(List::from(&["hello", "world"]));
+    let mut list = NumberedList::new();
+    list.extend(&["hello", "world"]);
+    doc.add(list);
+    assert_eq!(
+        doc.as_html(),
+        "<ul>\n<li>hello</li>\n<li>world</li>\n</ul>\n<ol>\n<li>hello</li>\n<li>world</li>\n</ol>\n"
+    );
+}
+
+#[test]
+fn test_html_table() {
+    let mut doc = Document::new();
+    let mut table = Table::new(&["Team", "Points"]);
+    table.add(&["[](/r/habs)MTL", "81"]).unwrap();
+    table.add_row_styled(&["TOR", "90"], true).unwrap();
+    doc.add(table);
+    assert_eq!(
+        doc.as_html(),
+        "<table>\n<thead>\n<tr><th>Team</th><th>Points</th></tr>\n</thead>\n<tbody>\n\
+         <tr><td>MTL</td><td>81</td></tr>\n\
+         <tr><td><strong>TOR</strong></td><td><strong>90</strong></td></tr>\n\
+         </tbody>\n</table>\n"
+    );
+}
+
+#[test]
+fn test_html_code_hr() {
+    let mut doc = Document::new();
+    doc.add(Code::new("let y = x < 2;\n"));
+    doc.add(HR);
+    assert_eq!(doc.as_html(), "<pre><code>let y = x &lt; 2;\n</code></pre>\n<hr>\n");
+}
+
+#[test]
+fn test_document_len() {
+    let mut doc = Document::new();
+    assert!(doc.is_empty());
+    assert_eq!(doc.element_count(), 0);
+    doc.add(H1::new("hello"));
+    doc.add(Paragraph::new("world"));
+    assert!(!doc.is_empty());
+    assert_eq!(doc.len(), doc.as_str().len());
+    assert_eq!(doc.element_count(), 2);
+}
+
+#[test]
+fn test_document_into_chunks() {
+    let mut doc = Document::new();
+    doc.add(H1::new("hello"));
+    let mut table = Table::new(&["a", "b"]);
+    for _ in 0..4 {
+        table.add(&["1", "2"]).unwrap();
+    }
+    doc.add(table);
+    doc.add(Paragraph::new("world"));
+
+    assert_eq!(doc.into_chunks(1000), vec![doc.as_str().to_string()]);
+    assert_eq!(doc.into_chunks(doc.len()).concat(), doc.as_str());
+
+    // The table is too long for a chunk, so it is split between rows.
+    let chunks = doc.into_chunks(20);
+    assert_eq!(chunks.concat(), doc.as_str());
+    assert!(chunks.iter().all(|c| c.len() <= 20));
+    assert!(chunks.iter().all(|c| c.ends_with('\n')));
+    assert_eq!(chunks[0], "# hello\n");
+
+    // Lines longer than a chunk are cut as a last resort.
+    let chunks = doc.into_chunks(4);
+    assert_eq!(chunks.concat(), doc.as_str());
+    assert!(chunks.iter().all(|c| c.len() <= 4));
+}
+
+#[test]
+fn test_validate() {
+    let mut doc = Document::new();
+    doc.add(H1::new("Race"));
+    doc.add(H2::new("Standings"));
+    let mut table = Table::new(&["", "Team", "Points"]);
+    table.add(&["1", "[](/r/habs)MTL", "81"]).unwrap();
+    doc.add(table);
+    let mut table = Table::new(&["Team", "Points"]);
+    table.set_pretty(true);
+    table.add(&["MTL \\| TOR", "81"]).unwrap();
+    doc.add(table);
+    doc.add(H2::new("Tonight"));
+    doc.add(H3::new("Our game"));
+    doc.add(Paragraph::new("MTL at TBL"));
+    assert_eq!(doc.validate(), vec![]);
+
+    let mut doc = Document::new();
+    doc.add(H2::new("Standings"));
+    let mut table = Table::new(&["Team", "Points"]);
+    table.add(&["MTL | TOR", "81"]).unwrap();
+    doc.add(table);
+    doc.add(H2::new("Tonight"));
+    doc.add(H2::new("Last night"));
+    doc.add(Paragraph::new("[](/r/)ATL at MTL"));
+    doc.add(H2::new("Odds"));
+    assert_eq!(
+        doc.validate(),
+        vec![
+            Issue::TableColumns {
+                section: "Standings".to_string(),
+                columns: 2,
+                cells: 3,
+            },
+            Issue::EmptySection {
+                section: "Tonight".to_string(),
+            },
+            Issue::EmptyFlair {
+                section: "Last night".to_string(),
+            },
+            Issue::EmptySection {
+                section: "Odds".to_string(),
+            },
+        ]
+    );
+}
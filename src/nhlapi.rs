@@ -3,6 +3,7 @@
 use std::fmt::Display;
 use std::str::FromStr;
 
+use chrono::{Datelike, NaiveDate};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 fn from_str<'de, T, D>(deserializer: D) -> Result<T, D::Error>
@@ -15,22 +16,50 @@ where
     T::from_str(&s).map_err(serde::de::Error::custom)
 }
 
-#[derive(Debug, Clone)]
+/// Like `from_str`, for the ranks of the standings. The API leaves some ranks
+/// empty, such as the wildcard rank of teams out of the wildcard race, so
+/// anything that is not a number is rank 0.
+fn rank_from_str<'de, D>(deserializer: D) -> Result<u32, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    Ok(s.trim().parse().unwrap_or(0))
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Season {
     pub begin: u32,
     pub end: u32,
 }
 
+impl Season {
+    /// The season that `date` belongs to. Summer days belong to the upcoming season.
+    pub fn of(date: &NaiveDate) -> Season {
+        let end = if date.month() < 7 { date.year() } else { date.year() + 1 } as u32;
+        Season { begin: end - 1, end }
+    }
+
+    /// Regular season bounds to use when the seasons endpoint is unavailable,
+    /// from October 1st to May 1st.
+    pub fn default_bounds(&self) -> (NaiveDate, NaiveDate) {
+        (
+            NaiveDate::from_ymd(self.begin as i32, 10, 1),
+            NaiveDate::from_ymd(self.end as i32, 5, 1),
+        )
+    }
+}
+
 impl<'de> Deserialize<'de> for Season {
     fn deserialize<D>(deserializer: D) -> Result<Season, D::Error>
     where
         D: Deserializer<'de>,
     {
         let s = String::deserialize(deserializer)?;
-        if s.len() == 8 && s.chars().all(|c| c.is_digit(10)) {
+        if s.len() == 8 && s.chars().all(|c| c.is_ascii_digit()) {
             Ok(Season {
-                begin: s[..4].parse().unwrap(),
-                end: s[4..].parse().unwrap(),
+                begin: s[..4].parse().map_err(serde::de::Error::custom)?,
+                end: s[4..].parse().map_err(serde::de::Error::custom)?,
             })
         } else {
             Err(serde::de::Error::custom("invalid season"))
@@ -60,6 +89,62 @@ pub struct Team {
     pub name: String,
 }
 
+/// NHL API the data is downloaded from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    /// statsapi.web.nhl.com, which the NHL has retired.
+    #[default]
+    StatsApi,
+    /// api-web.nhle.com and api.nhle.com/stats/rest, see the `web` module.
+    Web,
+}
+
+impl Backend {
+    /// The teams as they were on `date`, with that season's divisions and
+    /// conferences.
+    pub fn teams(self, date: &NaiveDate) -> Result<Vec<teams::Team>, standings::Error> {
+        match self {
+            Backend::StatsApi => Ok(teams::get_season(&Season::of(date))?),
+            Backend::Web => web::get_teams(date),
+        }
+    }
+
+    /// The standings of the league on `date`. The web backend needs the teams
+    /// to find the ids of the teams of the standings.
+    pub fn standings(
+        self,
+        date: &NaiveDate,
+        teams: &[teams::Team],
+    ) -> Result<Vec<standings::TeamRecord>, standings::Error> {
+        match self {
+            Backend::StatsApi => standings::get(date),
+            Backend::Web => web::get_standings(date, teams),
+        }
+    }
+
+    /// The games of `date`.
+    pub fn schedule(self, date: &NaiveDate) -> attohttpc::Result<schedule::Date> {
+        match self {
+            Backend::StatsApi => schedule::get(date),
+            Backend::Web => web::get_schedule(date),
+        }
+    }
+
+    /// The games of `team` from `begin` to `end`, inclusively.
+    pub fn schedule_range(
+        self,
+        team: &teams::Team,
+        begin: &NaiveDate,
+        end: &NaiveDate,
+    ) -> attohttpc::Result<Vec<schedule::Date>> {
+        match self {
+            Backend::StatsApi => schedule::get_range(team.id, begin, end),
+            Backend::Web => web::get_range(team, begin, end),
+        }
+    }
+}
+
 pub mod schedule {
     use chrono::{DateTime, Local, NaiveDate, TimeZone, Utc};
     use serde::{Deserialize, Serialize};
@@ -87,7 +172,30 @@ pub mod schedule {
         #[serde(rename = "gameDate")]
         pub game_date: DateTime<Utc>,
         pub teams: Teams,
+        /// Games far in the future sometimes come without a linescore.
+        #[serde(default)]
         pub linescore: LineScore,
+        #[serde(default)]
+        pub status: Status,
+        /// Only given for playoffs games.
+        #[serde(rename = "seriesSummary", default)]
+        pub series_summary: Option<SeriesSummary>,
+    }
+
+    #[derive(Debug, Clone, Deserialize, Serialize)]
+    pub struct SeriesSummary {
+        #[serde(rename = "gameNumber")]
+        pub game_number: u32,
+        /// Such as `TBL leads 1-0`.
+        #[serde(rename = "seriesStatusShort", default)]
+        pub series_status_short: String,
+    }
+
+    #[derive(Debug, Clone, Default, Deserialize, Serialize)]
+    pub struct Status {
+        /// One of `Preview`, `Live` or `Final`.
+        #[serde(rename = "abstractGameState", default)]
+        pub abstract_game_state: String,
     }
 
     impl Game {
@@ -132,11 +240,24 @@ pub mod schedule {
         }
 
         pub fn overtime(&self) -> bool {
-            self.linescore.current_period > 3
+            self.linescore.has_periods() && self.linescore.current_period > 3
         }
 
         pub fn shootout(&self) -> bool {
-            self.linescore.current_period > 4
+            self.linescore.has_periods() && self.linescore.current_period > 4
+        }
+
+        pub fn is_playoffs(&self) -> bool {
+            self.game_type == "P"
+        }
+
+        /// Whether the game is being played right now, intermissions included.
+        pub fn is_live(&self) -> bool {
+            self.status.abstract_game_state == "Live"
+        }
+
+        pub fn is_final(&self) -> bool {
+            self.status.abstract_game_state == "Final"
         }
     }
 
@@ -154,11 +275,28 @@ pub mod schedule {
         pub score: u32,
     }
 
-    #[derive(Debug, Clone, Deserialize, Serialize)]
+    #[derive(Debug, Clone, Default, Deserialize, Serialize)]
     pub struct LineScore {
-        #[serde(rename = "currentPeriod")]
+        #[serde(rename = "currentPeriod", default)]
         pub current_period: u32,
+        #[serde(default)]
         pub periods: Vec<Period>,
+        #[serde(rename = "intermissionInfo", default)]
+        pub intermission_info: IntermissionInfo,
+    }
+
+    #[derive(Debug, Clone, Default, Deserialize, Serialize)]
+    pub struct IntermissionInfo {
+        #[serde(rename = "inIntermission", default)]
+        pub in_intermission: bool,
+    }
+
+    impl LineScore {
+        /// Whether the linescore has real period data, which is not the case
+        /// for games that haven't started yet.
+        pub fn has_periods(&self) -> bool {
+            !self.periods.is_empty()
+        }
     }
 
     #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -170,35 +308,124 @@ pub mod schedule {
     pub fn get(date: &NaiveDate) -> attohttpc::Result<Date> {
         let sdate = format!("{}", date.format("%Y-%m-%d"));
 
-        let root: Root = attohttpc::get("https://statsapi.web.nhl.com/api/v1/schedule?expand=schedule.linescore")
-            .params(&[("date", sdate)])
-            .send()?
-            .json()?;
+        let root: Root = attohttpc::get(
+            "https://statsapi.web.nhl.com/api/v1/schedule?expand=schedule.linescore,schedule.game.seriesSummary",
+        )
+        .params(&[("date", sdate)])
+        .send()?
+        .json()?;
+
+        Ok(single_date(root, date))
+    }
 
+    /// Parse a schedule response for a single day, the same way `get` does.
+    pub fn parse_date(json: &str, date: &NaiveDate) -> serde_json::Result<Date> {
+        let root: Root = serde_json::from_str(json)?;
+        Ok(single_date(root, date))
+    }
+
+    fn single_date(root: Root, date: &NaiveDate) -> Date {
         // We there are no games scheduled for a day, for instance around Christmas,
         // there is no dates returned. To make this problem transparent we return
         // a mock date with no games.
-        Ok(root.dates.into_iter().next().unwrap_or_else(|| Date {
+        root.dates.into_iter().next().unwrap_or_else(|| Date {
             date: date.clone(),
             games: vec![],
-        }))
+        })
     }
 
     pub fn get_range(team_id: u32, begin: &NaiveDate, end: &NaiveDate) -> attohttpc::Result<Vec<Date>> {
         let begin = format!("{}", begin.format("%Y-%m-%d"));
         let end = format!("{}", end.format("%Y-%m-%d"));
 
-        let root: Root = attohttpc::get("https://statsapi.web.nhl.com/api/v1/schedule?expand=schedule.linescore")
-            .params(&[
-                ("teamId", format!("{}", team_id)),
-                ("startDate", begin),
-                ("endDate", end),
-            ])
-            .send()?
-            .json()?;
+        let root: Root = attohttpc::get(
+            "https://statsapi.web.nhl.com/api/v1/schedule?expand=schedule.linescore,schedule.game.seriesSummary",
+        )
+        .params(&[
+            ("teamId", format!("{}", team_id)),
+            ("startDate", begin),
+            ("endDate", end),
+        ])
+        .send()?
+        .json()?;
+        Ok(root.dates)
+    }
+
+    /// Parse a schedule response spanning multiple days, the same way `get_range` does.
+    pub fn parse_range(json: &str) -> serde_json::Result<Vec<Date>> {
+        let root: Root = serde_json::from_str(json)?;
         Ok(root.dates)
     }
 
+    /// Local dates spanned by `games` in timezone `tz`, for section headers:
+    /// `Mon Mar 4 – Tue Mar 5`, or just `Mon Mar 4` when they are all on the
+    /// same day. `None` without any game.
+    pub fn format_date_range<'a, I, T>(games: I, tz: &T) -> Option<String>
+    where
+        I: IntoIterator<Item = &'a Game>,
+        T: TimeZone,
+    {
+        let mut dates = games
+            .into_iter()
+            .map(|g| g.game_date.with_timezone(tz).naive_local().date());
+        let first = dates.next()?;
+        let (first, last) = dates.fold((first, first), |(first, last), date| (first.min(date), last.max(date)));
+        let format = |date: NaiveDate| date.format("%a %b %-d").to_string();
+        if first == last {
+            Some(format(first))
+        } else {
+            Some(format!("{} – {}", format(first), format(last)))
+        }
+    }
+
+    /// Keep only the regular season games happening on or before `end`.
+    pub fn regular_season(dates: Vec<Date>, end: &NaiveDate) -> Vec<Date> {
+        dates
+            .into_iter()
+            .filter(|d| d.date <= *end)
+            .map(|d| Date {
+                date: d.date,
+                games: d.games.into_iter().filter(|g| g.game_type == "R").collect(),
+            })
+            .filter(|d| !d.games.is_empty())
+            .collect()
+    }
+
+    /// Every regular season game of a team in `season`. The season bounds
+    /// default to `Season::default_bounds` when the seasons endpoint fails.
+    pub fn get_season(team_id: u32, season: &Season) -> attohttpc::Result<Vec<Date>> {
+        let (begin, end) = season_bounds(season);
+        let dates = get_range(team_id, &begin, &end)?;
+        Ok(regular_season(dates, &end))
+    }
+
+    /// The regular season games of a team from `from` until the end of its
+    /// season that are not final yet.
+    pub fn remaining(team_id: u32, from: &NaiveDate) -> attohttpc::Result<Vec<Date>> {
+        let (_, end) = season_bounds(&Season::of(from));
+        let dates = get_range(team_id, from, &end)?;
+        Ok(not_final(regular_season(dates, &end)))
+    }
+
+    fn season_bounds(season: &Season) -> (NaiveDate, NaiveDate) {
+        match super::seasons::get(season) {
+            Ok(season) => (season.regular_season_start_date, season.regular_season_end_date),
+            Err(_) => season.default_bounds(),
+        }
+    }
+
+    /// Keep only the games that are not final, dropping the days left empty.
+    pub fn not_final(dates: Vec<Date>) -> Vec<Date> {
+        dates
+            .into_iter()
+            .map(|d| Date {
+                date: d.date,
+                games: d.games.into_iter().filter(|g| !g.is_final()).collect(),
+            })
+            .filter(|d| !d.games.is_empty())
+            .collect()
+    }
+
     pub fn today() -> attohttpc::Result<Date> {
         get(&Local::today().naive_local())
     }
@@ -208,11 +435,73 @@ pub mod schedule {
     }
 }
 
+pub mod seasons {
+    use chrono::NaiveDate;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Clone, Deserialize, Serialize)]
+    struct Root {
+        seasons: Vec<Season>,
+    }
+
+    #[derive(Debug, Clone, Deserialize, Serialize)]
+    pub struct Season {
+        #[serde(rename = "seasonId")]
+        pub id: super::Season,
+        #[serde(rename = "regularSeasonStartDate")]
+        pub regular_season_start_date: NaiveDate,
+        #[serde(rename = "regularSeasonEndDate")]
+        pub regular_season_end_date: NaiveDate,
+        #[serde(rename = "seasonEndDate")]
+        pub season_end_date: NaiveDate,
+        #[serde(rename = "numberOfGames")]
+        pub number_of_games: u32,
+    }
+
+    pub fn current() -> attohttpc::Result<Season> {
+        let root: Root = attohttpc::get("https://statsapi.web.nhl.com/api/v1/seasons/current")
+            .send()?
+            .json()?;
+        first_season(root)
+    }
+
+    pub fn get(id: &super::Season) -> attohttpc::Result<Season> {
+        let root: Root = attohttpc::get(format!(
+            "https://statsapi.web.nhl.com/api/v1/seasons/{:04}{:04}",
+            id.begin, id.end
+        ))
+        .send()?
+        .json()?;
+        first_season(root)
+    }
+
+    /// Parse a seasons response, the same way `current` does.
+    pub fn parse(json: &str) -> attohttpc::Result<Season> {
+        let root: Root = serde_json::from_str(json)?;
+        first_season(root)
+    }
+
+    fn first_season(root: Root) -> attohttpc::Result<Season> {
+        root.seasons.into_iter().next().ok_or_else(|| {
+            attohttpc::Error::from(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "no season returned",
+            ))
+        })
+    }
+}
+
 pub mod standings {
+    use std::fmt;
+
     use chrono::{Local, NaiveDate};
     use serde::{Deserialize, Serialize};
 
-    use super::{from_str, LeagueRecord, Team};
+    use super::teams::{Conference, Division};
+    use super::{rank_from_str, LeagueRecord, Team};
+
+    /// Length of a streak for which a team is considered hot or cold.
+    pub const HOT_STREAK: u32 = 3;
 
     #[derive(Debug, Clone, Deserialize, Serialize)]
     struct Root {
@@ -225,6 +514,34 @@ pub mod standings {
         pub team_records: Vec<TeamRecord>,
     }
 
+    #[derive(Debug, Clone, Deserialize, Serialize)]
+    struct WildcardRoot {
+        pub records: Vec<Grouping>,
+    }
+
+    /// Group of teams from the wildcard standings, either the leaders of a
+    /// division or the wildcard race of a conference, in the NHL's order.
+    #[derive(Debug, Clone, Deserialize, Serialize)]
+    pub struct Grouping {
+        #[serde(rename = "standingsType")]
+        pub standings_type: String,
+        pub conference: Option<Conference>,
+        #[serde(default)]
+        pub division: Option<Division>,
+        #[serde(rename = "teamRecords")]
+        pub team_records: Vec<TeamRecord>,
+    }
+
+    impl Grouping {
+        pub fn is_division_leaders(&self) -> bool {
+            self.standings_type == "divisionLeaders"
+        }
+
+        pub fn is_wildcard(&self) -> bool {
+            self.standings_type == "wildCard"
+        }
+    }
+
     #[derive(Debug, Clone, Deserialize, Serialize)]
     pub struct TeamRecord {
         pub team: Team,
@@ -237,19 +554,24 @@ pub mod standings {
         pub goals_scored: u32,
         pub points: u32,
         pub row: u32,
+        /// Regulation wins, only given since the 2019-20 season.
+        #[serde(rename = "regulationWins", default)]
+        pub regulation_wins: u32,
         #[serde(rename = "gamesPlayed")]
         pub games_played: u32,
 
-        #[serde(rename = "divisionRank", deserialize_with = "from_str")]
+        #[serde(rename = "divisionRank", default, deserialize_with = "rank_from_str")]
         pub division_rank: u32,
-        #[serde(rename = "conferenceRank", deserialize_with = "from_str")]
+        #[serde(rename = "conferenceRank", default, deserialize_with = "rank_from_str")]
         pub conference_rank: u32,
-        #[serde(rename = "leagueRank", deserialize_with = "from_str")]
+        #[serde(rename = "leagueRank", default, deserialize_with = "rank_from_str")]
         pub league_rank: u32,
-        #[serde(rename = "wildCardRank", deserialize_with = "from_str")]
+        #[serde(rename = "wildCardRank", default, deserialize_with = "rank_from_str")]
         pub wildcard_rank: u32,
 
         pub records: Records,
+        #[serde(default)]
+        pub streak: Option<Streak>,
     }
 
     impl TeamRecord {
@@ -261,90 +583,400 @@ pub mod standings {
         }
 
         pub fn last10(&self) -> Option<String> {
-            self.records
-                .overall_records
-                .iter()
-                .find(|x| x.kind == "lastTen")
+            self.last10_record()
                 .map(|x| format!("{}-{}-{}", x.wins, x.losses, x.ot))
         }
 
+        pub fn last10_record(&self) -> Option<&Record> {
+            self.records.overall_records.iter().find(|x| x.kind == "lastTen")
+        }
+
+        pub fn streak(&self) -> Option<String> {
+            self.streak.as_ref().map(|x| {
+                let code = match x.kind.as_str() {
+                    "wins" => "W",
+                    "losses" => "L",
+                    _ => "OT",
+                };
+                format!("{}{}", code, x.number)
+            })
+        }
+
+        /// Whether the team is on a winning streak of `HOT_STREAK` games or more.
+        pub fn is_hot(&self) -> bool {
+            self.streak
+                .as_ref()
+                .map(|x| x.kind == "wins" && x.number >= HOT_STREAK)
+                .unwrap_or(false)
+        }
+
+        /// Whether the team is on a losing streak of `HOT_STREAK` games or more,
+        /// not counting overtime losses.
+        pub fn is_cold(&self) -> bool {
+            self.streak
+                .as_ref()
+                .map(|x| x.kind == "losses" && x.number >= HOT_STREAK)
+                .unwrap_or(false)
+        }
+
         pub fn point_percent(&self) -> String {
             format!("{:.3}", self.points as f64 / (self.games_played * 2) as f64)
         }
 
+        /// Points at the end of an 82 game season, at the current pace.
+        pub fn pace_82(&self) -> f64 {
+            (self.points as f64 / self.games_played as f64) * 82.0
+        }
+
         pub fn point_82(&self) -> String {
-            format!("{:.0}", (self.points as f64 / self.games_played as f64) * 82.0)
+            format!("{:.0}", self.pace_82())
         }
     }
 
     #[derive(Debug, Clone, Deserialize, Serialize)]
     pub struct Records {
         #[serde(rename = "overallRecords")]
-        overall_records: Vec<Record>,
+        pub(super) overall_records: Vec<Record>,
     }
 
     #[derive(Debug, Clone, Deserialize, Serialize)]
     pub struct Record {
-        wins: u32,
-        losses: u32,
+        pub wins: u32,
+        pub losses: u32,
         #[serde(default)]
-        ot: u32,
+        pub ot: u32,
         #[serde(rename = "type")]
-        kind: String,
+        pub(super) kind: String,
     }
 
-    pub fn get(date: &NaiveDate) -> attohttpc::Result<Vec<TeamRecord>> {
+    #[derive(Debug, Clone, Deserialize, Serialize)]
+    pub struct Streak {
+        #[serde(rename = "streakType")]
+        pub(super) kind: String,
+        #[serde(rename = "streakNumber")]
+        pub(super) number: u32,
+    }
+
+    /// Error getting the standings.
+    #[derive(Debug)]
+    pub enum Error {
+        Http(attohttpc::Error),
+        Json(serde_json::Error),
+        /// The response has no standings, such as for a date out of any season.
+        MissingStandings,
+    }
+
+    impl fmt::Display for Error {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                Error::Http(e) => write!(f, "{}", e),
+                Error::Json(e) => write!(f, "{}", e),
+                Error::MissingStandings => write!(f, "the response has no standings"),
+            }
+        }
+    }
+
+    impl std::error::Error for Error {}
+
+    impl From<attohttpc::Error> for Error {
+        fn from(e: attohttpc::Error) -> Error {
+            Error::Http(e)
+        }
+    }
+
+    impl From<serde_json::Error> for Error {
+        fn from(e: serde_json::Error) -> Error {
+            Error::Json(e)
+        }
+    }
+
+    pub fn get(date: &NaiveDate) -> Result<Vec<TeamRecord>, Error> {
         let date = format!("{}", date.format("%Y-%m-%d"));
-        let mut root: Root =
+        let root: Root =
             attohttpc::get("https://statsapi.web.nhl.com/api/v1/standings/byLeague?expand=standings.record")
                 .params(&[("date", date)])
                 .send()?
                 .json()?;
-        Ok(root.records.remove(0).team_records)
+        league_records(root)
+    }
+
+    /// Parse a standings response, the same way `get` does.
+    pub fn parse(json: &str) -> Result<Vec<TeamRecord>, Error> {
+        let root: Root = serde_json::from_str(json)?;
+        league_records(root)
+    }
+
+    /// The records of the whole league, the only grouping of the response.
+    fn league_records(root: Root) -> Result<Vec<TeamRecord>, Error> {
+        root.records
+            .into_iter()
+            .next()
+            .map(|records| records.team_records)
+            .ok_or(Error::MissingStandings)
+    }
+
+    /// Get the official division leaders and wildcard groupings.
+    pub fn get_wildcard(date: &NaiveDate) -> attohttpc::Result<Vec<Grouping>> {
+        let date = format!("{}", date.format("%Y-%m-%d"));
+        let root: WildcardRoot =
+            attohttpc::get("https://statsapi.web.nhl.com/api/v1/standings/wildCardWithLeaders?expand=standings.record")
+                .params(&[("date", date)])
+                .send()?
+                .json()?;
+        Ok(root.records)
     }
 
-    pub fn today() -> attohttpc::Result<Vec<TeamRecord>> {
+    /// Parse a wildcard standings response, the same way `get_wildcard` does.
+    pub fn parse_wildcard(json: &str) -> serde_json::Result<Vec<Grouping>> {
+        let root: WildcardRoot = serde_json::from_str(json)?;
+        Ok(root.records)
+    }
+
+    pub fn today() -> Result<Vec<TeamRecord>, Error> {
         get(&Local::today().naive_local())
     }
 
-    pub fn yesterday() -> attohttpc::Result<Vec<TeamRecord>> {
+    pub fn yesterday() -> Result<Vec<TeamRecord>, Error> {
         get(&Local::today().naive_local().pred())
     }
+
+    pub fn wildcard_today() -> attohttpc::Result<Vec<Grouping>> {
+        get_wildcard(&Local::today().naive_local())
+    }
+}
+
+pub mod game {
+    use std::collections::BTreeMap;
+
+    use serde::{Deserialize, Serialize};
+
+    use super::schedule::Status;
+
+    #[derive(Debug, Clone, Deserialize, Serialize)]
+    struct Root {
+        #[serde(rename = "liveData")]
+        pub live_data: LiveData,
+    }
+
+    #[derive(Debug, Clone, Deserialize, Serialize)]
+    struct LiveData {
+        #[serde(default)]
+        pub decisions: Decisions,
+    }
+
+    /// Goalie decisions and three stars of a game, once it is over.
+    #[derive(Debug, Clone, Default, Deserialize, Serialize)]
+    pub struct Decisions {
+        pub winner: Option<Player>,
+        pub loser: Option<Player>,
+        #[serde(rename = "firstStar")]
+        pub first_star: Option<Player>,
+        #[serde(rename = "secondStar")]
+        pub second_star: Option<Player>,
+        #[serde(rename = "thirdStar")]
+        pub third_star: Option<Player>,
+    }
+
+    impl Decisions {
+        /// The three stars, if they were all named.
+        pub fn three_stars(&self) -> Option<[&Player; 3]> {
+            match (&self.first_star, &self.second_star, &self.third_star) {
+                (Some(first), Some(second), Some(third)) => Some([first, second, third]),
+                _ => None,
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, Deserialize, Serialize)]
+    pub struct Player {
+        pub id: u32,
+        #[serde(rename = "fullName")]
+        pub full_name: String,
+    }
+
+    pub fn get_decisions(game_pk: u64) -> attohttpc::Result<Decisions> {
+        let root: Root = attohttpc::get(format!(
+            "https://statsapi.web.nhl.com/api/v1/game/{}/feed/live",
+            game_pk
+        ))
+        .send()?
+        .json()?;
+        Ok(root.live_data.decisions)
+    }
+
+    /// Parse a live feed response, the same way `get_decisions` does.
+    pub fn parse_decisions(json: &str) -> serde_json::Result<Decisions> {
+        let root: Root = serde_json::from_str(json)?;
+        Ok(root.live_data.decisions)
+    }
+
+    #[derive(Debug, Clone, Deserialize)]
+    struct StartersRoot {
+        #[serde(rename = "gamePk")]
+        game_pk: u64,
+        #[serde(rename = "gameData")]
+        game_data: GameData,
+        #[serde(rename = "liveData")]
+        live_data: StartersLiveData,
+    }
+
+    #[derive(Debug, Clone, Deserialize)]
+    struct GameData {
+        #[serde(default)]
+        status: Status,
+    }
+
+    #[derive(Debug, Clone, Deserialize)]
+    struct StartersLiveData {
+        #[serde(default)]
+        boxscore: Boxscore,
+    }
+
+    #[derive(Debug, Clone, Default, Deserialize)]
+    struct Boxscore {
+        #[serde(default)]
+        teams: BoxscoreTeams,
+    }
+
+    #[derive(Debug, Clone, Default, Deserialize)]
+    struct BoxscoreTeams {
+        #[serde(default)]
+        away: BoxscoreTeam,
+        #[serde(default)]
+        home: BoxscoreTeam,
+    }
+
+    #[derive(Debug, Clone, Default, Deserialize)]
+    struct BoxscoreTeam {
+        /// Ids of the goalies dressed for the game, the starter first. Empty
+        /// until the team publishes its lineup.
+        #[serde(default)]
+        goalies: Vec<u32>,
+        /// Players dressed for the game, keyed by `ID` and their id.
+        #[serde(default)]
+        players: BTreeMap<String, BoxscorePlayer>,
+    }
+
+    impl BoxscoreTeam {
+        fn starter(&self) -> Option<Player> {
+            let id = self.goalies.first()?;
+            self.players.get(&format!("ID{}", id)).map(|p| p.person.clone())
+        }
+    }
+
+    #[derive(Debug, Clone, Deserialize)]
+    struct BoxscorePlayer {
+        person: Player,
+    }
+
+    /// Starting goalies of a game, probable until the game starts.
+    #[derive(Debug, Clone, Deserialize, Serialize)]
+    pub struct Starters {
+        pub game_pk: u64,
+        pub away: Player,
+        pub home: Player,
+        /// Whether the game has started with these goalies in net.
+        pub confirmed: bool,
+    }
+
+    /// Starting goalies of game `game_pk`. `None` until both teams have
+    /// published their lineups, often only hours before the game.
+    pub fn get_starters(game_pk: u64) -> attohttpc::Result<Option<Starters>> {
+        let json = attohttpc::get(format!(
+            "https://statsapi.web.nhl.com/api/v1/game/{}/feed/live",
+            game_pk
+        ))
+        .send()?
+        .text()?;
+        Ok(parse_starters(&json)?)
+    }
+
+    /// Parse a live feed response, the same way `get_starters` does.
+    pub fn parse_starters(json: &str) -> serde_json::Result<Option<Starters>> {
+        let root: StartersRoot = serde_json::from_str(json)?;
+        let teams = &root.live_data.boxscore.teams;
+        Ok(match (teams.away.starter(), teams.home.starter()) {
+            (Some(away), Some(home)) => Some(Starters {
+                game_pk: root.game_pk,
+                away,
+                home,
+                confirmed: root.game_data.status.abstract_game_state != "Preview",
+            }),
+            _ => None,
+        })
+    }
 }
 
 pub mod teams {
-    const SUBREDDITS: &'static str = "\
-        anaheimducks
-        coyotes
-        bostonbruins
-        sabres
-        calgaryflames
-        canes
-        hawks
-        coloradoavalanche
-        bluejackets
-        dallasstars
-        detroitredwings
-        edmontonoilers
-        floridapanthers
-        losangeleskings
-        wildhockey
-        habs
-        predators
-        devils
-        newyorkislanders
-        rangers
-        ottawasenators
-        flyers
-        penguins
-        sanjosesharks
-        stlouisblues
-        tampabaylightning
-        leafs
-        canucks
-        goldenknights
-        caps
-        winnipegjets";
+    /// Subreddit of every team, by team id. Teams that no longer exist have none.
+    const SUBREDDITS: &[(u32, &str)] = &[
+        (1, "devils"),
+        (2, "newyorkislanders"),
+        (3, "rangers"),
+        (4, "flyers"),
+        (5, "penguins"),
+        (6, "bostonbruins"),
+        (7, "sabres"),
+        (8, "habs"),
+        (9, "ottawasenators"),
+        (10, "leafs"),
+        (12, "canes"),
+        (13, "floridapanthers"),
+        (14, "tampabaylightning"),
+        (15, "caps"),
+        (16, "hawks"),
+        (17, "detroitredwings"),
+        (18, "predators"),
+        (19, "stlouisblues"),
+        (20, "calgaryflames"),
+        (21, "coloradoavalanche"),
+        (22, "edmontonoilers"),
+        (23, "canucks"),
+        (24, "anaheimducks"),
+        (25, "dallasstars"),
+        (26, "losangeleskings"),
+        (28, "sanjosesharks"),
+        (29, "bluejackets"),
+        (30, "wildhockey"),
+        (52, "winnipegjets"),
+        (53, "coyotes"),
+        (54, "goldenknights"),
+    ];
+
+    /// Primary color and emoji of every team, by team id.
+    const THEMES: &[(u32, &str, &str)] = &[
+        (1, "#CE1126", "😈"),
+        (2, "#00539B", "🏝️"),
+        (3, "#0038A8", "🗽"),
+        (4, "#F74902", "🟠"),
+        (5, "#FCB514", "🐧"),
+        (6, "#FFB81C", "🐻"),
+        (7, "#002654", "🦬"),
+        (8, "#AF1E2D", "⚜️"),
+        (9, "#C52032", "🏛️"),
+        (10, "#00205B", "🍁"),
+        (12, "#CC0000", "🌀"),
+        (13, "#041E42", "🐆"),
+        (14, "#002868", "⚡"),
+        (15, "#041E42", "🦅"),
+        (16, "#CF0A2C", "🪶"),
+        (17, "#CE1126", "🐙"),
+        (18, "#FFB81C", "🐯"),
+        (19, "#002F87", "🎵"),
+        (20, "#C8102E", "🔥"),
+        (21, "#6F263D", "⛰️"),
+        (22, "#041E42", "🛢️"),
+        (23, "#00205B", "🐳"),
+        (24, "#F47A38", "🦆"),
+        (25, "#006847", "⭐"),
+        (26, "#111111", "👑"),
+        (28, "#006D75", "🦈"),
+        (29, "#002654", "💣"),
+        (30, "#154734", "🌲"),
+        (52, "#041E42", "✈️"),
+        (53, "#8C2633", "🐺"),
+        (54, "#B4975A", "⚔️"),
+    ];
 
     use std::str::FromStr;
 
@@ -373,9 +1005,52 @@ pub mod teams {
         pub subreddit: String,
     }
 
+    /// How the name of a team is displayed.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum NameStyle {
+        /// `MTL`
+        Abbrev,
+        /// `Canadiens`
+        Short,
+        /// `Montréal Canadiens`
+        Full,
+        /// `MTL` with the flair of the team's subreddit.
+        Flair,
+    }
+
+    /// Colors and emoji of a team, for the posts that use them.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Theme {
+        /// Hex color, such as `#AF1E2D`.
+        pub primary_color: &'static str,
+        pub emoji: &'static str,
+    }
+
     impl Team {
+        /// Theme of the team, `None` for teams that joined the league after the table was written.
+        pub fn theme(&self) -> Option<Theme> {
+            THEMES
+                .iter()
+                .find(|(id, _, _)| *id == self.id)
+                .map(|&(_, primary_color, emoji)| Theme { primary_color, emoji })
+        }
+
+        pub fn display(&self, style: NameStyle) -> String {
+            match style {
+                NameStyle::Abbrev => self.abbrev.clone(),
+                NameStyle::Short => self.name.clone(),
+                NameStyle::Full => self.full_name.clone(),
+                NameStyle::Flair => format!("[](/r/{}){}", self.subreddit, self.abbrev),
+            }
+        }
+
+        /// Time zone of the team's arena, as resolved when the teams were
+        /// loaded. See `TimeZone::resolve`.
         pub fn timezone(&self) -> chrono_tz::Tz {
-            chrono_tz::Tz::from_str(&self.venue.timezone.id).expect("Unknown timezone")
+            let timezone = &self.venue.timezone;
+            timezone
+                .resolved
+                .unwrap_or_else(|| timezone.resolve().unwrap_or_else(|tz| tz))
         }
 
         pub fn timezone_code(&self) -> &str {
@@ -410,20 +1085,605 @@ pub mod teams {
         pub offset: i32,
         #[serde(rename = "tz")]
         pub code: String,
+        /// The zone of `id`, filled in once by `with_subreddits`.
+        #[serde(skip)]
+        pub resolved: Option<chrono_tz::Tz>,
+    }
+
+    impl TimeZone {
+        /// The chrono-tz zone of `id`. When the id is unknown to chrono-tz,
+        /// falls back to the fixed UTC offset given with it, and then to Eastern
+        /// time, and returns that fallback as the error.
+        pub fn resolve(&self) -> Result<chrono_tz::Tz, chrono_tz::Tz> {
+            if let Ok(tz) = chrono_tz::Tz::from_str(&self.id) {
+                return Ok(tz);
+            }
+            // The sign of the Etc zones is inverted: Etc/GMT+5 is UTC-05:00.
+            let fixed = format!("Etc/GMT{:+}", -self.offset);
+            Err(chrono_tz::Tz::from_str(&fixed).unwrap_or(chrono_tz::America::Toronto))
+        }
     }
 
     pub fn get() -> attohttpc::Result<Vec<Team>> {
-        let mut root: Root = attohttpc::get("https://statsapi.web.nhl.com/api/v1/teams")
+        let root: Root = attohttpc::get("https://statsapi.web.nhl.com/api/v1/teams")
             .send()?
             .json()?;
+        Ok(with_subreddits(root.teams))
+    }
+
+    /// The teams as they were during `season`, with that season's divisions
+    /// and conferences, including the teams that no longer exist.
+    pub fn get_season(season: &super::Season) -> attohttpc::Result<Vec<Team>> {
+        let root: Root = attohttpc::get("https://statsapi.web.nhl.com/api/v1/teams")
+            .params(&[("season", format!("{:04}{:04}", season.begin, season.end))])
+            .send()?
+            .json()?;
+        Ok(with_subreddits(root.teams))
+    }
+
+    /// Parse a teams response, the same way `get` does.
+    pub fn parse(json: &str) -> serde_json::Result<Vec<Team>> {
+        let root: Root = serde_json::from_str(json)?;
+        Ok(with_subreddits(root.teams))
+    }
 
-        root.teams
-            .sort_unstable_by(|left, right| left.full_name.cmp(&right.full_name));
+    /// Sort the teams by name and fill in their subreddits and time zones,
+    /// warning about the zones that fall back.
+    pub(super) fn with_subreddits(mut teams: Vec<Team>) -> Vec<Team> {
+        teams.sort_unstable_by(|left, right| left.full_name.cmp(&right.full_name));
 
-        for (sub, team) in SUBREDDITS.lines().zip(root.teams.iter_mut()) {
-            team.subreddit = sub.trim().to_string();
+        for team in &mut teams {
+            team.subreddit = SUBREDDITS
+                .iter()
+                .find(|(id, _)| *id == team.id)
+                .map(|&(_, sub)| sub.to_string())
+                .unwrap_or_default();
+
+            let tz = team.venue.timezone.resolve().unwrap_or_else(|tz| {
+                eprintln!(
+                    "warning: unknown timezone {:?} for {}, using {}",
+                    team.venue.timezone.id,
+                    team.abbrev,
+                    tz.name()
+                );
+                tz
+            });
+            team.venue.timezone.resolved = Some(tz);
         }
 
-        Ok(root.teams)
+        teams
+    }
+}
+
+/// Backend for api-web.nhle.com, which replaced statsapi.web.nhl.com, and for
+/// the team ids of api.nhle.com/stats/rest. The responses are mapped into the
+/// types of the old API.
+///
+/// The new API has no endpoint with the divisions and arenas of the teams, so
+/// the teams are put together from the standings, and their arenas only have
+/// a time zone. There are no official wildcard groupings either, the seeding
+/// is computed from the standings.
+pub mod web {
+    use std::collections::BTreeMap;
+
+    use chrono::{DateTime, NaiveDate, Offset, TimeZone as _, Utc};
+    use serde::Deserialize;
+
+    use super::schedule::{self, Date, Game, LineScore, Period, SeriesSummary, Status, Teams};
+    use super::standings::{self, Record, Records, Streak, TeamRecord};
+    use super::teams::{self, Conference, Division, Team, TimeZone, Venue};
+    use super::{LeagueRecord, Season};
+
+    const WEB_API: &str = "https://api-web.nhle.com/v1";
+    const STATS_API: &str = "https://api.nhle.com/stats/rest/en";
+
+    /// Ids of the old API for the divisions and conferences, by name, so that
+    /// both backends agree. Names missing here get an id past these.
+    const DIVISION_IDS: &[(&str, u32)] = &[("Pacific", 15), ("Central", 16), ("Atlantic", 17), ("Metropolitan", 18)];
+    const CONFERENCE_IDS: &[(&str, u32)] = &[("Western", 5), ("Eastern", 6)];
+
+    /// Time zone of the arena of every team, by team id.
+    const TIMEZONES: &[(u32, &str)] = &[
+        (1, "America/New_York"),
+        (2, "America/New_York"),
+        (3, "America/New_York"),
+        (4, "America/New_York"),
+        (5, "America/New_York"),
+        (6, "America/New_York"),
+        (7, "America/New_York"),
+        (8, "America/Montreal"),
+        (9, "America/New_York"),
+        (10, "America/Toronto"),
+        (12, "America/New_York"),
+        (13, "America/New_York"),
+        (14, "America/New_York"),
+        (15, "America/New_York"),
+        (16, "America/Chicago"),
+        (17, "America/Detroit"),
+        (18, "America/Chicago"),
+        (19, "America/Chicago"),
+        (20, "America/Edmonton"),
+        (21, "America/Denver"),
+        (22, "America/Edmonton"),
+        (23, "America/Vancouver"),
+        (24, "America/Los_Angeles"),
+        (25, "America/Chicago"),
+        (26, "America/Los_Angeles"),
+        (28, "America/Los_Angeles"),
+        (29, "America/New_York"),
+        (30, "America/Chicago"),
+        (52, "America/Winnipeg"),
+        (53, "America/Phoenix"),
+        (54, "America/Los_Angeles"),
+        (55, "America/Los_Angeles"),
+        (59, "America/Denver"),
+    ];
+
+    /// Text given in several languages, of which only the default is used.
+    #[derive(Debug, Clone, Default, Deserialize)]
+    struct Localized {
+        default: String,
+    }
+
+    #[derive(Debug, Clone, Deserialize)]
+    struct StandingsRoot {
+        standings: Vec<Standing>,
+    }
+
+    #[derive(Debug, Clone, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Standing {
+        team_abbrev: Localized,
+        team_name: Localized,
+        team_common_name: Localized,
+        place_name: Localized,
+        #[serde(default)]
+        conference_name: String,
+        division_name: String,
+        games_played: u32,
+        wins: u32,
+        losses: u32,
+        ot_losses: u32,
+        points: u32,
+        regulation_plus_ot_wins: u32,
+        #[serde(default)]
+        regulation_wins: u32,
+        goal_for: u32,
+        goal_against: u32,
+        division_sequence: u32,
+        conference_sequence: u32,
+        league_sequence: u32,
+        wildcard_sequence: u32,
+        l10_wins: u32,
+        l10_losses: u32,
+        l10_ot_losses: u32,
+        /// `W`, `L` or `OT`, missing before a team's first game.
+        #[serde(default)]
+        streak_code: String,
+        #[serde(default)]
+        streak_count: u32,
+    }
+
+    #[derive(Debug, Clone, Deserialize)]
+    struct StatsTeamsRoot {
+        data: Vec<StatsTeam>,
+    }
+
+    #[derive(Debug, Clone, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct StatsTeam {
+        id: u32,
+        tri_code: String,
+    }
+
+    #[derive(Debug, Clone, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct ScheduleRoot {
+        game_week: Vec<GameDay>,
+    }
+
+    #[derive(Debug, Clone, Deserialize)]
+    struct GameDay {
+        date: NaiveDate,
+        games: Vec<WebGame>,
+    }
+
+    #[derive(Debug, Clone, Deserialize)]
+    struct ClubScheduleRoot {
+        games: Vec<WebGame>,
+    }
+
+    #[derive(Debug, Clone, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct WebGame {
+        id: u64,
+        season: u32,
+        /// 1 for the preseason, 2 for the regular season and 3 for the playoffs.
+        game_type: u32,
+        /// Local date of the game, only given by the club schedule.
+        #[serde(default)]
+        game_date: Option<NaiveDate>,
+        #[serde(rename = "startTimeUTC")]
+        start_time_utc: DateTime<Utc>,
+        /// `FUT` and `PRE` before the game, `LIVE` and `CRIT` during it, and
+        /// `FINAL` and `OFF` after.
+        game_state: String,
+        home_team: WebGameTeam,
+        away_team: WebGameTeam,
+        #[serde(default)]
+        period_descriptor: Option<PeriodDescriptor>,
+        #[serde(default)]
+        clock: Option<Clock>,
+        #[serde(default)]
+        series_status: Option<SeriesStatus>,
+    }
+
+    #[derive(Debug, Clone, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct WebGameTeam {
+        id: u32,
+        abbrev: String,
+        #[serde(default)]
+        score: u32,
+        #[serde(default)]
+        place_name: Option<Localized>,
+    }
+
+    #[derive(Debug, Clone, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct PeriodDescriptor {
+        number: u32,
+        /// `REG`, `OT` or `SO`.
+        #[serde(default)]
+        period_type: String,
+    }
+
+    #[derive(Debug, Clone, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Clock {
+        #[serde(default)]
+        in_intermission: bool,
+    }
+
+    #[derive(Debug, Clone, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct SeriesStatus {
+        game_number_of_series: u32,
+        top_seed_team_abbrev: String,
+        top_seed_wins: u32,
+        bottom_seed_team_abbrev: String,
+        bottom_seed_wins: u32,
+    }
+
+    /// The teams of the standings of `date`, with their ids from the stats API.
+    pub fn get_teams(date: &NaiveDate) -> Result<Vec<Team>, standings::Error> {
+        let standings = attohttpc::get(format!("{}/standings/{}", WEB_API, date.format("%Y-%m-%d")))
+            .send()?
+            .text()?;
+        let stats_teams = attohttpc::get(format!("{}/team", STATS_API)).send()?.text()?;
+        parse_teams(&standings, &stats_teams, date)
+    }
+
+    /// Parse the standings and the stats API teams, the same way `get_teams` does.
+    pub fn parse_teams(
+        standings_json: &str,
+        teams_json: &str,
+        date: &NaiveDate,
+    ) -> Result<Vec<Team>, standings::Error> {
+        let root: StandingsRoot = serde_json::from_str(standings_json)?;
+        let stats: StatsTeamsRoot = serde_json::from_str(teams_json)?;
+        if root.standings.is_empty() {
+            return Err(standings::Error::MissingStandings);
+        }
+
+        let mut division_ids = IdTable::new(DIVISION_IDS);
+        let mut conference_ids = IdTable::new(CONFERENCE_IDS);
+        let mut teams = vec![];
+        for standing in &root.standings {
+            let abbrev = &standing.team_abbrev.default;
+            let id = match stats.data.iter().find(|t| &t.tri_code == abbrev) {
+                Some(team) => team.id,
+                None => {
+                    eprintln!("warning: no id for team {}, leaving it out", abbrev);
+                    continue;
+                }
+            };
+            teams.push(Team {
+                id,
+                full_name: standing.team_name.default.clone(),
+                abbrev: abbrev.clone(),
+                name: standing.team_common_name.default.clone(),
+                location: standing.place_name.default.clone(),
+                division: Division {
+                    id: division_ids.get(&standing.division_name),
+                    name: standing.division_name.clone(),
+                },
+                conference: Conference {
+                    id: conference_ids.get(&standing.conference_name),
+                    name: standing.conference_name.clone(),
+                },
+                venue: Venue {
+                    id: None,
+                    name: String::new(),
+                    city: standing.place_name.default.clone(),
+                    timezone: timezone_of(id, date),
+                },
+                subreddit: String::new(),
+            });
+        }
+        Ok(teams::with_subreddits(teams))
+    }
+
+    /// Ids by name, handing out new ids to the names it doesn't know.
+    struct IdTable {
+        ids: BTreeMap<String, u32>,
+    }
+
+    impl IdTable {
+        fn new(known: &[(&str, u32)]) -> IdTable {
+            IdTable {
+                ids: known.iter().map(|&(name, id)| (name.to_string(), id)).collect(),
+            }
+        }
+
+        fn get(&mut self, name: &str) -> u32 {
+            let next = self.ids.values().max().map(|id| id + 1).unwrap_or(1).max(100);
+            *self.ids.entry(name.to_string()).or_insert(next)
+        }
+    }
+
+    fn timezone_of(team_id: u32, date: &NaiveDate) -> TimeZone {
+        let id = TIMEZONES
+            .iter()
+            .find(|&&(id, _)| id == team_id)
+            .map(|&(_, tz)| tz)
+            .unwrap_or("America/Toronto");
+        let tz: chrono_tz::Tz = id.parse().unwrap_or(chrono_tz::America::Toronto);
+        let offset = tz.offset_from_utc_date(date);
+        TimeZone {
+            id: id.to_string(),
+            offset: offset.fix().local_minus_utc() / 3600,
+            code: offset.to_string(),
+            resolved: None,
+        }
+    }
+
+    /// The standings of the league on `date`.
+    pub fn get_standings(date: &NaiveDate, teams: &[Team]) -> Result<Vec<TeamRecord>, standings::Error> {
+        let json = attohttpc::get(format!("{}/standings/{}", WEB_API, date.format("%Y-%m-%d")))
+            .send()?
+            .text()?;
+        parse_standings(&json, teams)
+    }
+
+    /// Parse a standings response, the same way `get_standings` does. Teams
+    /// missing from `teams` are left out.
+    pub fn parse_standings(json: &str, teams: &[Team]) -> Result<Vec<TeamRecord>, standings::Error> {
+        let root: StandingsRoot = serde_json::from_str(json)?;
+        if root.standings.is_empty() {
+            return Err(standings::Error::MissingStandings);
+        }
+        Ok(root
+            .standings
+            .iter()
+            .filter_map(|standing| {
+                let team = teams.iter().find(|t| t.abbrev == standing.team_abbrev.default)?;
+                Some(team_record(standing, team))
+            })
+            .collect())
+    }
+
+    fn team_record(standing: &Standing, team: &Team) -> TeamRecord {
+        let streak = match standing.streak_code.as_str() {
+            "W" => Some("wins"),
+            "L" => Some("losses"),
+            "OT" => Some("ot"),
+            _ => None,
+        };
+        TeamRecord {
+            team: super::Team {
+                id: team.id,
+                name: team.full_name.clone(),
+            },
+            league_record: LeagueRecord {
+                wins: standing.wins,
+                losses: standing.losses,
+                ot: standing.ot_losses,
+            },
+            goals_against: standing.goal_against,
+            goals_scored: standing.goal_for,
+            points: standing.points,
+            row: standing.regulation_plus_ot_wins,
+            regulation_wins: standing.regulation_wins,
+            games_played: standing.games_played,
+            division_rank: standing.division_sequence,
+            conference_rank: standing.conference_sequence,
+            league_rank: standing.league_sequence,
+            wildcard_rank: standing.wildcard_sequence,
+            records: Records {
+                overall_records: vec![Record {
+                    wins: standing.l10_wins,
+                    losses: standing.l10_losses,
+                    ot: standing.l10_ot_losses,
+                    kind: "lastTen".to_string(),
+                }],
+            },
+            streak: streak.map(|kind| Streak {
+                kind: kind.to_string(),
+                number: standing.streak_count,
+            }),
+        }
+    }
+
+    /// The games of `date`.
+    pub fn get_schedule(date: &NaiveDate) -> attohttpc::Result<Date> {
+        let json = attohttpc::get(format!("{}/schedule/{}", WEB_API, date.format("%Y-%m-%d")))
+            .send()?
+            .text()?;
+        Ok(parse_schedule(&json, date)?)
+    }
+
+    /// Parse a schedule response, the same way `get_schedule` does. The
+    /// response spans a week, only the games of `date` are kept.
+    pub fn parse_schedule(json: &str, date: &NaiveDate) -> serde_json::Result<Date> {
+        let root: ScheduleRoot = serde_json::from_str(json)?;
+        let games = root
+            .game_week
+            .into_iter()
+            .filter(|day| day.date == *date)
+            .flat_map(|day| day.games)
+            .map(game)
+            .collect();
+        Ok(Date { date: *date, games })
+    }
+
+    /// The games of `team` from `begin` to `end`, inclusively, from the
+    /// schedule of its season.
+    pub fn get_range(team: &Team, begin: &NaiveDate, end: &NaiveDate) -> attohttpc::Result<Vec<Date>> {
+        let season = Season::of(begin);
+        let json = attohttpc::get(format!(
+            "{}/club-schedule-season/{}/{:04}{:04}",
+            WEB_API, team.abbrev, season.begin, season.end
+        ))
+        .send()?
+        .text()?;
+        Ok(parse_range(&json, begin, end)?)
+    }
+
+    /// Parse a club schedule response, the same way `get_range` does.
+    pub fn parse_range(json: &str, begin: &NaiveDate, end: &NaiveDate) -> serde_json::Result<Vec<Date>> {
+        let root: ClubScheduleRoot = serde_json::from_str(json)?;
+        let mut dates: BTreeMap<NaiveDate, Vec<Game>> = BTreeMap::new();
+        for web_game in root.games {
+            let date = web_game
+                .game_date
+                .unwrap_or_else(|| web_game.start_time_utc.naive_utc().date());
+            if date >= *begin && date <= *end {
+                dates.entry(date).or_default().push(game(web_game));
+            }
+        }
+        Ok(dates.into_iter().map(|(date, games)| Date { date, games }).collect())
+    }
+
+    fn game(web: WebGame) -> Game {
+        let game_type = match web.game_type {
+            1 => "PR",
+            2 => "R",
+            3 => "P",
+            _ => "A",
+        };
+        let abstract_game_state = match web.game_state.as_str() {
+            "LIVE" | "CRIT" => "Live",
+            "FINAL" | "OFF" => "Final",
+            _ => "Preview",
+        };
+        // The old API only has periods once the game has started.
+        let linescore = match &web.period_descriptor {
+            Some(period) if abstract_game_state != "Preview" => LineScore {
+                current_period: period.number,
+                periods: (1..=period.number)
+                    .map(|number| Period {
+                        period_type: match number {
+                            1..=3 => "REGULAR".to_string(),
+                            _ if period.period_type == "SO" && number == period.number => "SHOOTOUT".to_string(),
+                            _ => "OVERTIME".to_string(),
+                        },
+                    })
+                    .collect(),
+                intermission_info: schedule::IntermissionInfo {
+                    in_intermission: web.clock.as_ref().map(|c| c.in_intermission).unwrap_or(false),
+                },
+            },
+            _ => LineScore::default(),
+        };
+        let series_summary = web.series_status.as_ref().map(|series| SeriesSummary {
+            game_number: series.game_number_of_series,
+            series_status_short: series_status(series),
+        });
+        Game {
+            game_pk: web.id,
+            game_type: game_type.to_string(),
+            season: Season {
+                begin: web.season / 10000,
+                end: web.season % 10000,
+            },
+            game_date: web.start_time_utc,
+            teams: Teams {
+                away: game_team(web.away_team),
+                home: game_team(web.home_team),
+            },
+            linescore,
+            status: Status {
+                abstract_game_state: abstract_game_state.to_string(),
+            },
+            series_summary,
+        }
+    }
+
+    fn game_team(team: WebGameTeam) -> schedule::TeamRecord {
+        schedule::TeamRecord {
+            team: super::Team {
+                id: team.id,
+                name: team.place_name.map(|name| name.default).unwrap_or(team.abbrev),
+            },
+            // The schedule has no records, the standings do.
+            league_record: LeagueRecord {
+                wins: 0,
+                losses: 0,
+                ot: 0,
+            },
+            score: team.score,
+        }
+    }
+
+    /// Such as `TBL leads 1-0`, like the old API's short series status.
+    fn series_status(series: &SeriesStatus) -> String {
+        let (leader, wins, losses) = if series.top_seed_wins >= series.bottom_seed_wins {
+            (
+                &series.top_seed_team_abbrev,
+                series.top_seed_wins,
+                series.bottom_seed_wins,
+            )
+        } else {
+            (
+                &series.bottom_seed_team_abbrev,
+                series.bottom_seed_wins,
+                series.top_seed_wins,
+            )
+        };
+        if wins == 0 {
+            String::new()
+        } else if wins == losses {
+            format!("Tied {}-{}", wins, losses)
+        } else if wins == 4 {
+            format!("{} wins {}-{}", leader, wins, losses)
+        } else {
+            format!("{} leads {}-{}", leader, wins, losses)
+        }
+    }
+}
+
+#[test]
+fn test_season_round_trip() {
+    let season: Season = serde_json::from_str("\"20182019\"").unwrap();
+    assert_eq!(season, Season { begin: 2018, end: 2019 });
+    assert_eq!(serde_json::to_string(&season).unwrap(), "\"20182019\"");
+}
+
+#[test]
+fn test_season_malformed() {
+    for input in &[
+        "\"2018201\"",
+        "\"201820190\"",
+        "\"2018201a\"",
+        "\"+2018201\"",
+        "\"2018２019\"",
+        "20182019",
+    ] {
+        assert!(
+            serde_json::from_str::<Season>(input).is_err(),
+            "{} should not parse",
+            input
+        );
     }
 }
@@ -1,131 +1,1098 @@
-use std::cmp::Reverse;
-use std::collections::BTreeSet;
+use std::cell::{Cell, RefCell};
+use std::cmp;
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::{Add, RangeInclusive, Sub};
+use std::str::FromStr;
+use std::thread;
 
+use chrono::NaiveDate;
+use failure::{bail, Error};
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
+use rand::{Rng, RngCore, SeedableRng};
+use serde::{Deserialize, Serialize};
 
+use crate::analysis::{self, Api, PlayoffFormat};
 use crate::nhlapi;
 use crate::nhlapi::schedule::Game;
 use crate::nhlapi::standings::TeamRecord;
 use crate::nhlapi::teams::Team;
-use crate::Api;
 
+/// Number of seasons simulated for each odds, unless `Params::times` says otherwise.
 pub const TIMES: u32 = 50_000;
 
+pub const GAMES_PER_SEASON: u32 = 82;
+
+/// Games left in the season for which `OddsMode::Auto` considers a team's
+/// fate decided.
+pub const AUTO_BUFFER_GAMES: u32 = 1;
+
+thread_local! {
+    /// Number of simulations run and of seasons simulated on this thread since
+    /// it started, for profiling. Counted by thread so that the teams processed
+    /// at the same time don't count each other's simulations.
+    static SIM_COUNT: Cell<SimCount> = Cell::new(SimCount::default());
+}
+
+/// Count of the simulations run, see `sim_count`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SimCount {
+    pub simulations: u64,
+    pub seasons: u64,
+}
+
+impl SimCount {
+    const SIMULATION: SimCount = SimCount {
+        simulations: 1,
+        seasons: 0,
+    };
+    const SEASON: SimCount = SimCount {
+        simulations: 0,
+        seasons: 1,
+    };
+}
+
+impl Add for SimCount {
+    type Output = SimCount;
+
+    fn add(self, other: SimCount) -> SimCount {
+        SimCount {
+            simulations: self.simulations + other.simulations,
+            seasons: self.seasons + other.seasons,
+        }
+    }
+}
+
+impl Sub for SimCount {
+    type Output = SimCount;
+
+    fn sub(self, other: SimCount) -> SimCount {
+        SimCount {
+            simulations: self.simulations - other.simulations,
+            seasons: self.seasons - other.seasons,
+        }
+    }
+}
+
+/// Simulations run and seasons simulated so far on this thread, including
+/// the threads it spawned to run simulations. Take the difference of two
+/// counts to know how many ran in between.
+pub fn sim_count() -> SimCount {
+    SIM_COUNT.with(Cell::get)
+}
+
+/// Add `count` to the simulations of this thread.
+fn add_sim_count(count: SimCount) {
+    SIM_COUNT.with(|c| c.set(c.get() + count));
+}
+
+/// Parameters of the simulation model.
+#[derive(Debug, Clone, Default)]
+pub struct Params {
+    /// How much the last 10 games weigh in the odds of each outcome, from 0
+    /// (only the whole season) to 1 (only the last 10 games).
+    pub form_weight: f64,
+    /// How much more likely a hot team is to win, and a cold team to lose,
+    /// from 0 (no effect) up.
+    pub streak_weight: f64,
+    /// How many games at the league's average rates are added to the record
+    /// of every team, so that a few games early in the season don't make
+    /// for extreme odds. 0 leaves the records alone.
+    pub prior_games: f64,
+    /// How the playoffs odds of a team are computed.
+    pub odds_mode: OddsMode,
+    /// How much less likely a team is to win a game of the day when it also
+    /// played the day before, from 0 (no effect) to 1 (never wins).
+    pub back_to_back_penalty: f64,
+    /// How the teams are ranked in the simulated standings.
+    pub sort_basis: SortBasis,
+    /// Wins that break ties in points, or `None` for the official rule of
+    /// the season.
+    pub tiebreaker: Option<Tiebreaker>,
+    /// Results forced on some games before the rest of the season is
+    /// simulated, keyed by game pk.
+    pub pinned: BTreeMap<u64, PinnedResult>,
+    /// Which teams are simulated.
+    pub scope: SimulationScope,
+    /// Seed of the random numbers of every simulation run, so that the same
+    /// data always gives the same odds. `None` draws fresh random numbers.
+    pub seed: Option<u64>,
+    /// Number of seasons simulated for each odds, or `None` for `TIMES`.
+    pub times: Option<u32>,
+}
+
+/// Known result of a game that is not final yet, such as a rescheduled game
+/// that was already played or a game in progress that is all but over.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct PinnedResult {
+    /// Abbreviation of the winner.
+    pub winner: String,
+    /// Abbreviation of the loser.
+    pub loser: String,
+    /// Whether the game is decided in overtime or in a shootout, which gives
+    /// the loser a point.
+    #[serde(default)]
+    pub overtime: bool,
+}
+
+/// How the playoffs odds of a team are computed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OddsMode {
+    /// Always simulate the rest of the season.
+    Simulation,
+    /// 100% or 0% from the clinch status, or else from the points pace.
+    Analytic,
+    /// Analytic when the team's fate is decided or about to be, simulated otherwise.
+    #[default]
+    Auto,
+}
+
+/// How the teams are ranked in the standings.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortBasis {
+    /// Points, when every team plays the same number of games.
+    #[default]
+    Points,
+    /// Points percentage, as the NHL does when the teams end up with uneven
+    /// numbers of games played, such as after the 2020 pause.
+    PointsPercentage,
+}
+
+impl SortBasis {
+    /// Order two teams by their `(points, games_played)`, the best first.
+    pub fn cmp(self, a: (u32, u32), b: (u32, u32)) -> cmp::Ordering {
+        match self {
+            SortBasis::Points => b.0.cmp(&a.0),
+            SortBasis::PointsPercentage => {
+                let percent = |(points, games_played): (u32, u32)| {
+                    if games_played == 0 {
+                        0.0
+                    } else {
+                        f64::from(points) / f64::from(games_played)
+                    }
+                };
+                percent(b).partial_cmp(&percent(a)).unwrap()
+            }
+        }
+    }
+}
+
+/// Wins that break the ties in points, before the goal differential.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Tiebreaker {
+    /// Regulation wins, the first tiebreaker since the 2019-20 season.
+    Rw,
+    /// Regulation and overtime wins, the first tiebreaker until 2018-19.
+    Row,
+}
+
+impl Tiebreaker {
+    /// The NHL's first tiebreaker during `season`.
+    pub fn official(season: &nhlapi::Season) -> Tiebreaker {
+        if season.end >= 2020 {
+            Tiebreaker::Rw
+        } else {
+            Tiebreaker::Row
+        }
+    }
+
+    /// Wins of `record` that count for this tiebreaker.
+    pub fn wins(self, record: &TeamRecord) -> u32 {
+        match self {
+            Tiebreaker::Rw => record.regulation_wins,
+            Tiebreaker::Row => record.row,
+        }
+    }
+
+    /// Header of the standings column.
+    pub fn header(self) -> &'static str {
+        match self {
+            Tiebreaker::Rw => "RW",
+            Tiebreaker::Row => "ROW",
+        }
+    }
+}
+
+impl Params {
+    /// The tiebreaker in use on `date`, the configured one or else the official one.
+    pub fn tiebreaker_on(&self, date: &NaiveDate) -> Tiebreaker {
+        self.tiebreaker
+            .unwrap_or_else(|| Tiebreaker::official(&nhlapi::Season::of(date)))
+    }
+
+    /// Number of seasons simulated for each odds.
+    pub fn times(&self) -> u32 {
+        self.times.unwrap_or(TIMES)
+    }
+}
+
+/// Teams simulated along with the team of a simulation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SimulationScope {
+    /// The teams of its conference, enough for the playoffs odds.
+    #[default]
+    Conference,
+    /// Every team of the league, for league-wide questions such as the best
+    /// record or the bottom of the league.
+    League,
+}
+
+/// Method that actually produced some odds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OddsMethod {
+    Simulation,
+    Analytic,
+}
+
+impl OddsMethod {
+    pub fn text(&self) -> &'static str {
+        match self {
+            OddsMethod::Simulation => "simulated",
+            OddsMethod::Analytic => "analytic",
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone)]
 struct Entry {
     team_id: u32,
     division_id: u32,
+    conference_id: u32,
     wins: u32,
     losses: u32,
     ot: u32,
     games_played: u32,
     points: u32,
+    /// Wins that break ties in points. Every simulated win counts, which
+    /// side of regulation it is won on is not simulated.
+    tiebreak_wins: u32,
+    win_weight: f64,
+    loss_weight: f64,
+    ot_loss_weight: f64,
 }
 
+impl Entry {
+    /// Entry of `team` starting from its `record`, with the odds of each
+    /// outcome given by `params` and `prior`. Every field of an entry is set here.
+    fn new(record: &TeamRecord, team: &Team, params: &Params, tiebreaker: Tiebreaker, prior: &Prior) -> Entry {
+        debug_assert_eq!(record.team.id, team.id, "record of another team");
+        let (win_weight, loss_weight, ot_loss_weight) = streak_adjusted(
+            regressed(outcome_weights(record, params.form_weight), record, prior),
+            record,
+            params.streak_weight,
+        );
+        Entry {
+            team_id: team.id,
+            division_id: team.division.id,
+            conference_id: team.conference.id,
+            wins: record.league_record.wins,
+            losses: record.league_record.losses,
+            ot: record.league_record.ot,
+            games_played: record.games_played,
+            points: record.points,
+            tiebreak_wins: tiebreaker.wins(record),
+            win_weight,
+            loss_weight,
+            ot_loss_weight,
+        }
+    }
+
+    fn add_event(&mut self, event: Event) {
+        self.games_played += 1;
+        self.points += event.points();
+        match event {
+            Event::Win => {
+                self.wins += 1;
+                self.tiebreak_wins += 1;
+            }
+            Event::Loss => self.losses += 1,
+            Event::OtLoss => self.ot += 1,
+        }
+    }
+}
+
+/// Game between two teams of the conference, played head-to-head in every
+/// simulated season: the winner's win is the loser's loss.
 #[derive(Debug, Copy, Clone)]
-enum Event {
+struct SlateGame {
+    game_pk: u64,
+    /// Index of each team in `Simulation::base`.
+    home: usize,
+    away: usize,
+    /// Factor of the odds of a win of each team in this game, below 1 for a
+    /// team on the second night of a back-to-back.
+    home_win_factor: f64,
+    away_win_factor: f64,
+}
+
+impl SlateGame {
+    /// Odds of a win of the home and of the away team in this game, before
+    /// they are weighed against each other.
+    fn win_weights(&self, base: &[Entry]) -> (f64, f64) {
+        (
+            base[self.home].win_weight * self.home_win_factor,
+            base[self.away].win_weight * self.away_win_factor,
+        )
+    }
+}
+
+/// Odds of a win, a loss and an overtime loss for a team, blending the whole
+/// season with the last 10 games according to `form_weight`.
+fn outcome_weights(record: &TeamRecord, form_weight: f64) -> (f64, f64, f64) {
+    let rates = |wins: u32, losses: u32, ot: u32| {
+        let total = (wins + losses + ot) as f64;
+        (wins as f64 / total, losses as f64 / total, ot as f64 / total)
+    };
+
+    let league = &record.league_record;
+    let season = rates(league.wins, league.losses, league.ot);
+    match record.last10_record() {
+        Some(last10) if form_weight > 0.0 => {
+            let form = rates(last10.wins, last10.losses, last10.ot);
+            (
+                (1.0 - form_weight) * season.0 + form_weight * form.0,
+                (1.0 - form_weight) * season.1 + form_weight * form.1,
+                (1.0 - form_weight) * season.2 + form_weight * form.2,
+            )
+        }
+        _ => season,
+    }
+}
+
+/// Odds of each outcome across the league before any game is played: every
+/// game has a winner, and about 1 in 4 goes past regulation.
+const LEAGUE_RATES: (f64, f64, f64) = (0.5, 0.375, 0.125);
+
+/// Odds of each outcome across the league, that the odds of the teams are
+/// regressed to.
+#[derive(Debug, Clone, Copy)]
+struct Prior {
+    /// Weight of the league's odds, in games.
+    games: f64,
+    rates: (f64, f64, f64),
+}
+
+impl Prior {
+    /// Prior of `games` games at the rates of the teams of `records`, or at
+    /// `LEAGUE_RATES` before any game is played.
+    fn new(records: &[TeamRecord], games: f64) -> Prior {
+        let (wins, losses, ot) = records.iter().fold((0, 0, 0), |(wins, losses, ot), record| {
+            let league = &record.league_record;
+            (wins + league.wins, losses + league.losses, ot + league.ot)
+        });
+        let total = (wins + losses + ot) as f64;
+        let rates = if total > 0.0 {
+            (wins as f64 / total, losses as f64 / total, ot as f64 / total)
+        } else {
+            LEAGUE_RATES
+        };
+        Prior { games, rates }
+    }
+}
+
+/// Blend the odds of a team with the league's, as if the team had also
+/// played `prior.games` games at the league's rates.
+fn regressed(weights: (f64, f64, f64), record: &TeamRecord, prior: &Prior) -> (f64, f64, f64) {
+    if prior.games <= 0.0 {
+        return weights;
+    }
+    if record.games_played == 0 {
+        return prior.rates;
+    }
+    let played = record.games_played as f64;
+    let blend = |own: f64, league: f64| (played * own + prior.games * league) / (played + prior.games);
+    (
+        blend(weights.0, prior.rates.0),
+        blend(weights.1, prior.rates.1),
+        blend(weights.2, prior.rates.2),
+    )
+}
+
+/// Nudge the odds of a team on a streak, see `TeamRecord::is_hot` and
+/// `TeamRecord::is_cold`: the odds of a win, or of a loss, are multiplied by
+/// `1 + streak_weight` before the odds are normalized again.
+fn streak_adjusted(weights: (f64, f64, f64), record: &TeamRecord, streak_weight: f64) -> (f64, f64, f64) {
+    let (mut win, mut loss, ot_loss) = weights;
+    if record.is_hot() {
+        win *= 1.0 + streak_weight;
+    } else if record.is_cold() {
+        loss *= 1.0 + streak_weight;
+    }
+    let total = win + loss + ot_loss;
+    (win / total, loss / total, ot_loss / total)
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Event {
     Win,
+    /// Loss in regulation, worth no points.
     Loss,
-    Ot,
+    /// Loss in overtime or in a shootout, worth the loser point.
+    OtLoss,
+}
+
+impl FromStr for Event {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Event, Error> {
+        match s.to_ascii_uppercase().as_str() {
+            "W" => Ok(Event::Win),
+            "L" => Ok(Event::Loss),
+            "OT" => Ok(Event::OtLoss),
+            _ => bail!("invalid result {:?}, expected W, L or OT", s),
+        }
+    }
 }
 
 impl Event {
-    fn points(&self) -> u32 {
+    pub fn points(&self) -> u32 {
         match self {
             Event::Win => 2,
             Event::Loss => 0,
-            Event::Ot => 1,
+            Event::OtLoss => 1,
         }
     }
 }
 
+thread_local! {
+    /// Random numbers of the simulations run on this thread, when seeded.
+    static SEEDED_RNG: RefCell<Option<StdRng>> = RefCell::new(None);
+}
+
+/// Seed the random numbers of the simulations run next on this thread, or
+/// go back to fresh random numbers if `seed` is `None`.
+fn reseed(seed: Option<u64>) {
+    SEEDED_RNG.with(|rng| *rng.borrow_mut() = seed.map(StdRng::seed_from_u64));
+}
+
+/// Call `f` with the seeded random numbers of this thread, if any, or else
+/// with `rand::thread_rng()`.
+fn with_rng<T>(f: impl FnOnce(&mut dyn RngCore) -> T) -> T {
+    SEEDED_RNG.with(|rng| match rng.borrow_mut().as_mut() {
+        Some(rng) => f(rng),
+        None => f(&mut rand::thread_rng()),
+    })
+}
+
+/// Result of a slate game, from the home team's point of view. Each team
+/// wins in proportion of its odds of a win in that game, and the game goes to
+/// overtime as often as the two teams do on average.
+fn random_head_to_head(base: &[Entry], game: &SlateGame) -> (Event, Event) {
+    let (home, away) = (&base[game.home], &base[game.away]);
+    let (home_win, away_win) = game.win_weights(base);
+    let total = home_win + away_win;
+    let (home_wins, overtime) = with_rng(|rng| {
+        let home_wins = if total > 0.0 {
+            rng.gen_bool(home_win / total)
+        } else {
+            rng.gen_bool(0.5)
+        };
+        let overtime = rng.gen_bool(((home.ot_loss_weight + away.ot_loss_weight) / 2.0).min(1.0));
+        (home_wins, overtime)
+    });
+    let loss = if overtime { Event::OtLoss } else { Event::Loss };
+    if home_wins {
+        (Event::Win, loss)
+    } else {
+        (loss, Event::Win)
+    }
+}
+
 fn random_event(base: &Entry) -> Event {
-    [
-        (Event::Win, base.wins),
-        (Event::Loss, base.losses),
-        (Event::Ot, base.ot),
-    ]
-    .choose_weighted(&mut rand::thread_rng(), |x| x.1)
-    .unwrap()
-    .0
-}
-
-pub fn odds_for_team<'a>(api: &'a Api, team: &'a Team, past: bool) -> f64 {
-    let sim = if !past {
-        Simulation::new(api, team, &api.standings)
+    let events = [
+        (Event::Win, base.win_weight),
+        (Event::Loss, base.loss_weight),
+        (Event::OtLoss, base.ot_loss_weight),
+    ];
+    with_rng(|rng| events.choose_weighted(rng, |x| x.1).unwrap().0)
+}
+
+/// Whether a team's playoffs fate is already decided.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Clinch {
+    Clinched,
+    Eliminated,
+    Undecided,
+}
+
+/// Decide analytically if `team` has clinched a playoffs spot or has been
+/// eliminated. This errs on the side of `Undecided`: ties are assumed to go
+/// against the team when checking for a clinch, and in its favor when checking
+/// for an elimination.
+pub fn clinch_status(api: &Api, team: &Team, records: &[TeamRecord]) -> Clinch {
+    clinch_status_within(api, team, records, 0)
+}
+
+/// Like `clinch_status`, but as if every team had `buffer_games` fewer games
+/// left, so that teams whose fate is all but decided count as decided.
+pub fn clinch_status_within(api: &Api, team: &Team, records: &[TeamRecord], buffer_games: u32) -> Clinch {
+    let max_points = |r: &TeamRecord| r.points + 2 * remaining_games(r).saturating_sub(buffer_games);
+
+    let my_record = records
+        .iter()
+        .find(|r| r.team.id == team.id)
+        .expect("team id not found");
+    let my_points = my_record.points;
+    let my_max_points = max_points(my_record);
+
+    let mut division_can_pass = 0;
+    let mut conference_can_pass = 0;
+    let mut division_ahead = 0;
+    let mut other_divisions_ahead: usize = 0;
+    for record in records.iter().filter(|r| r.team.id != team.id) {
+        let other = api.get_team_by_id(record.team.id);
+        if other.conference.id != team.conference.id {
+            continue;
+        }
+        let same_division = other.division.id == team.division.id;
+        if max_points(record) >= my_points {
+            conference_can_pass += 1;
+            if same_division {
+                division_can_pass += 1;
+            }
+        }
+        if record.points > my_max_points {
+            if same_division {
+                division_ahead += 1;
+            } else {
+                other_divisions_ahead += 1;
+            }
+        }
+    }
+
+    // Finishing in the spots of the division, or ahead of enough teams of the
+    // conference that they can't all be division seeds and wildcards, always
+    // makes the playoffs: the top 3 of the division or the top 5 of the
+    // conference in the usual format.
+    let format = PlayoffFormat::of_conference(&api.teams, team.conference.id);
+    if division_can_pass < format.division_spots || conference_can_pass < format.division_spots + format.wildcard_spots
+    {
+        return Clinch::Clinched;
+    }
+
+    // Out of the spots of the division, and behind as many teams competing for
+    // the wildcards as there are wildcards. The other divisions have at most
+    // their spots' worth of division seeds.
+    if division_ahead >= format.division_spots {
+        let other_division_spots = format.divisions.saturating_sub(1) * format.division_spots;
+        let wildcards_ahead =
+            (division_ahead - format.division_spots) + other_divisions_ahead.saturating_sub(other_division_spots);
+        if wildcards_ahead >= format.wildcard_spots {
+            return Clinch::Eliminated;
+        }
+    }
+
+    Clinch::Undecided
+}
+
+/// Playoffs odds of `team`, computed according to `params.odds_mode`. `None`
+/// if `past` and yesterday's standings are unavailable.
+pub fn odds_for_team<'a>(api: &'a Api, team: &'a Team, past: bool, params: &'a Params) -> Option<f64> {
+    odds_and_method(api, team, past, params).map(|(odds, _)| odds)
+}
+
+/// Like `odds_for_team`, along with the method that produced the odds.
+pub fn odds_and_method<'a>(api: &'a Api, team: &'a Team, past: bool, params: &'a Params) -> Option<(f64, OddsMethod)> {
+    let records = api.records(past)?;
+    if odds_method(api, team, records, params) == OddsMethod::Analytic {
+        return Some((analytic_odds(api, team, records), OddsMethod::Analytic));
+    }
+
+    let sim = Simulation::for_team(api, team, past, params)?;
+    let x = sim.run_for(params.times());
+    Some((x as f64 / params.times() as f64, OddsMethod::Simulation))
+}
+
+/// Method that `params.odds_mode` picks for the odds of `team`, without
+/// computing them.
+pub fn odds_method(api: &Api, team: &Team, records: &[TeamRecord], params: &Params) -> OddsMethod {
+    let analytic = match params.odds_mode {
+        OddsMode::Simulation => false,
+        OddsMode::Analytic => true,
+        OddsMode::Auto => clinch_status_within(api, team, records, AUTO_BUFFER_GAMES) != Clinch::Undecided,
+    };
+    if analytic {
+        OddsMethod::Analytic
+    } else {
+        OddsMethod::Simulation
+    }
+}
+
+/// Deterministic odds: 100% or 0% when the team's fate is decided, or when it
+/// is all but decided, and otherwise whether the points pace makes the playoffs.
+fn analytic_odds(api: &Api, team: &Team, records: &[TeamRecord]) -> f64 {
+    let status = match clinch_status(api, team, records) {
+        Clinch::Undecided => clinch_status_within(api, team, records, AUTO_BUFFER_GAMES),
+        status => status,
+    };
+    let makes_playoffs = match status {
+        Clinch::Clinched => true,
+        Clinch::Eliminated => false,
+        Clinch::Undecided => pace_projection(api, team, records).makes_playoffs,
+    };
+    if makes_playoffs {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+/// Playoffs odds of `team` if the given results were to happen, in order,
+/// before the rest of the season is simulated.
+pub fn odds_with_results<'a>(api: &'a Api, team: &'a Team, results: &[(u32, Event)], params: &'a Params) -> f64 {
+    let mut sim = Simulation::today(api, team, params);
+    for &(team_id, event) in results {
+        sim.give_team_event(team_id, event);
+    }
+    let x = sim.run_for(params.times());
+    x as f64 / params.times() as f64
+}
+
+/// Playoffs odds of every team in `team`'s conference, best odds first.
+/// `None` if `past` and yesterday's standings are unavailable.
+pub fn odds_for_conference<'a>(
+    api: &'a Api,
+    team: &'a Team,
+    past: bool,
+    params: &'a Params,
+) -> Option<Vec<(u32, f64)>> {
+    let sim = Simulation::for_team(api, team, past, params)?;
+    let mut odds: Vec<_> = sim
+        .run_distribution(params.times())
+        .into_iter()
+        .map(|(team_id, x)| (team_id, x as f64 / params.times() as f64))
+        .collect();
+    odds.sort_by(|left, right| right.1.partial_cmp(&left.1).unwrap());
+    Some(odds)
+}
+
+/// Playoffs odds of every team in the league, best odds first. The
+/// conferences are simulated in parallel.
+pub fn odds_for_league(api: &Api, params: &Params) -> Vec<(u32, f64)> {
+    let mut conference_teams: Vec<&Team> = vec![];
+    for team in &api.teams {
+        if conference_teams.iter().all(|t| t.conference.id != team.conference.id) {
+            conference_teams.push(team);
+        }
+    }
+
+    let mut odds: Vec<_> = thread::scope(|scope| {
+        let handles: Vec<_> = conference_teams
+            .into_iter()
+            .map(|team| {
+                scope.spawn(move || {
+                    let odds = odds_for_conference(api, team, false, params).unwrap_or_default();
+                    (odds, sim_count())
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| {
+                let (odds, count) = handle.join().expect("simulation thread panicked");
+                add_sim_count(count);
+                odds
+            })
+            .collect()
+    });
+    odds.sort_by(|left, right| right.1.partial_cmp(&left.1).unwrap());
+    odds
+}
+
+/// Odds of each team being `team`'s first round opponent, most likely first.
+/// The odds sum to `team`'s playoffs odds.
+pub fn first_round_opponents<'a>(api: &'a Api, team: &'a Team, params: &'a Params) -> Vec<(u32, f64)> {
+    let sim = Simulation::today(api, team, params);
+    let mut odds: Vec<_> = sim
+        .run_opponents(params.times())
+        .into_iter()
+        .map(|(team_id, x)| (team_id, x as f64 / params.times() as f64))
+        .collect();
+    odds.sort_by(|left, right| right.1.partial_cmp(&left.1).unwrap());
+    odds
+}
+
+/// Mean and standard deviation of a team's simulated final points.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PointsStats {
+    pub mean: f64,
+    pub std_dev: f64,
+}
+
+/// Final points `team` can expect at the end of the season.
+pub fn expected_points<'a>(api: &'a Api, team: &'a Team, params: &'a Params) -> PointsStats {
+    let sim = Simulation::today(api, team, params);
+    sim.run_with_stats(params.times())[&team.id]
+}
+
+/// Number of games `record`'s team has left in the regular season.
+pub fn remaining_games(record: &TeamRecord) -> u32 {
+    GAMES_PER_SEASON.saturating_sub(record.games_played)
+}
+
+/// Smallest number of remaining games `team` must win, losing the others in
+/// regulation, for its playoffs odds to reach `threshold`. Returns `None` if
+/// winning every game is not enough.
+pub fn wins_needed<'a>(api: &'a Api, team: &'a Team, threshold: f64, params: &'a Params) -> Option<u32> {
+    let record = api.record_for(team.id, false).expect("team id not found");
+    let remaining = remaining_games(record);
+
+    let odds_with_wins = |wins: u32| {
+        let mut sim = Simulation::today(api, team, params);
+        for game in 0..remaining {
+            if game < wins {
+                sim.give_team_win(team.id);
+            } else {
+                sim.give_team_loss(team.id);
+            }
+        }
+        sim.run_for(params.times()) as f64 / params.times() as f64
+    };
+
+    if odds_with_wins(remaining) < threshold {
+        return None;
+    }
+
+    // The odds grow with the number of wins, so binary search the smallest.
+    let mut low = 0;
+    let mut high = remaining;
+    while low < high {
+        let mid = (low + high) / 2;
+        if odds_with_wins(mid) >= threshold {
+            high = mid;
+        } else {
+            low = mid + 1;
+        }
+    }
+    Some(low)
+}
+
+/// Deterministic baseline where every team finishes the season at its
+/// current points pace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PaceProjection {
+    pub makes_playoffs: bool,
+    /// Rank of the team in its conference by projected points.
+    pub rank: u32,
+}
+
+pub fn points_pace_projection(api: &Api, team: &Team) -> PaceProjection {
+    pace_projection(api, team, &api.standings)
+}
+
+fn pace_projection(api: &Api, team: &Team, records: &[TeamRecord]) -> PaceProjection {
+    let params = Params::default();
+    let sim = Simulation::new(api, team, records, &params);
+    let mut entries = sim.base.clone();
+    for entry in &mut entries {
+        if entry.games_played > 0 {
+            let pace = GAMES_PER_SEASON as f64 / entry.games_played as f64;
+            entry.points = (entry.points as f64 * pace).round() as u32;
+            entry.wins = (entry.wins as f64 * pace).round() as u32;
+        }
+    }
+
+    let qualifiers = sim.select_qualifiers(&mut entries);
+    let rank = entries
+        .iter()
+        .position(|e| e.team_id == team.id)
+        .expect("team id not found") as u32
+        + 1;
+    PaceProjection {
+        makes_playoffs: qualifiers.contains(&team.id),
+        rank,
+    }
+}
+
+/// How much `my_team`'s playoffs odds swing on its game `game`: the odds if it
+/// wins, minus the odds if it loses in regulation.
+pub fn odds_swing<'a>(api: &'a Api, my_team: &'a Team, game: &'a Game, params: &'a Params) -> f64 {
+    let (win, loss) = win_loss_odds(api, my_team, game, params);
+    win - loss
+}
+
+/// Playoffs odds of `my_team` if it wins its game `game`, and if it loses it
+/// in regulation.
+pub fn win_loss_odds<'a>(api: &'a Api, my_team: &'a Team, game: &'a Game, params: &'a Params) -> (f64, f64) {
+    let opponent_id = if game.home_team().id == my_team.id {
+        game.away_team().id
     } else {
-        Simulation::new(api, team, &api.past_standings)
+        game.home_team().id
     };
-    let x = sim.run_for(TIMES);
-    x as f64 / TIMES as f64
+
+    let mut win_sim = Simulation::today(api, my_team, params);
+    win_sim.pin_result(game.game_pk, my_team.id, opponent_id, false);
+    let win_x = win_sim.run_for(params.times());
+
+    let mut loss_sim = Simulation::today(api, my_team, params);
+    loss_sim.pin_result(game.game_pk, opponent_id, my_team.id, false);
+    let loss_x = loss_sim.run_for(params.times());
+
+    (
+        win_x as f64 / params.times() as f64,
+        loss_x as f64 / params.times() as f64,
+    )
+}
+
+/// How much `game` sways the playoffs races: the playoffs odds that change
+/// hands between the teams of its conferences, whether the home or the away
+/// team wins it. Every team's odds count, not only those of the game's teams.
+pub fn game_importance(api: &Api, game: &Game, params: &Params) -> f64 {
+    let home = api.get_team_by_id(game.home_team().id);
+    let away = api.get_team_by_id(game.away_team().id);
+    let mut conferences = vec![home];
+    if away.conference.id != home.conference.id {
+        conferences.push(away);
+    }
+
+    let mut moved = 0;
+    for team in conferences {
+        let distribution = |winner_id: u32, loser_id: u32| {
+            let mut sim = Simulation::today(api, team, params);
+            sim.pin_result(game.game_pk, winner_id, loser_id, false);
+            sim.run_distribution(params.times())
+        };
+        let home_win = distribution(home.id, away.id);
+        let away_win = distribution(away.id, home.id);
+        moved += home_win
+            .iter()
+            .map(|(team_id, &x)| (i64::from(x) - i64::from(away_win[team_id])).abs())
+            .sum::<i64>();
+    }
+    // Every change is counted twice, once for the team gaining the odds and
+    // once for the team losing them.
+    moved as f64 / 2.0 / params.times() as f64
+}
+
+/// Playoffs odds of the team after each team of a game loses it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LoserOdds {
+    pub home_loses: f64,
+    pub away_loses: f64,
+}
+
+impl LoserOdds {
+    /// The team whose loss gives the best odds, the home team on a tie.
+    pub fn ideal_loser<'a>(&self, game: &'a Game) -> &'a nhlapi::Team {
+        if self.away_loses > self.home_loses {
+            game.away_team()
+        } else {
+            game.home_team()
+        }
+    }
 }
 
+/// Playoffs odds of `my_team` after each team of `game` loses it in
+/// regulation, from yesterday's standings if `past`.
+pub fn loser_odds<'a>(api: &'a Api, my_team: &'a Team, past: bool, game: &'a Game, params: &'a Params) -> LoserOdds {
+    // Without yesterday's standings, fall back on today's.
+    let past = past && api.past_standings.is_some();
+    let sim = || Simulation::for_team(api, my_team, past, params).expect("standings are available");
+
+    let mut home_win_sim = sim();
+    home_win_sim.pin_result(game.game_pk, game.home_team().id, game.away_team().id, false);
+    let home_win_x = home_win_sim.run_for(params.times());
+
+    let mut away_win_sim = sim();
+    away_win_sim.pin_result(game.game_pk, game.away_team().id, game.home_team().id, false);
+    let away_win_x = away_win_sim.run_for(params.times());
+
+    LoserOdds {
+        home_loses: f64::from(away_win_x) / f64::from(params.times()),
+        away_loses: f64::from(home_win_x) / f64::from(params.times()),
+    }
+}
+
+/// The team of `game` whose loss most helps `my_team`'s playoffs odds, as
+/// simulated from today's standings, or from yesterday's if `past`. The odds
+/// come from the same simulation as `odds_for_team`, with `game` pinned.
 pub fn pick_ideal_loser<'a>(
     api: &'a Api,
     my_team: &'a Team,
-    records: &'a [TeamRecord],
+    past: bool,
     game: &'a Game,
+    params: &'a Params,
 ) -> &'a nhlapi::Team {
-    let mut home_win_sim = Simulation::new(api, my_team, records);
-    home_win_sim.give_team_win(game.home_team().id);
-    home_win_sim.give_team_loss(game.away_team().id);
-    let home_win_x = home_win_sim.run_for(TIMES);
-
-    let mut away_win_sim = Simulation::new(api, my_team, records);
-    away_win_sim.give_team_win(game.away_team().id);
-    away_win_sim.give_team_loss(game.home_team().id);
-    let away_win_x = away_win_sim.run_for(TIMES);
-
-    if home_win_x > away_win_x {
-        game.away_team()
-    } else {
-        game.home_team()
+    loser_odds(api, my_team, past, game, params).ideal_loser(game)
+}
+
+/// Ids of the teams that played on `previous` when it is the day before
+/// `date`, so that their games on `date` are the second night of a
+/// back-to-back.
+pub fn back_to_back_teams(previous: &nhlapi::schedule::Date, date: &NaiveDate) -> BTreeSet<u32> {
+    if previous.date.succ() != *date {
+        return BTreeSet::new();
     }
+    previous
+        .games
+        .iter()
+        .flat_map(|game| vec![game.home_team().id, game.away_team().id])
+        .collect()
 }
 
+/// Simulation of the rest of the season of a conference.
+///
+/// Every odds of the bot, from the headline odds to the games to cheer for,
+/// comes from `Simulation::for_team`. It plays the known games of the day
+/// between teams of the conference head-to-head, so that both teams of a game
+/// can't win it, and fills the rest of the season team by team.
 pub struct Simulation<'a> {
     my_team: &'a Team,
     base: Vec<Entry>,
+    /// Pks of the games whose result is already given to the teams.
+    pinned: BTreeSet<u64>,
+    /// Games played head-to-head before the rest of the season.
+    slate: Vec<SlateGame>,
+    sort_basis: SortBasis,
+    /// How the playoffs spots of `my_team`'s conference are handed out.
+    format: PlayoffFormat,
+    seed: Option<u64>,
 }
 
 impl Simulation<'_> {
-    pub fn new<'a>(api: &'a Api, my_team: &'a Team, records: &'a [TeamRecord]) -> Simulation<'a> {
-        let mut base = Vec::new();
-        for record in records {
-            let team = api.get_team_by_id(record.team.id);
-            if team.conference.id == my_team.conference.id {
-                base.push(Entry {
-                    team_id: team.id,
-                    division_id: team.division.id,
-                    wins: record.league_record.wins,
-                    losses: record.league_record.losses,
-                    ot: record.league_record.ot,
-                    games_played: record.games_played,
-                    points: record.points,
-                });
+    /// Simulation of `my_team`'s conference from today's standings, or from
+    /// yesterday's if `past`, with the games of that day played head-to-head.
+    /// `None` if `past` and yesterday's standings are unavailable.
+    pub fn for_team<'a>(api: &'a Api, my_team: &'a Team, past: bool, params: &'a Params) -> Option<Simulation<'a>> {
+        let records = api.records(past)?;
+        // Yesterday's games were still to be played in yesterday's standings,
+        // but today's final games are already in today's.
+        let games: Vec<&Game> = if past {
+            api.results.iter().flat_map(|d| &d.games).collect()
+        } else {
+            api.games.games.iter().filter(|g| !g.is_final()).collect()
+        };
+        // The games of the day before yesterday are unknown.
+        let back_to_back = match &api.results {
+            Some(results) if !past => back_to_back_teams(results, &api.games.date),
+            _ => BTreeSet::new(),
+        };
+        Some(Simulation::with_slate(
+            api,
+            my_team,
+            records,
+            &games,
+            &back_to_back,
+            params,
+        ))
+    }
+
+    /// `Simulation::for_team` from today's standings, which are always available.
+    pub fn today<'a>(api: &'a Api, my_team: &'a Team, params: &'a Params) -> Simulation<'a> {
+        Simulation::for_team(api, my_team, false, params).expect("today's standings are available")
+    }
+
+    /// Simulation of `my_team`'s conference from `records`, every team
+    /// playing the rest of its season on its own.
+    pub fn new<'a>(api: &'a Api, my_team: &'a Team, records: &'a [TeamRecord], params: &'a Params) -> Simulation<'a> {
+        Simulation::with_slate(api, my_team, records, &[], &BTreeSet::new(), params)
+    }
+
+    fn with_slate<'a>(
+        api: &'a Api,
+        my_team: &'a Team,
+        records: &'a [TeamRecord],
+        games: &[&Game],
+        back_to_back: &BTreeSet<u32>,
+        params: &'a Params,
+    ) -> Simulation<'a> {
+        let prior = Prior::new(records, params.prior_games);
+        let tiebreaker = params.tiebreaker_on(&api.games.date);
+        let base: Vec<Entry> = records
+            .iter()
+            .map(|record| (record, api.get_team_by_id(record.team.id)))
+            .filter(|(_, team)| params.scope == SimulationScope::League || team.conference.id == my_team.conference.id)
+            .map(|(record, team)| Entry::new(record, team, params, tiebreaker, &prior))
+            .collect();
+        let index_of = |team_id: u32| base.iter().position(|e| e.team_id == team_id);
+        let win_factor = |team_id: u32| {
+            if back_to_back.contains(&team_id) {
+                1.0 - params.back_to_back_penalty
+            } else {
+                1.0
+            }
+        };
+        let slate = games
+            .iter()
+            .filter_map(|game| {
+                Some(SlateGame {
+                    game_pk: game.game_pk,
+                    home: index_of(game.home_team().id)?,
+                    away: index_of(game.away_team().id)?,
+                    home_win_factor: win_factor(game.home_team().id),
+                    away_win_factor: win_factor(game.away_team().id),
+                })
+            })
+            .collect();
+        let mut sim = Simulation {
+            my_team,
+            base,
+            pinned: BTreeSet::new(),
+            slate,
+            sort_basis: params.sort_basis,
+            format: PlayoffFormat::of_conference(&api.teams, my_team.conference.id),
+            seed: params.seed,
+        };
+        // Results naming an unknown team are left out.
+        for (&game_pk, result) in &params.pinned {
+            let winner = api.find_team_by_abbrev(&result.winner);
+            let loser = api.find_team_by_abbrev(&result.loser);
+            if let (Some(winner), Some(loser)) = (winner, loser) {
+                sim.pin_result(game_pk, winner.id, loser.id, result.overtime);
             }
         }
-        Simulation { my_team, base }
+        sim
     }
 
-    pub fn give_team_win(&mut self, team_id: u32) {
-        if let Some(entry) = self.base.iter_mut().find(|x| x.team_id == team_id) {
-            entry.wins += 1;
-            entry.points += 2;
-            entry.games_played += 1;
+    /// Give the result of game `game_pk` to both of its teams, before the rest
+    /// of the season is simulated. Returns false, leaving the simulation as it
+    /// was, if the game already has a result.
+    pub fn pin_result(&mut self, game_pk: u64, winner_id: u32, loser_id: u32, overtime: bool) -> bool {
+        if !self.pinned.insert(game_pk) {
+            return false;
         }
+        self.slate.retain(|g| g.game_pk != game_pk);
+        self.add_event(winner_id, Event::Win);
+        self.add_event(loser_id, if overtime { Event::OtLoss } else { Event::Loss });
+        true
+    }
+
+    pub fn give_team_win(&mut self, team_id: u32) {
+        self.give_team_event(team_id, Event::Win);
     }
 
     pub fn give_team_loss(&mut self, team_id: u32) {
+        self.give_team_event(team_id, Event::Loss);
+    }
+
+    pub fn give_team_ot_loss(&mut self, team_id: u32) {
+        self.give_team_event(team_id, Event::OtLoss);
+    }
+
+    /// Give a result to a team, as the result of its next game. Its game of
+    /// the day, if any, is no longer played head-to-head.
+    pub fn give_team_event(&mut self, team_id: u32, event: Event) {
+        if let Some(index) = self.base.iter().position(|x| x.team_id == team_id) {
+            self.slate.retain(|g| g.home != index && g.away != index);
+        }
+        self.add_event(team_id, event);
+    }
+
+    fn add_event(&mut self, team_id: u32, event: Event) {
         if let Some(entry) = self.base.iter_mut().find(|x| x.team_id == team_id) {
-            entry.losses += 1;
-            entry.games_played += 1;
+            entry.add_event(event);
         }
     }
 
     /// Run the simulation for `times` times, and return the number of times
     /// `self.my_team` made the playoffs.
     pub fn run_for(&self, times: u32) -> u32 {
+        add_sim_count(SimCount::SIMULATION);
+        reseed(self.seed);
         let mut x = 0;
         for _ in 0..times {
             if self.run() {
@@ -135,44 +1102,639 @@ impl Simulation<'_> {
         x
     }
 
+    /// Run the simulation for `times` times, and return the number of times
+    /// each team of the conference made the playoffs, keyed by team id.
+    pub fn run_distribution(&self, times: u32) -> BTreeMap<u32, u32> {
+        add_sim_count(SimCount::SIMULATION);
+        reseed(self.seed);
+        let mut counts: BTreeMap<u32, u32> = self.base.iter().map(|e| (e.team_id, 0)).collect();
+        for _ in 0..times {
+            for team_id in self.qualifiers() {
+                *counts.entry(team_id).or_insert(0) += 1;
+            }
+        }
+        counts
+    }
+
+    /// Run the simulation for `times` times, and return the mean and standard
+    /// deviation of the final points of each team of the conference, keyed by
+    /// team id.
+    pub fn run_with_stats(&self, times: u32) -> BTreeMap<u32, PointsStats> {
+        add_sim_count(SimCount::SIMULATION);
+        reseed(self.seed);
+        let mut sums: BTreeMap<u32, (f64, f64)> = self.base.iter().map(|e| (e.team_id, (0.0, 0.0))).collect();
+        for _ in 0..times {
+            for entry in self.simulate_season() {
+                let sum = sums.entry(entry.team_id).or_insert((0.0, 0.0));
+                sum.0 += entry.points as f64;
+                sum.1 += (entry.points as f64).powi(2);
+            }
+        }
+        sums.into_iter()
+            .map(|(team_id, (sum, sum_squares))| {
+                let mean = sum / times as f64;
+                let variance = (sum_squares / times as f64 - mean * mean).max(0.0);
+                (
+                    team_id,
+                    PointsStats {
+                        mean,
+                        std_dev: variance.sqrt(),
+                    },
+                )
+            })
+            .collect()
+    }
+
+    /// Run the simulation for `times` times, and return the number of times
+    /// `self.my_team` finished within `ranks` of the simulated teams, 1 being
+    /// the best record. The ranks are league-wide with
+    /// `SimulationScope::League`, and within the conference otherwise.
+    pub fn run_for_rank(&self, times: u32, ranks: RangeInclusive<usize>) -> u32 {
+        add_sim_count(SimCount::SIMULATION);
+        reseed(self.seed);
+        let mut x = 0;
+        for _ in 0..times {
+            let mut entries = self.simulate_season();
+            self.sort(&mut entries);
+            let rank = entries.iter().position(|e| e.team_id == self.my_team.id).map(|i| i + 1);
+            if rank.is_some_and(|rank| ranks.contains(&rank)) {
+                x += 1;
+            }
+        }
+        x
+    }
+
     fn run(&self) -> bool {
+        self.qualifiers().contains(&self.my_team.id)
+    }
+
+    /// Run the simulation for `times` times, and return the number of times
+    /// each team was `self.my_team`'s first round opponent, keyed by team id.
+    pub fn run_opponents(&self, times: u32) -> BTreeMap<u32, u32> {
+        add_sim_count(SimCount::SIMULATION);
+        reseed(self.seed);
+        let mut counts = BTreeMap::new();
+        for _ in 0..times {
+            let mut entries = self.simulate_season();
+            let opponent = self
+                .seed(&mut entries)
+                .first_round()
+                .into_iter()
+                .find_map(|(high, low)| {
+                    if high == self.my_team.id {
+                        Some(low)
+                    } else if low == self.my_team.id {
+                        Some(high)
+                    } else {
+                        None
+                    }
+                });
+            if let Some(opponent) = opponent {
+                *counts.entry(opponent).or_insert(0) += 1;
+            }
+        }
+        counts
+    }
+
+    /// Simulate the rest of the season once, and return the ids of the teams
+    /// that made the playoffs.
+    fn qualifiers(&self) -> BTreeSet<u32> {
+        let mut entries = self.simulate_season();
+        self.select_qualifiers(&mut entries)
+    }
+
+    /// Simulate the rest of the season once, and return the final records.
+    fn simulate_season(&self) -> Vec<Entry> {
+        add_sim_count(SimCount::SEASON);
         let mut entries = self.base.clone();
+        for game in &self.slate {
+            if entries[game.home].games_played >= GAMES_PER_SEASON
+                || entries[game.away].games_played >= GAMES_PER_SEASON
+            {
+                continue;
+            }
+            let (home_event, away_event) = random_head_to_head(&self.base, game);
+            entries[game.home].add_event(home_event);
+            entries[game.away].add_event(away_event);
+        }
         for (base, entry) in self.base.iter().zip(entries.iter_mut()) {
-            while entry.games_played < 82 {
-                let event = random_event(base);
-                entry.games_played += 1;
-                entry.points += event.points();
-                match event {
-                    Event::Win => entry.wins += 1,
-                    Event::Loss => entry.losses += 1,
-                    Event::Ot => entry.ot += 1,
+            while entry.games_played < GAMES_PER_SEASON {
+                entry.add_event(random_event(base));
+            }
+        }
+        entries
+    }
+
+    /// Sort the final standings and return the ids of the teams that made the
+    /// playoffs.
+    fn select_qualifiers(&self, entries: &mut [Entry]) -> BTreeSet<u32> {
+        self.seed(entries).qualifiers()
+    }
+
+    /// Sort the final standings, the best record first.
+    fn sort(&self, entries: &mut [Entry]) {
+        let basis = self.sort_basis;
+        entries.sort_unstable_by(|a, b| {
+            basis
+                .cmp((a.points, a.games_played), (b.points, b.games_played))
+                .then_with(|| b.tiebreak_wins.cmp(&a.tiebreak_wins))
+        });
+    }
+
+    /// Sort the final standings and seed the teams that made the playoffs.
+    fn seed(&self, entries: &mut [Entry]) -> Seeds {
+        self.sort(entries);
+
+        // The playoffs are per conference, whatever the scope. The entries
+        // are sorted, so the teams are seeded in the order of their rank.
+        let format = self.format;
+        let mut division_ids = vec![self.my_team.division.id];
+        let mut division_seeds = vec![vec![]];
+        let mut wildcards = vec![];
+        let conference = entries
+            .iter()
+            .filter(|x| x.conference_id == self.my_team.conference.id)
+            .enumerate();
+        for (rank, x) in conference {
+            let index = match division_ids.iter().position(|&id| id == x.division_id) {
+                Some(index) => index,
+                None => {
+                    division_ids.push(x.division_id);
+                    division_seeds.push(vec![]);
+                    division_ids.len() - 1
                 }
+            };
+            if division_seeds[index].len() < format.division_spots {
+                division_seeds[index].push((rank, x.team_id));
+            } else if wildcards.len() < format.wildcard_spots {
+                wildcards.push((rank, x.team_id));
             }
         }
 
-        entries.sort_unstable_by_key(|e| Reverse((e.points, e.wins)));
+        Seeds {
+            format,
+            division_seeds,
+            wildcards,
+        }
+    }
+}
+
+/// Playoffs seeds of a simulated season, as the rank of each team in its
+/// conference along with its id.
+struct Seeds {
+    format: PlayoffFormat,
+    /// Teams holding the spots of each division, the own division first.
+    division_seeds: Vec<Vec<(usize, u32)>>,
+    wildcards: Vec<(usize, u32)>,
+}
 
-        let top_3_teams: BTreeSet<u32> = entries
+impl Seeds {
+    fn qualifiers(&self) -> BTreeSet<u32> {
+        self.division_seeds
             .iter()
-            .filter(|x| x.division_id == self.my_team.division.id)
-            .take(3)
-            .map(|x| x.team_id)
-            .chain(
-                entries
-                    .iter()
-                    .filter(|x| x.division_id != self.my_team.division.id)
-                    .take(3)
-                    .map(|x| x.team_id),
+            .flatten()
+            .chain(&self.wildcards)
+            .map(|&(_, team_id)| team_id)
+            .collect()
+    }
+
+    /// First round matchups, high seed first, as `analysis` seeds them.
+    fn first_round(&self) -> Vec<(u32, u32)> {
+        analysis::first_round(
+            self.format,
+            &self.division_seeds,
+            &self.wildcards,
+            |a, b| a.0.cmp(&b.0),
+            |_| 'D',
+        )
+        .into_iter()
+        .map(|((high, _), (low, _))| (high.1, low.1))
+        .collect()
+    }
+}
+
+#[test]
+fn test_event_from_str() {
+    assert_eq!("W".parse::<Event>().unwrap(), Event::Win);
+    assert_eq!("l".parse::<Event>().unwrap(), Event::Loss);
+    assert_eq!("OT".parse::<Event>().unwrap(), Event::OtLoss);
+    assert!("SO".parse::<Event>().is_err());
+}
+
+#[test]
+fn test_outcome_weights_form() {
+    let standings = nhlapi::standings::parse(include_str!("../tests/fixtures/standings-2019-03-15.json")).unwrap();
+    // Carolina is 38-25-6 on the season, but 8-2-0 in its last 10 games.
+    let record = standings.iter().find(|r| r.team.id == 12).unwrap();
+
+    let season = outcome_weights(record, 0.0);
+    assert!((season.0 - 38.0 / 69.0).abs() < 1e-9);
+    assert!((season.0 + season.1 + season.2 - 1.0).abs() < 1e-9);
+
+    let blended = outcome_weights(record, 0.3);
+    assert!(blended.0 > season.0);
+    assert!((blended.0 - (0.7 * 38.0 / 69.0 + 0.3 * 0.8)).abs() < 1e-9);
+    assert!((blended.0 + blended.1 + blended.2 - 1.0).abs() < 1e-9);
+
+    let form = outcome_weights(record, 1.0);
+    assert!((form.0 - 0.8).abs() < 1e-9);
+}
+
+#[test]
+fn test_streak_adjusted() {
+    let standings = nhlapi::standings::parse(include_str!("../tests/fixtures/standings-2019-03-15.json")).unwrap();
+    let record = |team_id| standings.iter().find(|r| r.team.id == team_id).unwrap();
+    // Carolina is on a 4 game winning streak, New Jersey on a 4 game losing streak.
+    let (hot, cold) = (record(12), record(1));
+
+    let season = outcome_weights(hot, 0.0);
+    assert_eq!(streak_adjusted(season, hot, 0.0), season);
+    let nudged = streak_adjusted(season, hot, 0.2);
+    assert!(nudged.0 > season.0 && nudged.1 < season.1);
+    assert!((nudged.0 + nudged.1 + nudged.2 - 1.0).abs() < 1e-9);
+    assert!((nudged.0 / nudged.1 - 1.2 * season.0 / season.1).abs() < 1e-9);
+
+    let season = outcome_weights(cold, 0.0);
+    let nudged = streak_adjusted(season, cold, 0.2);
+    assert!(nudged.0 < season.0 && nudged.1 > season.1);
+
+    // Montreal's 1 game streak is not a streak.
+    let season = outcome_weights(record(8), 0.0);
+    assert_eq!(streak_adjusted(season, record(8), 0.2), season);
+}
+
+#[test]
+fn test_regressed() {
+    let standings = nhlapi::standings::parse(include_str!("../tests/fixtures/standings-2019-03-15.json")).unwrap();
+    // Every game has a winner, so the league wins about half of its games.
+    let prior = Prior::new(&standings, 10.0);
+    assert!((prior.rates.0 - 0.5).abs() < 0.01);
+    assert!((prior.rates.0 + prior.rates.1 + prior.rates.2 - 1.0).abs() < 1e-9);
+
+    // A team that is 3-0-0 always wins without a prior, but is closer to the
+    // league average with one.
+    let mut record = standings[0].clone();
+    record.league_record = nhlapi::LeagueRecord {
+        wins: 3,
+        losses: 0,
+        ot: 0,
+    };
+    record.games_played = 3;
+    let season = outcome_weights(&record, 0.0);
+    assert_eq!(season, (1.0, 0.0, 0.0));
+    assert_eq!(regressed(season, &record, &Prior::new(&standings, 0.0)), season);
+    let weights = regressed(season, &record, &prior);
+    assert!((weights.0 - (3.0 + 10.0 * prior.rates.0) / 13.0).abs() < 1e-9);
+    assert!(weights.1 > 0.0 && weights.2 > 0.0);
+    assert!((weights.0 + weights.1 + weights.2 - 1.0).abs() < 1e-9);
+
+    // The prior weighs less as the season goes on.
+    let late = standings.iter().find(|r| r.team.id == 14).unwrap();
+    let late_season = outcome_weights(late, 0.0);
+    let late_weights = regressed(late_season, late, &prior);
+    assert!(late_weights.0 < late_season.0);
+    assert!(late_season.0 - late_weights.0 < 0.05);
+
+    // Before the first game, the odds are the league's.
+    record.league_record = nhlapi::LeagueRecord {
+        wins: 0,
+        losses: 0,
+        ot: 0,
+    };
+    record.games_played = 0;
+    assert_eq!(regressed((0.0, 0.0, 0.0), &record, &prior), prior.rates);
+    assert_eq!(Prior::new(&[record], 10.0).rates, LEAGUE_RATES);
+}
+
+#[test]
+fn test_entry_from_record() {
+    let teams = nhlapi::teams::parse(include_str!("../tests/fixtures/teams.json")).unwrap();
+    let standings = nhlapi::standings::parse(include_str!("../tests/fixtures/standings-2019-03-15.json")).unwrap();
+    let params = Params::default();
+    let prior = Prior::new(&standings, params.prior_games);
+    for record in &standings {
+        let team = teams.iter().find(|t| t.id == record.team.id).unwrap();
+        let mut entry = Entry::new(record, team, &params, Tiebreaker::Row, &prior);
+        assert_eq!((entry.team_id, entry.division_id), (team.id, team.division.id));
+        let league = &record.league_record;
+        assert_eq!(
+            (entry.wins, entry.losses, entry.ot),
+            (league.wins, league.losses, league.ot)
+        );
+        assert_eq!(entry.games_played, record.games_played);
+        assert_eq!(entry.points, record.points);
+        assert_eq!(entry.tiebreak_wins, record.row);
+        assert_eq!(
+            (entry.win_weight, entry.loss_weight, entry.ot_loss_weight),
+            streak_adjusted(regressed(outcome_weights(record, 0.0), record, &prior), record, 0.0)
+        );
+
+        // Playing out the season keeps the record consistent.
+        while entry.games_played < GAMES_PER_SEASON {
+            entry.add_event(random_event(&entry.clone()));
+        }
+        assert_eq!(entry.wins + entry.losses + entry.ot, GAMES_PER_SEASON);
+        assert_eq!(entry.points, 2 * entry.wins + entry.ot);
+        assert_eq!(entry.tiebreak_wins, record.row + entry.wins - league.wins);
+    }
+}
+
+#[test]
+fn test_expected_points_per_game() {
+    let standings = nhlapi::standings::parse(include_str!("../tests/fixtures/standings-2019-03-15.json")).unwrap();
+    for record in &standings {
+        let (win, loss, ot_loss) = outcome_weights(record, 0.0);
+        let expected = win * Event::Win.points() as f64
+            + loss * Event::Loss.points() as f64
+            + ot_loss * Event::OtLoss.points() as f64;
+        // Sampling the outcomes reproduces the points pace of the season.
+        let pace = record.points as f64 / record.games_played as f64;
+        assert!(
+            (expected - pace).abs() < 1e-9,
+            "{}: {} != {}",
+            record.team.name,
+            expected,
+            pace
+        );
+    }
+}
+
+#[test]
+fn test_sort_basis() {
+    // 81 points in 70 games against 82 points in 72 games.
+    let (a, b) = ((81, 70), (82, 72));
+    assert_eq!(SortBasis::Points.cmp(a, b), cmp::Ordering::Greater);
+    assert_eq!(SortBasis::PointsPercentage.cmp(a, b), cmp::Ordering::Less);
+    assert_eq!(
+        SortBasis::PointsPercentage.cmp((81, 70), (162, 140)),
+        cmp::Ordering::Equal
+    );
+    // A team that has yet to play is at 0%.
+    assert_eq!(SortBasis::PointsPercentage.cmp((0, 0), (1, 1)), cmp::Ordering::Greater);
+}
+
+#[test]
+fn test_first_round() {
+    // Teams 1 to 8 ranked 1st to 8th of the conference.
+    let ranked = |ids: &[u32]| ids.iter().map(|&id| (id as usize - 1, id)).collect::<Vec<_>>();
+    let seeds = Seeds {
+        format: PlayoffFormat::with_divisions(2),
+        division_seeds: vec![ranked(&[2, 3, 5]), ranked(&[1, 4, 6])],
+        wildcards: ranked(&[7, 8]),
+    };
+    assert_eq!(seeds.first_round(), vec![(1, 8), (2, 7), (3, 5), (4, 6)]);
+    assert_eq!(seeds.qualifiers().len(), 8);
+
+    let seeds = Seeds {
+        division_seeds: vec![ranked(&[1, 3, 5]), ranked(&[2, 4, 6])],
+        ..seeds
+    };
+    assert_eq!(seeds.first_round(), vec![(1, 8), (2, 7), (3, 5), (4, 6)]);
+
+    // With 4 divisions, the division leaders are the top seeds of the
+    // conference.
+    let seeds = Seeds {
+        format: PlayoffFormat::with_divisions(4),
+        division_seeds: vec![ranked(&[5]), ranked(&[1]), ranked(&[2]), ranked(&[7])],
+        wildcards: ranked(&[3, 4, 6, 8]),
+    };
+    assert_eq!(seeds.first_round(), vec![(1, 8), (2, 6), (5, 4), (7, 3)]);
+    assert_eq!(seeds.qualifiers().len(), 8);
+
+    // Short of a full bracket, there is no first round.
+    let seeds = Seeds {
+        wildcards: ranked(&[3, 4, 6]),
+        ..seeds
+    };
+    assert!(seeds.first_round().is_empty());
+}
+
+/// The league on March 15th 2019, from the fixtures of the tests, as
+/// `tests/common::load_api` loads it.
+#[cfg(test)]
+fn fixture_api() -> Api {
+    let today = NaiveDate::from_ymd(2019, 3, 15);
+    Api::from_parts(
+        nhlapi::teams::parse(include_str!("../tests/fixtures/teams.json")).unwrap(),
+        Some(nhlapi::standings::parse(include_str!("../tests/fixtures/standings-2019-03-14.json")).unwrap()),
+        nhlapi::standings::parse(include_str!("../tests/fixtures/standings-2019-03-15.json")).unwrap(),
+        Some(
+            nhlapi::schedule::parse_date(
+                include_str!("../tests/fixtures/schedule-2019-03-14.json"),
+                &today.pred(),
             )
-            .collect();
+            .unwrap(),
+        ),
+        nhlapi::schedule::parse_date(include_str!("../tests/fixtures/schedule-2019-03-15.json"), &today).unwrap(),
+    )
+}
 
-        let wildcard: BTreeSet<u32> = entries
-            .iter()
-            .filter(|x| !top_3_teams.contains(&x.team_id))
-            .take(2)
-            .map(|x| x.team_id)
-            .collect();
+#[test]
+fn test_slate_head_to_head() {
+    let api = fixture_api();
+    let team = api.get_team_by_abbrev("MTL");
+    let params = Params::default();
+
+    // Today's games between teams of the conference are on the slate.
+    let conference = |id: u32| api.get_team_by_id(id).conference.id == team.conference.id;
+    let expected = api
+        .games
+        .games
+        .iter()
+        .filter(|g| !g.is_final() && conference(g.home_team().id) && conference(g.away_team().id))
+        .count();
+    let mut sim = Simulation::today(&api, team, &params);
+    assert!(expected > 0);
+    assert_eq!(sim.slate.len(), expected);
+    let no_past = Api {
+        past_standings: None,
+        ..fixture_api()
+    };
+    assert!(Simulation::for_team(&no_past, team, true, &params).is_none());
+    assert!(Simulation::new(&api, team, &api.standings, &params).slate.is_empty());
+
+    // With one game left each, the slate games have exactly one winner.
+    for entry in &mut sim.base {
+        entry.games_played = GAMES_PER_SEASON - 1;
+    }
+    let entries = sim.simulate_season();
+    for game in &sim.slate {
+        assert_eq!(
+            entries[game.home].wins + entries[game.away].wins,
+            sim.base[game.home].wins + sim.base[game.away].wins + 1
+        );
+    }
+
+    // A pinned game, or a result given to one of its teams, is off the slate.
+    let game = sim.slate[0];
+    let (home_id, away_id) = (sim.base[game.home].team_id, sim.base[game.away].team_id);
+    assert!(sim.pin_result(game.game_pk, home_id, away_id, false));
+    assert_eq!(sim.slate.len(), expected - 1);
+    if let Some(game) = sim.slate.first().cloned() {
+        sim.give_team_win(sim.base[game.away].team_id);
+        assert!(sim.slate.iter().all(|g| g.game_pk != game.game_pk));
+    }
+}
+
+#[test]
+fn test_back_to_back_penalty() {
+    let today = NaiveDate::from_ymd(2019, 3, 15);
+    let api = fixture_api();
+    let results = api.results.clone().unwrap();
+    let team = api.get_team_by_abbrev("MTL");
+
+    let back_to_back = back_to_back_teams(&results, &today);
+    assert!(back_to_back.contains(&team.id));
+    assert!(!back_to_back.contains(&api.get_team_by_abbrev("TBL").id));
+    assert!(back_to_back_teams(&results, &today.succ()).is_empty());
+
+    // Montreal played last night at home, Tampa Bay did not.
+    let params = Params {
+        back_to_back_penalty: 0.2,
+        ..Default::default()
+    };
+    let sim = Simulation::today(&api, team, &params);
+    let game = sim.slate.iter().find(|g| sim.base[g.away].team_id == team.id).unwrap();
+    let (home_win, away_win) = game.win_weights(&sim.base);
+    assert!((home_win - sim.base[game.home].win_weight).abs() < 1e-9);
+    assert!((away_win - 0.8 * sim.base[game.away].win_weight).abs() < 1e-9);
+
+    // Without a penalty, or without last night's games, nobody is penalized.
+    for sim in &[
+        Simulation::today(&api, team, &Params::default()),
+        Simulation::new(&api, team, &api.standings, &params),
+    ] {
+        for game in &sim.slate {
+            assert_eq!(
+                game.win_weights(&sim.base),
+                (sim.base[game.home].win_weight, sim.base[game.away].win_weight)
+            );
+        }
+    }
+}
+
+#[test]
+fn test_forced_results_monotonic() {
+    let api = fixture_api();
+    let team = api.get_team_by_abbrev("MTL");
+    let params = Params {
+        seed: Some(2019),
+        ..Default::default()
+    };
+    let times = 20_000;
+    let odds = |sim: &Simulation| sim.run_for(times) as f64 / times as f64;
+
+    let baseline = odds(&Simulation::new(&api, team, &api.standings, &params));
+    assert!(baseline > 0.05 && baseline < 0.95);
+    for &(event, better) in &[(Event::Win, true), (Event::OtLoss, false), (Event::Loss, false)] {
+        let mut sim = Simulation::new(&api, team, &api.standings, &params);
+        sim.give_team_event(team.id, event);
+        let forced = odds(&sim);
+        if better {
+            assert!(forced >= baseline, "{:?}: {} < {}", event, forced, baseline);
+        } else {
+            assert!(forced <= baseline, "{:?}: {} > {}", event, forced, baseline);
+        }
+    }
 
-        top_3_teams.contains(&self.my_team.id) || wildcard.contains(&self.my_team.id)
+    // A win is worth more than an overtime loss, which is worth more than a loss.
+    let mut forced = vec![];
+    for &event in &[Event::Win, Event::OtLoss, Event::Loss] {
+        let mut sim = Simulation::new(&api, team, &api.standings, &params);
+        for _ in 0..3 {
+            sim.give_team_event(team.id, event);
+        }
+        forced.push(odds(&sim));
     }
+    assert!(forced[0] >= forced[1] && forced[1] >= forced[2], "{:?}", forced);
+}
+
+#[test]
+fn test_seeded_runs() {
+    let api = fixture_api();
+    let team = api.get_team_by_abbrev("MTL");
+    let params = |seed| Params {
+        seed,
+        ..Default::default()
+    };
+    let seeded = params(Some(2019));
+    let sim = Simulation::today(&api, team, &seeded);
+    let first = (sim.run_for(1000), sim.run_distribution(1000));
+    assert_eq!((sim.run_for(1000), sim.run_distribution(1000)), first);
+    // Also from another thread, and after unseeded runs.
+    let unseeded = params(None);
+    Simulation::today(&api, team, &unseeded).run_for(1000);
+    let other = thread::scope(|scope| {
+        scope
+            .spawn(|| Simulation::today(&api, team, &seeded).run_for(1000))
+            .join()
+            .unwrap()
+    });
+    assert_eq!(other, first.0);
+    assert_eq!(Simulation::today(&api, team, &seeded).run_for(1000), first.0);
+}
+
+#[test]
+fn test_simulation_scope() {
+    let api = fixture_api();
+    let params = |scope| Params {
+        scope,
+        seed: Some(2019),
+        ..Default::default()
+    };
+    let conference = params(SimulationScope::Conference);
+    let league = params(SimulationScope::League);
+    let times = 2000;
+
+    // 16 teams in the East, 31 in the league.
+    let mtl = api.get_team_by_abbrev("MTL");
+    let sim = Simulation::today(&api, mtl, &conference);
+    assert_eq!(sim.base.len(), 16);
+    assert_eq!(sim.run_for_rank(times, 1..=16), times);
+    let sim = Simulation::today(&api, mtl, &league);
+    assert_eq!(sim.base.len(), 31);
+    assert_eq!(sim.run_for_rank(times, 1..=31), times);
+    assert_eq!(sim.run_for_rank(times, 32..=40), 0);
+
+    // The playoffs stay within the conference.
+    let in_conference = Simulation::today(&api, mtl, &conference).run_for(times) as f64 / times as f64;
+    let in_league = Simulation::today(&api, mtl, &league).run_for(times) as f64 / times as f64;
+    assert!(
+        (in_conference - in_league).abs() < 0.05,
+        "{} vs {}",
+        in_conference,
+        in_league
+    );
+    let distribution = Simulation::today(&api, mtl, &league).run_distribution(100);
+    assert_eq!(distribution.len(), 31);
+    assert_eq!(distribution.values().sum::<u32>(), 8 * 100);
+
+    // TBL is 13 points clear of the league, and CGY leads the West.
+    let tbl = api.get_team_by_abbrev("TBL");
+    assert!(Simulation::today(&api, tbl, &league).run_for_rank(times, 1..=1) > times * 9 / 10);
+    let cgy = api.get_team_by_abbrev("CGY");
+    let cgy_first = Simulation::today(&api, cgy, &league).run_for_rank(times, 1..=1);
+    let cgy_conference_first = Simulation::today(&api, cgy, &conference).run_for_rank(times, 1..=1);
+    assert!(cgy_first < cgy_conference_first);
+}
+
+#[test]
+fn test_tiebreaker() {
+    let season = |end| nhlapi::Season { begin: end - 1, end };
+    assert_eq!(Tiebreaker::official(&season(2019)), Tiebreaker::Row);
+    assert_eq!(Tiebreaker::official(&season(2020)), Tiebreaker::Rw);
+
+    let params = Params::default();
+    assert_eq!(params.tiebreaker_on(&NaiveDate::from_ymd(2019, 3, 15)), Tiebreaker::Row);
+    assert_eq!(params.tiebreaker_on(&NaiveDate::from_ymd(2019, 10, 15)), Tiebreaker::Rw);
+    let params = Params {
+        tiebreaker: Some(Tiebreaker::Rw),
+        ..Default::default()
+    };
+    assert_eq!(params.tiebreaker_on(&NaiveDate::from_ymd(2019, 3, 15)), Tiebreaker::Rw);
+
+    let standings = nhlapi::standings::parse(include_str!("../tests/fixtures/standings-2019-03-15.json")).unwrap();
+    let mtl = standings.iter().find(|r| r.team.id == 8).unwrap();
+    assert_eq!((Tiebreaker::Row.wins(mtl), Tiebreaker::Rw.wins(mtl)), (33, 30));
+    assert_eq!((Tiebreaker::Row.header(), Tiebreaker::Rw.header()), ("ROW", "RW"));
 }
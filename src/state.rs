@@ -0,0 +1,165 @@
+//! Record of the last post made for each team, kept between runs so that
+//! unchanged posts are not submitted again.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use failure::Error;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct State {
+    teams: BTreeMap<String, TeamState>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TeamState {
+    /// Hash of the markdown of the last post.
+    pub hash: u64,
+    /// Reddit id of the last post, if it was submitted. The post to the
+    /// first subreddit when it went to several.
+    pub post_id: Option<String>,
+    /// Reddit ids of the last posts by subreddit, when they were submitted.
+    #[serde(default)]
+    pub post_ids: BTreeMap<String, String>,
+    /// Playoffs odds of the team in the last post, if they were computed.
+    #[serde(default)]
+    pub odds: Option<f64>,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl State {
+    /// Load the state from `path`, or an empty state if the file does not exist.
+    pub fn load(path: &Path) -> Result<State, Error> {
+        match File::open(path) {
+            Ok(file) => Ok(serde_json::from_reader(file)?),
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(State::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        let file = File::create(path)?;
+        serde_json::to_writer_pretty(file, self)?;
+        Ok(())
+    }
+
+    pub fn get(&self, abbrev: &str) -> Option<&TeamState> {
+        self.teams.get(abbrev)
+    }
+
+    /// Whether the last post of the team had the same hash.
+    pub fn is_unchanged(&self, abbrev: &str, hash: u64) -> bool {
+        self.get(abbrev).map(|s| s.hash == hash).unwrap_or(false)
+    }
+
+    /// Whether the odds of the team moved by less than `delta` since the last
+    /// post. Always false when the last post has no odds.
+    pub fn odds_within(&self, abbrev: &str, odds: f64, delta: f64) -> bool {
+        self.get(abbrev)
+            .and_then(|s| s.odds)
+            .map(|last| (odds - last).abs() < delta)
+            .unwrap_or(false)
+    }
+
+    pub fn update(&mut self, abbrev: &str, hash: u64, post_id: Option<String>, odds: Option<f64>) {
+        self.teams.insert(
+            abbrev.to_string(),
+            TeamState {
+                hash,
+                post_id,
+                post_ids: BTreeMap::new(),
+                odds,
+                timestamp: Utc::now(),
+            },
+        );
+    }
+
+    /// `update` after posting to several subreddits, with the `(subreddit,
+    /// post id)` of every post submitted, in the order they were posted.
+    pub fn update_posts(&mut self, abbrev: &str, hash: u64, post_ids: Vec<(String, String)>, odds: Option<f64>) {
+        self.update(abbrev, hash, post_ids.first().map(|(_, id)| id.clone()), odds);
+        if let Some(team) = self.teams.get_mut(abbrev) {
+            team.post_ids = post_ids.into_iter().collect();
+        }
+    }
+}
+
+/// Hash a document with FNV-1a. Unlike the standard library's hasher, the
+/// result is stable across builds, so it can be stored on disk.
+pub fn hash_document(doc: &str) -> u64 {
+    doc.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[test]
+fn test_hash_document() {
+    assert_eq!(hash_document(""), 0xcbf2_9ce4_8422_2325);
+    assert_eq!(hash_document("a"), 0xaf63_dc4c_8601_ec8c);
+    assert_ne!(hash_document("# hello\n"), hash_document("# hello!\n"));
+}
+
+#[test]
+fn test_state_update() {
+    let mut state = State::default();
+    assert!(!state.is_unchanged("MTL", 42));
+
+    state.update("MTL", 42, Some("t3_abcdef".to_string()), None);
+    assert!(state.is_unchanged("MTL", 42));
+    assert!(!state.is_unchanged("MTL", 43));
+    assert!(!state.is_unchanged("TOR", 42));
+
+    let json = serde_json::to_string(&state).unwrap();
+    let state: State = serde_json::from_str(&json).unwrap();
+    assert!(state.is_unchanged("MTL", 42));
+    assert_eq!(state.get("MTL").unwrap().post_id.as_deref(), Some("t3_abcdef"));
+}
+
+#[test]
+fn test_state_odds_within() {
+    let mut state = State::default();
+    assert!(!state.odds_within("MTL", 0.5, 0.02));
+
+    state.update("MTL", 42, None, None);
+    assert!(!state.odds_within("MTL", 0.5, 0.02));
+
+    state.update("MTL", 42, None, Some(0.5));
+    assert!(state.odds_within("MTL", 0.51, 0.02));
+    assert!(state.odds_within("MTL", 0.49, 0.02));
+    assert!(!state.odds_within("MTL", 0.53, 0.02));
+    assert!(!state.odds_within("TOR", 0.5, 0.02));
+
+    // States saved before the odds were kept still load.
+    let state: State = serde_json::from_str(
+        r#"{"teams": {"MTL": {"hash": 42, "post_id": null, "timestamp": "2019-03-15T12:00:00Z"}}}"#,
+    )
+    .unwrap();
+    assert!(state.get("MTL").unwrap().odds.is_none());
+}
+
+#[test]
+fn test_state_update_posts() {
+    let mut state = State::default();
+    let post_ids = vec![
+        ("habs".to_string(), "t3_abc".to_string()),
+        ("habsstats".to_string(), "t3_def".to_string()),
+    ];
+    state.update_posts("MTL", 42, post_ids, None);
+    let json = serde_json::to_string(&state).unwrap();
+    let state: State = serde_json::from_str(&json).unwrap();
+    let mtl = state.get("MTL").unwrap();
+    assert!(state.is_unchanged("MTL", 42));
+    assert_eq!(mtl.post_id.as_deref(), Some("t3_abc"));
+    assert_eq!(mtl.post_ids["habsstats"], "t3_def");
+
+    // States saved before the posts by subreddit still load.
+    let state: State = serde_json::from_str(
+        r#"{"teams": {"MTL": {"hash": 42, "post_id": null, "timestamp": "2019-03-15T12:00:00Z"}}}"#,
+    )
+    .unwrap();
+    assert!(state.get("MTL").unwrap().post_ids.is_empty());
+}
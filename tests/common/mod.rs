@@ -0,0 +1,55 @@
+#![allow(dead_code)]
+
+use std::fs;
+
+use chrono::NaiveDate;
+
+use playoffsbot::analysis::{self, Api};
+use playoffsbot::nhlapi;
+use playoffsbot::simulation;
+
+pub fn fixture(name: &str) -> String {
+    let path = format!("{}/tests/fixtures/{}.json", env!("CARGO_MANIFEST_DIR"), name);
+    fs::read_to_string(&path).unwrap_or_else(|e| panic!("error reading fixture {}: {}", path, e))
+}
+
+pub fn load_api() -> Api {
+    let today = NaiveDate::from_ymd(2019, 3, 15);
+    let teams = nhlapi::teams::parse(&fixture("teams")).unwrap();
+    let past_standings = nhlapi::standings::parse(&fixture("standings-2019-03-14")).unwrap();
+    let standings = nhlapi::standings::parse(&fixture("standings-2019-03-15")).unwrap();
+    let results = nhlapi::schedule::parse_date(&fixture("schedule-2019-03-14"), &today.pred()).unwrap();
+    let games = nhlapi::schedule::parse_date(&fixture("schedule-2019-03-15"), &today).unwrap();
+    Api::from_parts(teams, Some(past_standings), standings, Some(results), games)
+}
+
+pub fn load_schedule() -> Vec<nhlapi::schedule::Date> {
+    nhlapi::schedule::parse_range(&fixture("schedule-MTL-2019-03-15-2019-05-01")).unwrap()
+}
+
+/// Parameters of the simulations of the tests: seeded, and with far fewer
+/// seasons than the posts so that the tests run quickly.
+pub fn test_params() -> simulation::Params {
+    simulation::Params {
+        seed: Some(2019),
+        times: Some(5000),
+        ..Default::default()
+    }
+}
+
+/// Options of the analysis of the tests, simulated with `test_params`.
+pub fn test_options() -> analysis::Options {
+    analysis::Options {
+        params: test_params(),
+        ..Default::default()
+    }
+}
+
+/// Options of the analysis of the tests that only check the seeding, the
+/// standings or the formatting of the post, without any simulation.
+pub fn seeding_options() -> analysis::Options {
+    analysis::Options {
+        skip_simulations: true,
+        ..test_options()
+    }
+}
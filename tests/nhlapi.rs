@@ -0,0 +1,396 @@
+mod common;
+
+use chrono::{Duration, NaiveDate};
+
+use playoffsbot::nhlapi::{self, teams::NameStyle};
+
+use common::fixture;
+
+#[test]
+fn test_schedule_without_linescore() {
+    let dates = nhlapi::schedule::parse_range(&fixture("schedule-MTL-2019-04-04-2019-04-06")).unwrap();
+    assert_eq!(dates.len(), 2);
+
+    // The linescore has no periods.
+    let game = &dates[0].games[0];
+    assert_eq!(game.linescore.current_period, 0);
+    assert!(game.linescore.periods.is_empty());
+    assert!(!game.overtime());
+    assert!(!game.shootout());
+
+    // The linescore is missing entirely.
+    let game = &dates[1].games[0];
+    assert_eq!(game.linescore.current_period, 0);
+    assert!(!game.overtime());
+    assert!(!game.shootout());
+}
+
+#[test]
+fn test_schedule_not_final() {
+    let mut dates = nhlapi::schedule::parse_range(&fixture("schedule-MTL-2018-10-03-2019-03-14")).unwrap();
+    assert!(dates.iter().flat_map(|d| &d.games).all(|g| g.is_final()));
+    dates.extend(nhlapi::schedule::parse_range(&fixture("schedule-MTL-2019-03-15-2019-05-01")).unwrap());
+
+    let remaining = nhlapi::schedule::not_final(dates);
+    assert_eq!(remaining.iter().map(|d| d.games.len()).sum::<usize>(), 12);
+    assert!(remaining.iter().all(|d| d.date >= NaiveDate::from_ymd(2019, 3, 15)));
+}
+
+#[test]
+fn test_season_of() {
+    let season = nhlapi::Season { begin: 2018, end: 2019 };
+    assert_eq!(nhlapi::Season::of(&NaiveDate::from_ymd(2018, 10, 3)), season);
+    assert_eq!(nhlapi::Season::of(&NaiveDate::from_ymd(2019, 4, 6)), season);
+    assert_eq!(
+        season.default_bounds(),
+        (NaiveDate::from_ymd(2018, 10, 1), NaiveDate::from_ymd(2019, 5, 1))
+    );
+}
+
+#[test]
+fn test_schedule_overtime() {
+    let date = nhlapi::schedule::parse_range(&fixture("schedule-2019-03-14"))
+        .unwrap()
+        .remove(0);
+    let ot = date.games.iter().filter(|g| g.overtime()).count();
+    let so = date.games.iter().filter(|g| g.shootout()).count();
+    assert_eq!(ot, 2);
+    assert_eq!(so, 1);
+}
+
+#[test]
+fn test_team_timezone_fallback() {
+    let teams = nhlapi::teams::parse(&fixture("teams")).unwrap();
+    let mut team = teams.into_iter().find(|t| t.abbrev == "MTL").unwrap();
+    assert_eq!(team.timezone().name(), "America/Montreal");
+    assert_eq!(team.venue.timezone.resolved, Some(chrono_tz::America::Montreal));
+
+    let timezone = &mut team.venue.timezone;
+    timezone.id = "America/Nowhere".to_string();
+    timezone.offset = -4;
+    assert_eq!(timezone.resolve().unwrap_err().name(), "Etc/GMT+4");
+
+    timezone.offset = 42;
+    assert_eq!(timezone.resolve().unwrap_err().name(), "America/Toronto");
+}
+
+#[test]
+fn test_standings_streak() {
+    let standings = nhlapi::standings::parse(&fixture("standings-2019-03-15")).unwrap();
+    let streak_of = |team_id| standings.iter().find(|r| r.team.id == team_id).unwrap().streak();
+    assert_eq!(streak_of(8), Some("W1".to_string()));
+    assert_eq!(streak_of(12), Some("W4".to_string()));
+    assert_eq!(streak_of(1), Some("L4".to_string()));
+
+    let record = |team_id| standings.iter().find(|r| r.team.id == team_id).unwrap();
+    assert_eq!((record(8).row, record(8).regulation_wins), (33, 30));
+    assert!(record(12).is_hot() && !record(12).is_cold());
+    assert!(record(1).is_cold() && !record(1).is_hot());
+    assert!(!record(8).is_hot() && !record(8).is_cold());
+}
+
+#[test]
+fn test_standings_without_streak() {
+    let mut json: serde_json::Value = serde_json::from_str(&fixture("standings-2019-03-15")).unwrap();
+    for record in json["records"][0]["teamRecords"].as_array_mut().unwrap() {
+        record.as_object_mut().unwrap().remove("streak");
+    }
+    let standings = nhlapi::standings::parse(&json.to_string()).unwrap();
+    assert!(standings.iter().all(|r| r.streak().is_none()));
+}
+
+#[test]
+fn test_standings_empty_ranks() {
+    let mut json: serde_json::Value = serde_json::from_str(&fixture("standings-2019-03-15")).unwrap();
+    let records = json["records"][0]["teamRecords"].as_array_mut().unwrap();
+    records[0]["wildCardRank"] = "".into();
+    records[1]["wildCardRank"] = "-".into();
+    records[2].as_object_mut().unwrap().remove("leagueRank");
+    let standings = nhlapi::standings::parse(&json.to_string()).unwrap();
+    assert_eq!(standings.len(), 31);
+    assert_eq!(standings[0].wildcard_rank, 0);
+    assert_eq!(standings[1].wildcard_rank, 0);
+    assert_eq!(standings[2].league_rank, 0);
+    assert!(standings[3].league_rank > 0);
+}
+
+#[test]
+fn test_standings_without_regulation_wins() {
+    // They are only given since the 2019-20 season.
+    let mut json: serde_json::Value = serde_json::from_str(&fixture("standings-2019-03-15")).unwrap();
+    json["records"][0]["teamRecords"][0]
+        .as_object_mut()
+        .unwrap()
+        .remove("regulationWins");
+    let standings = nhlapi::standings::parse(&json.to_string()).unwrap();
+    assert_eq!(standings[0].regulation_wins, 0);
+}
+
+#[test]
+fn test_current_season() {
+    let season = nhlapi::seasons::parse(&fixture("seasons-current")).unwrap();
+    assert_eq!(season.id.begin, 2018);
+    assert_eq!(season.id.end, 2019);
+    assert_eq!(season.regular_season_end_date, NaiveDate::from_ymd(2019, 4, 6));
+    assert_eq!(season.number_of_games, 82);
+}
+
+#[test]
+fn test_regular_season_clamp() {
+    let mut dates = nhlapi::schedule::parse_range(&fixture("schedule-MTL-2019-03-15-2019-05-01")).unwrap();
+    assert_eq!(dates.len(), 12);
+    dates[1].games[0].game_type = "P".to_string();
+
+    let end = NaiveDate::from_ymd(2019, 3, 28);
+    let dates = nhlapi::schedule::regular_season(dates, &end);
+    assert_eq!(dates.len(), 7);
+    assert!(dates.iter().all(|d| d.date <= end && !d.games.is_empty()));
+    assert!(dates.iter().all(|d| d.date != NaiveDate::from_ymd(2019, 3, 16)));
+}
+
+#[test]
+fn test_standings_wildcard() {
+    let groupings = nhlapi::standings::parse_wildcard(&fixture("standings-wildcard-2019-03-15")).unwrap();
+    assert_eq!(groupings.len(), 6);
+    assert_eq!(groupings.iter().filter(|g| g.is_division_leaders()).count(), 4);
+    assert_eq!(groupings.iter().filter(|g| g.is_wildcard()).count(), 2);
+
+    let leaders = &groupings[0];
+    assert!(leaders.is_division_leaders());
+    assert_eq!(leaders.division.as_ref().unwrap().name, "Pacific");
+    assert_eq!(leaders.team_records.len(), 3);
+
+    let wildcard = groupings.iter().find(|g| g.is_wildcard()).unwrap();
+    assert!(wildcard.division.is_none());
+    assert_eq!(wildcard.conference.as_ref().unwrap().name, "Western");
+    assert_eq!(wildcard.team_records[0].wildcard_rank, 1);
+}
+
+#[test]
+fn test_game_decisions() {
+    let decisions = nhlapi::game::parse_decisions(&fixture("game-2018021041-feed")).unwrap();
+    assert_eq!(decisions.winner.as_ref().unwrap().full_name, "Carey Price");
+    let stars = decisions.three_stars().unwrap();
+    assert_eq!(stars[0].full_name, "Brendan Gallagher");
+    assert_eq!(stars[2].full_name, "Carey Price");
+
+    // Games that are not over have no decisions.
+    let decisions = nhlapi::game::parse_decisions(r#"{"liveData": {}}"#).unwrap();
+    assert!(decisions.winner.is_none());
+    assert!(decisions.three_stars().is_none());
+}
+
+#[test]
+fn test_game_starters() {
+    let starters = nhlapi::game::parse_starters(&fixture("game-2018021048-feed"))
+        .unwrap()
+        .unwrap();
+    assert_eq!(starters.game_pk, 2018021048);
+    assert_eq!(starters.away.full_name, "Carey Price");
+    assert_eq!(starters.home.full_name, "Andrei Vasilevskiy");
+    assert!(!starters.confirmed);
+
+    // Confirmed once the game has started.
+    let mut root: serde_json::Value = serde_json::from_str(&fixture("game-2018021048-feed")).unwrap();
+    root["gameData"]["status"]["abstractGameState"] = "Live".into();
+    let starters = nhlapi::game::parse_starters(&root.to_string()).unwrap().unwrap();
+    assert!(starters.confirmed);
+
+    // Unknown until both teams have published their lineups.
+    root["liveData"]["boxscore"]["teams"]["home"]["goalies"] = serde_json::json!([]);
+    assert!(nhlapi::game::parse_starters(&root.to_string()).unwrap().is_none());
+    let json = r#"{"gamePk": 2018021048, "gameData": {}, "liveData": {}}"#;
+    assert!(nhlapi::game::parse_starters(json).unwrap().is_none());
+}
+
+#[test]
+fn test_team_display() {
+    let teams = nhlapi::teams::parse(&fixture("teams")).unwrap();
+    let mtl = teams.iter().find(|t| t.abbrev == "MTL").unwrap();
+    assert_eq!(mtl.display(NameStyle::Abbrev), "MTL");
+    assert_eq!(mtl.display(NameStyle::Short), "Canadiens");
+    assert_eq!(mtl.display(NameStyle::Full), "Montréal Canadiens");
+    assert_eq!(mtl.display(NameStyle::Flair), "[](/r/habs)MTL");
+}
+
+#[test]
+fn test_team_subreddits() {
+    let teams = nhlapi::teams::parse(&fixture("teams")).unwrap();
+    assert!(teams.iter().all(|t| !t.subreddit.is_empty()));
+    let subreddit_of =
+        |teams: &[nhlapi::teams::Team], abbrev| teams.iter().find(|t| t.abbrev == abbrev).unwrap().subreddit.clone();
+    assert_eq!(subreddit_of(&teams, "ANA"), "anaheimducks");
+    assert_eq!(subreddit_of(&teams, "WPG"), "winnipegjets");
+
+    // A team that no longer exists, as in the teams of a past season, has no
+    // subreddit and leaves the other teams' alone.
+    let mut root: serde_json::Value = serde_json::from_str(&fixture("teams")).unwrap();
+    let mut atlanta = root["teams"][0].clone();
+    atlanta["id"] = 11.into();
+    atlanta["name"] = "Atlanta Thrashers".into();
+    atlanta["abbreviation"] = "ATL".into();
+    root["teams"].as_array_mut().unwrap().push(atlanta);
+    let teams = nhlapi::teams::parse(&root.to_string()).unwrap();
+    assert_eq!(subreddit_of(&teams, "ATL"), "");
+    assert_eq!(subreddit_of(&teams, "ARI"), "coyotes");
+    assert_eq!(subreddit_of(&teams, "MTL"), "habs");
+}
+
+#[test]
+fn test_team_theme() {
+    let teams = nhlapi::teams::parse(&fixture("teams")).unwrap();
+    assert!(teams.iter().all(|t| t.theme().is_some()));
+    let mtl = teams.iter().find(|t| t.abbrev == "MTL").unwrap().theme().unwrap();
+    assert_eq!(mtl.primary_color, "#AF1E2D");
+    assert_eq!(mtl.emoji, "⚜️");
+}
+
+#[test]
+fn test_format_date_range() {
+    let dates = nhlapi::schedule::parse_range(&fixture("schedule-MTL-2019-04-04-2019-04-06")).unwrap();
+    let tz = chrono_tz::America::Montreal;
+    let games = || dates.iter().flat_map(|d| &d.games);
+
+    assert_eq!(
+        nhlapi::schedule::format_date_range(games(), &tz).as_deref(),
+        Some("Thu Apr 4 – Sat Apr 6")
+    );
+    assert_eq!(
+        nhlapi::schedule::format_date_range(&dates[0].games, &tz).as_deref(),
+        Some("Thu Apr 4")
+    );
+    assert_eq!(nhlapi::schedule::format_date_range(&[], &tz), None);
+
+    // The dates are the local dates of the games: 7 PM in Montréal is the
+    // next morning in Tokyo.
+    assert_eq!(
+        nhlapi::schedule::format_date_range(games(), &chrono_tz::Asia::Tokyo).as_deref(),
+        Some("Fri Apr 5 – Sun Apr 7")
+    );
+}
+
+#[test]
+fn test_standings_missing() {
+    let err = nhlapi::standings::parse(r#"{"records": []}"#).unwrap_err();
+    assert!(matches!(err, nhlapi::standings::Error::MissingStandings));
+
+    let err = nhlapi::standings::parse(r#"{"records": "#).unwrap_err();
+    assert!(matches!(err, nhlapi::standings::Error::Json(_)));
+}
+
+#[test]
+fn test_web_teams() {
+    let date = NaiveDate::from_ymd(2019, 3, 15);
+    let teams = nhlapi::web::parse_teams(&fixture("web-standings-2019-03-15"), &fixture("web-teams"), &date).unwrap();
+    let abbrevs: Vec<_> = teams.iter().map(|t| t.abbrev.as_str()).collect();
+    assert_eq!(abbrevs, vec!["MTL", "TBL", "TOR", "VAN"]);
+
+    // The ids, divisions and conferences agree with the old API.
+    let old = nhlapi::teams::parse(&fixture("teams")).unwrap();
+    for team in &teams {
+        let old = old.iter().find(|t| t.abbrev == team.abbrev).unwrap();
+        assert_eq!(team.id, old.id);
+        assert_eq!(team.division.id, old.division.id);
+        assert_eq!(team.conference.id, old.conference.id);
+        assert_eq!(team.subreddit, old.subreddit);
+    }
+
+    let mtl = &teams[0];
+    assert_eq!(mtl.full_name, "Montréal Canadiens");
+    assert_eq!(mtl.name, "Canadiens");
+    assert_eq!(mtl.location, "Montréal");
+    assert_eq!(mtl.timezone_code(), "EDT");
+    assert_eq!(teams[3].timezone_code(), "PDT");
+}
+
+#[test]
+fn test_web_standings() {
+    let date = NaiveDate::from_ymd(2019, 3, 15);
+    let teams = nhlapi::web::parse_teams(&fixture("web-standings-2019-03-15"), &fixture("web-teams"), &date).unwrap();
+    let records = nhlapi::web::parse_standings(&fixture("web-standings-2019-03-15"), &teams).unwrap();
+    assert_eq!(records.len(), 4);
+
+    let mtl = records.iter().find(|r| r.team.id == 8).unwrap();
+    assert_eq!(mtl.format(), "37-26-7");
+    assert_eq!(mtl.points, 81);
+    assert_eq!((mtl.row, mtl.regulation_wins), (33, 30));
+    assert_eq!(mtl.games_played, 70);
+    assert_eq!(mtl.division_rank, 4);
+    assert_eq!(mtl.wildcard_rank, 2);
+    assert_eq!(mtl.last10().as_deref(), Some("6-3-1"));
+    assert_eq!(mtl.streak().as_deref(), Some("W1"));
+
+    let tbl = records.iter().find(|r| r.team.id == 14).unwrap();
+    assert!(tbl.is_hot());
+    let van = records.iter().find(|r| r.team.id == 23).unwrap();
+    assert_eq!(van.streak().as_deref(), Some("OT2"));
+
+    // Teams missing from the teams are left out.
+    let records = nhlapi::web::parse_standings(&fixture("web-standings-2019-03-15"), &teams[..1]).unwrap();
+    assert_eq!(records.len(), 1);
+
+    let err = nhlapi::web::parse_standings(r#"{"standings": []}"#, &teams).unwrap_err();
+    assert!(matches!(err, nhlapi::standings::Error::MissingStandings));
+}
+
+#[test]
+fn test_web_schedule() {
+    let date = NaiveDate::from_ymd(2019, 3, 15);
+    let schedule = nhlapi::web::parse_schedule(&fixture("web-schedule-2019-03-15"), &date).unwrap();
+    assert_eq!(schedule.date, date);
+    assert_eq!(schedule.games.len(), 3);
+
+    let future = &schedule.games[0];
+    assert_eq!(future.away_team().id, 8);
+    assert_eq!(future.home_team().id, 14);
+    assert_eq!(future.game_type, "R");
+    assert!(!future.is_live() && !future.is_final());
+    assert!(!future.linescore.has_periods());
+
+    let live = &schedule.games[1];
+    assert!(live.is_live());
+    assert_eq!(live.linescore.current_period, 2);
+    assert!(live.linescore.intermission_info.in_intermission);
+
+    let shootout = &schedule.games[2];
+    assert!(shootout.is_final());
+    assert!(shootout.overtime() && shootout.shootout());
+    assert_eq!(shootout.winner().id, 3);
+
+    // The other days of the week are left out.
+    let schedule =
+        nhlapi::web::parse_schedule(&fixture("web-schedule-2019-03-15"), &(date + Duration::days(1))).unwrap();
+    assert!(schedule.games.is_empty());
+}
+
+#[test]
+fn test_web_range() {
+    let begin = NaiveDate::from_ymd(2019, 3, 14);
+    let end = NaiveDate::from_ymd(2019, 4, 11);
+    let dates = nhlapi::web::parse_range(&fixture("web-club-schedule-MTL-20182019"), &begin, &end).unwrap();
+    let days: Vec<_> = dates.iter().map(|d| d.date.format("%m-%d").to_string()).collect();
+    assert_eq!(days, vec!["03-14", "03-15", "03-16", "04-11"]);
+
+    let overtime = &dates[0].games[0];
+    assert!(overtime.is_final());
+    assert!(overtime.overtime() && !overtime.shootout());
+    assert_eq!(overtime.winner().id, 8);
+    assert_eq!(overtime.season, nhlapi::Season { begin: 2018, end: 2019 });
+
+    let playoffs = &dates[3].games[0];
+    assert!(playoffs.is_playoffs());
+    let series = playoffs.series_summary.as_ref().unwrap();
+    assert_eq!(series.game_number, 2);
+    assert_eq!(series.series_status_short, "MTL leads 1-0");
+}
+
+#[test]
+fn test_backend_names() {
+    let backend: nhlapi::Backend = serde_json::from_str("\"web\"").unwrap();
+    assert_eq!(backend, nhlapi::Backend::Web);
+    assert_eq!(
+        serde_json::to_string(&nhlapi::Backend::StatsApi).unwrap(),
+        "\"statsapi\""
+    );
+    assert_eq!(nhlapi::Backend::default(), nhlapi::Backend::StatsApi);
+}
@@ -0,0 +1,1968 @@
+mod common;
+
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+
+use playoffsbot::analysis::{self, Analyzer, Api, Matchup, Mood, PlayoffFormat, PlayoffPosition};
+use playoffsbot::generate::{self, MarkdownGenerator};
+use playoffsbot::nhlapi;
+use playoffsbot::simulation;
+
+use common::{fixture, load_api, load_schedule, seeding_options, test_options, test_params};
+
+#[test]
+fn test_fixtures_parse() {
+    let api = load_api();
+    assert_eq!(api.teams.len(), 31);
+    assert_eq!(api.standings.len(), 31);
+    assert_eq!(api.get_team_by_abbrev("mtl").subreddit, "habs");
+    assert_eq!(api.results.as_ref().unwrap().games.len(), 7);
+    assert_eq!(api.games.games.len(), 6);
+}
+
+#[test]
+fn test_analysis() {
+    let api = load_api();
+    let team = api.get_team_by_abbrev("MTL");
+    let analyzer = Analyzer::new(&api, team).with_options(test_options());
+    let an = analyzer.perform();
+
+    assert!(an.my_result.is_some());
+    assert!(an.my_game.is_some());
+    assert_eq!(an.own_division_seed.len(), 3);
+    assert_eq!(an.other_division_seeds.len(), 1);
+    assert_eq!(an.other_division_seeds[0].len(), 3);
+    assert_eq!(an.wildcard_seed.len(), 10);
+    assert_eq!(an.wildcard_seed[1].record.team.id, team.id);
+    assert_eq!(an.playoffs.len(), 4);
+}
+
+#[test]
+fn test_games_in_hand() {
+    let mut api = load_api();
+    let schedule = load_schedule();
+    {
+        let team = api.get_team_by_abbrev("MTL");
+        let analyzer = Analyzer::new(&api, team).with_options(seeding_options());
+        let an = analyzer.perform();
+
+        // CAR is right ahead of MTL and CBJ right behind, both with a game in hand.
+        let games_in_hand = an.games_in_hand(team.id);
+        assert_eq!(games_in_hand.len(), 2);
+        assert_eq!(games_in_hand[0].rival.team.id, api.get_team_by_abbrev("CAR").id);
+        assert_eq!(games_in_hand[0].games, -1);
+        assert_eq!(games_in_hand[1].rival.team.id, api.get_team_by_abbrev("CBJ").id);
+        assert_eq!(games_in_hand[1].games, -1);
+
+        let doc = MarkdownGenerator::new(&api, &an, &schedule, team).markdown().unwrap();
+        assert!(doc
+            .as_str()
+            .contains("CAR has 1 game in hand on you. CBJ has 1 game in hand on you."));
+        assert_eq!(doc.validate(), vec![]);
+    }
+
+    let mtl = api.get_team_by_abbrev("MTL").id;
+    let games_played = api.standings.iter().find(|r| r.team.id == mtl).unwrap().games_played;
+    for (abbrev, delta) in &[("CAR", 2), ("CBJ", 0)] {
+        let id = api.get_team_by_abbrev(abbrev).id;
+        let record = api.standings.iter_mut().find(|r| r.team.id == id).unwrap();
+        record.games_played = games_played + delta;
+    }
+    let team = api.get_team_by_abbrev("MTL");
+    let analyzer = Analyzer::new(&api, team).with_options(seeding_options());
+    let an = analyzer.perform();
+    let doc = MarkdownGenerator::new(&api, &an, &schedule, team).markdown().unwrap();
+    assert!(doc
+        .as_str()
+        .contains("You have 2 games in hand on CAR. No games in hand on CBJ."));
+}
+
+#[test]
+fn test_analysis_rivals() {
+    let mut api = load_api();
+    let team = api.get_team_by_abbrev("MTL");
+    let (van, col) = (api.get_team_by_abbrev("VAN").id, api.get_team_by_abbrev("COL").id);
+    let is_shown = |an: &analysis::Analysis, id| {
+        an.games
+            .iter()
+            .any(|m| m.game.home_team().id == id || m.game.away_team().id == id)
+    };
+
+    let analyzer = Analyzer::new(&api, team).with_options(seeding_options());
+    assert!(!is_shown(&analyzer.perform(), van));
+
+    // Rivals are shown across the conference, and we cheer against them.
+    let analyzer = Analyzer::new(&api, team).with_options(analysis::Options {
+        rivals: vec![van].into_iter().collect(),
+        ..seeding_options()
+    });
+    let an = analyzer.perform();
+    let game = an.games.iter().find(|m| m.game.away_team().id == van).unwrap();
+    assert_eq!(game.cheer_for().id, col);
+
+    // NJD is eliminated and TOR has clinched, so their game changes nothing.
+    let (tor, njd) = (api.get_team_by_abbrev("TOR").id, api.get_team_by_abbrev("NJD").id);
+    for record in api.standings.iter_mut() {
+        if record.team.id == njd {
+            record.games_played = 80;
+            record.points = 60;
+        } else if record.team.id == tor {
+            record.points = 140;
+        }
+    }
+    let team = api.get_team_by_abbrev("MTL");
+    let analyzer = Analyzer::new(&api, team).with_options(seeding_options());
+    assert!(is_shown(&analyzer.perform(), njd));
+    let analyzer = Analyzer::new(&api, team).with_options(analysis::Options {
+        relevant_only: true,
+        ..seeding_options()
+    });
+    let an = analyzer.perform();
+    assert!(!is_shown(&an, njd));
+    assert!(is_shown(&an, api.get_team_by_abbrev("CBJ").id));
+}
+
+#[test]
+fn test_relevance_points() {
+    let mut api = load_api();
+    let (tor, njd) = (api.get_team_by_abbrev("TOR").id, api.get_team_by_abbrev("NJD").id);
+    let is_shown = |an: &analysis::Analysis, id| {
+        an.games
+            .iter()
+            .any(|m| m.game.home_team().id == id || m.game.away_team().id == id)
+    };
+
+    // TOR runs away with the conference and NJD is far behind.
+    for record in api.standings.iter_mut() {
+        if record.team.id == tor {
+            record.points = 140;
+        } else if record.team.id == njd {
+            record.points = 50;
+        }
+    }
+    let team = api.get_team_by_abbrev("MTL");
+    assert!(is_shown(
+        &Analyzer::new(&api, team).with_options(seeding_options()).perform(),
+        tor
+    ));
+
+    let options = analysis::Options {
+        relevance_points: Some(6),
+        ..seeding_options()
+    };
+    let analyzer = Analyzer::new(&api, team).with_options(options.clone());
+    let an = analyzer.perform();
+    assert!(!is_shown(&an, tor));
+    let my_points = api.get_points(team.id, false).unwrap() as i32;
+    for m in &an.games {
+        let teams = [m.game.home_team().id, m.game.away_team().id];
+        assert!(teams.iter().any(|&id| {
+            api.get_team_by_id(id).conference.id == team.conference.id
+                && (api.get_points(id, false).unwrap() as i32 - my_points).abs() <= 6
+        }));
+    }
+
+    // Unless it is a rival.
+    let analyzer = Analyzer::new(&api, team).with_options(analysis::Options {
+        rivals: vec![tor].into_iter().collect(),
+        ..options
+    });
+    assert!(is_shown(&analyzer.perform(), tor));
+}
+
+#[test]
+fn test_summary_line() {
+    let api = load_api();
+    let schedule = load_schedule();
+    let team = api.get_team_by_abbrev("MTL");
+    let analyzer = Analyzer::new(&api, team).with_options(test_options());
+    let an = analyzer.perform();
+    let points = |abbrev| {
+        let id = api.get_team_by_abbrev(abbrev).id;
+        api.standings.iter().find(|r| r.team.id == id).unwrap().points as i32
+    };
+
+    // MTL holds the last wildcard, ahead of CBJ.
+    let line = points("MTL") - points("CBJ");
+    assert_eq!(an.points_vs_line(team.id), Some(line));
+    assert_eq!(an.points_vs_line(api.get_team_by_abbrev("CBJ").id), Some(-line));
+    assert_eq!(
+        an.points_vs_line(api.get_team_by_abbrev("TBL").id),
+        Some(points("TBL") - points("CBJ"))
+    );
+    assert_eq!(an.points_vs_line(api.get_team_by_abbrev("CGY").id), None);
+
+    let summary = MarkdownGenerator::new(&api, &an, &schedule, team).make_summary_line();
+    assert!(summary.starts_with("MTL: "));
+    assert!(summary.ends_with(&format!("% to make playoffs ({:+} on the line, 12 GP left)", line)));
+}
+
+#[test]
+fn test_analysis_official_wildcard() {
+    let wildcard = nhlapi::standings::parse_wildcard(&fixture("standings-wildcard-2019-03-15")).unwrap();
+    let api = load_api().with_wildcard(Some(wildcard.clone()));
+    let team = api.get_team_by_abbrev("MTL");
+    let analyzer = Analyzer::new(&api, team).with_options(seeding_options());
+    let an = analyzer.perform();
+
+    // The official groupings agree with the seeding computed from the standings.
+    assert_eq!(an.own_division_seed[0].record.team.id, api.get_team_by_abbrev("TBL").id);
+    assert_eq!(
+        an.other_division_seeds[0][0].record.team.id,
+        api.get_team_by_abbrev("WSH").id
+    );
+    assert_eq!(an.wildcard_seed.len(), 10);
+    assert_eq!(an.wildcard_seed[1].record.team.id, team.id);
+
+    // When they disagree, the official groupings win.
+    let mut wildcard = wildcard;
+    let eastern = wildcard
+        .iter_mut()
+        .find(|g| g.is_wildcard() && g.conference.as_ref().unwrap().name == "Eastern")
+        .unwrap();
+    eastern.team_records.swap(0, 1);
+    let api = load_api().with_wildcard(Some(wildcard));
+    let team = api.get_team_by_abbrev("MTL");
+    let analyzer = Analyzer::new(&api, team).with_options(seeding_options());
+    let an = analyzer.perform();
+    assert_eq!(an.wildcard_seed[0].record.team.id, team.id);
+    assert_eq!(an.wildcard_seed[0].seed, 1);
+}
+
+#[test]
+fn test_sort_basis() {
+    // CBJ is 3 points back of CAR with 3 games in hand, which puts it ahead
+    // on points percentage.
+    let mut api = load_api();
+    let cbj = api.get_team_by_abbrev("CBJ").id;
+    api.standings
+        .iter_mut()
+        .find(|r| r.team.id == cbj)
+        .unwrap()
+        .games_played = 66;
+    let wildcards = |api: &Api, sort_basis| {
+        let team = api.get_team_by_abbrev("MTL");
+        let options = analysis::Options {
+            params: simulation::Params {
+                sort_basis,
+                ..test_params()
+            },
+            ..seeding_options()
+        };
+        let analyzer = Analyzer::new(api, team).with_options(options);
+        let an = analyzer.perform();
+        an.wildcard_seed
+            .iter()
+            .take(3)
+            .map(|s| api.get_team_by_id(s.record.team.id).abbrev.clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        wildcards(&api, simulation::SortBasis::Points),
+        vec!["CAR", "MTL", "CBJ"]
+    );
+    assert_eq!(
+        wildcards(&api, simulation::SortBasis::PointsPercentage),
+        vec!["CBJ", "CAR", "MTL"]
+    );
+
+    // The official groupings are ranked by points, so they are left out.
+    let wildcard = nhlapi::standings::parse_wildcard(&fixture("standings-wildcard-2019-03-15")).unwrap();
+    let api = api.with_wildcard(Some(wildcard));
+    assert_eq!(
+        wildcards(&api, simulation::SortBasis::PointsPercentage),
+        vec!["CBJ", "CAR", "MTL"]
+    );
+}
+
+#[test]
+fn test_tiebreaker() {
+    // MTL catches CAR at 82 points, with fewer regulation and overtime wins
+    // but more regulation wins.
+    let mut api = load_api();
+    let mtl = api.get_team_by_abbrev("MTL").id;
+    let record = api.standings.iter_mut().find(|r| r.team.id == mtl).unwrap();
+    record.points = 82;
+    record.regulation_wins = 34;
+    let post = |api: &Api, tiebreaker| {
+        let team = api.get_team_by_abbrev("MTL");
+        let options = analysis::Options {
+            params: simulation::Params {
+                tiebreaker,
+                ..test_params()
+            },
+            ..seeding_options()
+        };
+        let analyzer = Analyzer::new(api, team).with_options(options);
+        let an = analyzer.perform();
+        let wildcards: Vec<_> = an
+            .wildcard_seed
+            .iter()
+            .take(2)
+            .map(|s| api.get_team_by_id(s.record.team.id).abbrev.clone())
+            .collect();
+        let doc = MarkdownGenerator::new(api, &an, &[], team)
+            .with_options(generate::Options {
+                sections: vec![generate::PostSection::Standings],
+                ..Default::default()
+            })
+            .markdown()
+            .unwrap();
+        (wildcards, doc.as_str().to_string())
+    };
+
+    // ROW was the official tiebreaker in 2018-19.
+    for tiebreaker in &[None, Some(simulation::Tiebreaker::Row)] {
+        let (wildcards, md) = post(&api, *tiebreaker);
+        assert_eq!(wildcards, vec!["CAR", "MTL"]);
+        assert!(md.contains("|Points|ROW|L10|"));
+        assert!(md.contains("|**82**|**33**|"));
+    }
+    let (wildcards, md) = post(&api, Some(simulation::Tiebreaker::Rw));
+    assert_eq!(wildcards, vec!["MTL", "CAR"]);
+    assert!(md.contains("|Points|RW|L10|"));
+    assert!(md.contains("|**82**|**34**|"));
+}
+
+#[test]
+fn test_playoff_format() {
+    let api = load_api();
+    let format = PlayoffFormat::of_conference(&api.teams, api.get_team_by_abbrev("MTL").conference.id);
+    assert_eq!(format, PlayoffFormat::with_divisions(2));
+    assert_eq!((format.division_spots, format.wildcard_spots), (3, 2));
+
+    let format = PlayoffFormat::with_divisions(4);
+    assert_eq!((format.division_spots, format.wildcard_spots), (1, 4));
+}
+
+/// The league with each division of the Eastern conference split in two.
+fn load_four_division_api() -> Api {
+    let mut api = load_api();
+    let conference_id = api.get_team_by_abbrev("MTL").conference.id;
+    let mut seen = vec![];
+    for team in api.teams.iter_mut().filter(|t| t.conference.id == conference_id) {
+        let count = seen.iter().filter(|&&id| id == team.division.id).count();
+        seen.push(team.division.id);
+        if count % 2 == 1 {
+            team.division.id += 1000;
+        }
+    }
+    assert_eq!(
+        PlayoffFormat::of_conference(&api.teams, conference_id),
+        PlayoffFormat::with_divisions(4)
+    );
+    api
+}
+
+#[test]
+fn test_analysis_four_divisions() {
+    let api = load_four_division_api();
+    let team = api.get_team_by_abbrev("MTL");
+    let analyzer = Analyzer::new(&api, team).with_options(seeding_options());
+    let an = analyzer.perform();
+
+    assert_eq!(an.own_division_seed.len(), 1);
+    assert_eq!(an.other_division_seeds.len(), 3);
+    assert!(an.other_division_seeds.iter().all(|seeds| seeds.len() == 1));
+    assert_eq!(an.wildcard_spots, 4);
+    assert_eq!(an.wildcard_seed.len(), 16 - 4);
+    assert!(an
+        .own_division_seed
+        .iter()
+        .all(|s| api.get_team_by_id(s.record.team.id).division.id == team.division.id));
+
+    // Every qualifier plays once in the first round.
+    assert_eq!(an.playoffs.len(), 4);
+    let mut qualifiers: Vec<_> = an
+        .playoffs
+        .iter()
+        .flat_map(|m| vec![m.high_team.team.id, m.low_team.team.id])
+        .collect();
+    qualifiers.sort();
+    qualifiers.dedup();
+    assert_eq!(qualifiers.len(), 8);
+}
+
+#[test]
+fn test_odds_four_divisions() {
+    let api = load_four_division_api();
+    let team = api.get_team_by_abbrev("MTL");
+    let params = simulation::Params {
+        odds_mode: simulation::OddsMode::Simulation,
+        seed: Some(2019),
+        ..test_params()
+    };
+
+    // The leader of every division makes the playoffs.
+    let times = 2000;
+    let distribution = simulation::Simulation::today(&api, team, &params).run_distribution(times);
+    assert_eq!(distribution.values().sum::<u32>(), 8 * times);
+    let mut by_division = BTreeMap::new();
+    for (team_id, count) in distribution {
+        *by_division.entry(api.get_team_by_id(team_id).division.id).or_insert(0) += count;
+    }
+    assert_eq!(by_division.len(), 4);
+    assert!(by_division.values().all(|&count| count >= times), "{:?}", by_division);
+
+    // Seeded alike, every season the team makes the playoffs gives it a first
+    // round opponent.
+    let opponents: f64 = simulation::first_round_opponents(&api, team, &params)
+        .iter()
+        .map(|(_, odds)| odds)
+        .sum();
+    let odds = simulation::odds_for_team(&api, team, false, &params).unwrap();
+    assert!(odds > 0.0);
+    assert!((opponents - odds).abs() < 1e-9, "{} vs {}", opponents, odds);
+}
+
+#[test]
+fn test_playoff_position() {
+    let api = load_api();
+    let team = api.get_team_by_abbrev("MTL");
+    let analyzer = Analyzer::new(&api, team).with_options(seeding_options());
+    let an = analyzer.perform();
+    let position = |abbrev, hunt_points| an.playoff_position(api.get_team_by_abbrev(abbrev).id, hunt_points);
+
+    assert_eq!(position("TBL", 6), Some(PlayoffPosition::DivisionTop3));
+    assert_eq!(position("PIT", 6), Some(PlayoffPosition::DivisionTop3));
+    assert_eq!(position("MTL", 6), Some(PlayoffPosition::Wildcard));
+    assert_eq!(position("CAR", 6), Some(PlayoffPosition::Wildcard));
+    assert_eq!(position("CBJ", 6), Some(PlayoffPosition::InHunt));
+    // PHI is 9 points behind MTL.
+    assert_eq!(position("PHI", 6), Some(PlayoffPosition::OutOfRace));
+    assert_eq!(position("PHI", 9), Some(PlayoffPosition::InHunt));
+    assert_eq!(position("CGY", 6), None);
+}
+
+#[test]
+fn test_analysis_other_conference() {
+    let api = load_api();
+    let team = api.get_team_by_abbrev("MTL");
+    let analyzer = Analyzer::new(&api, team).with_options(seeding_options());
+    assert!(analyzer.perform().other_conference.is_none());
+
+    let analyzer = analyzer.with_options(analysis::Options {
+        other_conference: true,
+        ..seeding_options()
+    });
+    let an = analyzer.perform();
+    let other = an.other_conference.as_ref().unwrap();
+    assert_eq!(other.conference.name, "Western");
+    assert_eq!(
+        other.division_seeds[0][0].record.team.id,
+        api.get_team_by_abbrev("CGY").id
+    );
+    assert_eq!(
+        other.division_seeds[1][0].record.team.id,
+        api.get_team_by_abbrev("WPG").id
+    );
+    assert_eq!(other.wildcard_seed.len(), 9);
+    assert_eq!(other.playoffs.len(), 4);
+    // The own conference seeding is unaffected.
+    assert_eq!(an.wildcard_seed[1].record.team.id, team.id);
+}
+
+#[test]
+fn test_markdown_both_conferences() {
+    let api = load_api();
+    let schedule = load_schedule();
+    let team = api.get_team_by_abbrev("MTL");
+    let analyzer = Analyzer::new(&api, team).with_options(analysis::Options {
+        other_conference: true,
+        ..seeding_options()
+    });
+    let an = analyzer.perform();
+
+    // By default the other conference is only an appendix.
+    let doc = MarkdownGenerator::new(&api, &an, &schedule, team).markdown().unwrap();
+    let doc = doc.as_str();
+    assert!(doc.contains("## Standings\n"));
+    assert!(doc.contains("## Appendix: Western Conference\n"));
+
+    let options = generate::Options {
+        both_conferences: true,
+        ..Default::default()
+    };
+    let doc = MarkdownGenerator::new(&api, &an, &schedule, team)
+        .with_options(options)
+        .markdown()
+        .unwrap();
+    let doc = doc.as_str();
+    assert!(!doc.contains("## Standings\n"));
+    assert!(!doc.contains("## Playoffs matchups\n"));
+    assert!(!doc.contains("Appendix: Western Conference"));
+    let eastern = doc.find("## Eastern Conference\n").unwrap();
+    let western = doc.find("## Western Conference\n").unwrap();
+    let tonight = doc.find("## Tonight's race\n").unwrap();
+    assert!(eastern < western && western < tonight);
+    assert!(doc[western..tonight].contains("CGY"));
+}
+
+#[test]
+fn test_markdown_sections() {
+    use generate::PostSection;
+
+    let api = load_api();
+    let schedule = load_schedule();
+    let team = api.get_team_by_abbrev("MTL");
+    let analyzer = Analyzer::new(&api, team).with_options(analysis::Options {
+        other_conference: true,
+        ..test_options()
+    });
+    let an = analyzer.perform();
+    let markdown = |options| {
+        MarkdownGenerator::new(&api, &an, &schedule, team)
+            .with_options(options)
+            .markdown()
+            .unwrap()
+            .as_str()
+            .to_string()
+    };
+
+    let md = markdown(generate::Options {
+        sections: vec![PostSection::Schedule, PostSection::Standings, PostSection::Disclaimer],
+        ..Default::default()
+    });
+    let schedule_at = md.find("## Upcoming schedule\nFri Mar 15 – Tue Apr 2\n").unwrap();
+    let standings_at = md.find("## Standings\n").unwrap();
+    assert!(md.starts_with("# Playoffs race!\n"));
+    assert!(schedule_at < standings_at);
+    assert!(md.contains("### Disclaimer"));
+    for missing in &[
+        "Playoffs odds today",
+        "## Last night's race",
+        "## Playoffs matchups",
+        "## Tonight's race",
+    ] {
+        assert!(!md.contains(missing), "unexpected {:?}", missing);
+    }
+    // The other conference was seeded, but its section is not listed.
+    assert!(!md.contains("Western Conference"));
+
+    // Both conferences are shown together, even without the bracket.
+    let md = markdown(generate::Options {
+        sections: vec![
+            PostSection::Standings,
+            PostSection::Schedule,
+            PostSection::OtherConference,
+        ],
+        both_conferences: true,
+        ..Default::default()
+    });
+    let western = md.find("## Western Conference\n").unwrap();
+    assert!(md.find("## Eastern Conference\n").unwrap() < western);
+    assert!(western < md.find("## Upcoming schedule\n").unwrap());
+}
+
+#[test]
+fn test_team_render_style() {
+    use generate::{PostSection, TeamRenderStyle};
+
+    let api = load_api();
+    let team = api.get_team_by_abbrev("MTL");
+    assert_eq!(TeamRenderStyle::RedditFlair.render(team), "[](/r/habs)MTL");
+    assert_eq!(TeamRenderStyle::PlainAbbrev.render(team), "MTL");
+    assert_eq!(TeamRenderStyle::FullName.render(team), "Montréal Canadiens");
+    assert_eq!(
+        TeamRenderStyle::MarkdownLink.render(team),
+        "[MTL](https://www.reddit.com/r/habs)"
+    );
+    let mut defunct = team.clone();
+    defunct.subreddit = String::new();
+    assert_eq!(TeamRenderStyle::MarkdownLink.render(&defunct), "MTL");
+
+    let analyzer = Analyzer::new(&api, team).with_options(seeding_options());
+    let an = analyzer.perform();
+    let markdown = |team_style| {
+        let options = generate::Options {
+            sections: vec![PostSection::Standings],
+            team_style,
+            ..Default::default()
+        };
+        let gen = MarkdownGenerator::new(&api, &an, &[], team).with_options(options);
+        gen.markdown().unwrap().as_str().to_string()
+    };
+    assert!(markdown(TeamRenderStyle::RedditFlair).contains("[](/r/habs)MTL"));
+    let md = markdown(TeamRenderStyle::PlainAbbrev);
+    assert!(!md.contains("](/r/"));
+    assert!(md.contains("|TOR|"));
+    let md = markdown(TeamRenderStyle::MarkdownLink);
+    assert!(!md.contains("[](/r/"));
+    assert!(md.contains("[TOR](https://www.reddit.com/r/leafs)"));
+}
+
+#[test]
+fn test_win_loss_odds() {
+    use generate::PostSection;
+
+    let api = load_api();
+    let options = analysis::Options {
+        win_loss_odds: true,
+        ..test_options()
+    };
+    let tonight = |an: &analysis::Analysis, team| {
+        let gen = MarkdownGenerator::new(&api, an, &[], team).with_options(generate::Options {
+            sections: vec![PostSection::Tonight],
+            ..Default::default()
+        });
+        gen.markdown().unwrap().as_str().to_string()
+    };
+
+    let team = api.get_team_by_abbrev("MTL");
+    let analyzer = Analyzer::new(&api, team).with_options(options.clone());
+    let an = analyzer.perform();
+    let (win, loss) = an.my_game.as_ref().unwrap().win_loss_odds.unwrap();
+    assert!(win > loss);
+    assert!(win <= 1.0 && loss >= 0.0);
+    assert!(an.games.iter().all(|m| m.win_loss_odds.is_none()));
+    assert!(tonight(&an, team).contains(&format!(
+        "Playoffs odds after tonight's game: **Win: {:.1}% / Loss: {:.1}%**\n\n",
+        win * 100.0,
+        loss * 100.0
+    )));
+
+    // Nothing is simulated without the option, nor without a game tonight.
+    let analyzer = Analyzer::new(&api, team).with_options(test_options());
+    let an = analyzer.perform();
+    assert!(an.my_game.as_ref().unwrap().win_loss_odds.is_none());
+    assert!(!tonight(&an, team).contains("Playoffs odds after tonight's game"));
+    let team = api.get_team_by_abbrev("BOS");
+    let analyzer = Analyzer::new(&api, team).with_options(options);
+    let an = analyzer.perform();
+    assert!(an.my_game.is_none());
+    assert!(!tonight(&an, team).contains("Playoffs odds after tonight's game"));
+}
+
+#[test]
+fn test_must_win_games() {
+    let api = load_api();
+    let schedule = load_schedule();
+    let team = api.get_team_by_abbrev("MTL");
+    let options = generate::Options {
+        sections: vec![generate::PostSection::Schedule],
+        target_odds: None,
+        ..Default::default()
+    };
+
+    let analyzer = Analyzer::new(&api, team)
+        .with_options(analysis::Options {
+            must_win_swing: Some(0.01),
+            ..test_options()
+        })
+        .with_schedule(&schedule);
+    let an = analyzer.perform();
+    // Every game matters for MTL in the thick of the race.
+    assert!(!an.must_win.is_empty());
+    assert!(an.must_win.values().all(|&swing| swing >= 0.01));
+    let doc = MarkdownGenerator::new(&api, &an, &schedule, team)
+        .with_options(options)
+        .markdown()
+        .unwrap();
+    assert!(doc.as_str().contains("|⭐ "));
+    assert!(doc
+        .as_str()
+        .contains("⭐ Must-win games: a win instead of a loss is worth up to +"));
+
+    // Nothing is simulated without a threshold.
+    let analyzer = Analyzer::new(&api, team)
+        .with_options(seeding_options())
+        .with_schedule(&schedule);
+    assert!(analyzer.perform().must_win.is_empty());
+}
+
+#[test]
+fn test_points_from_spots() {
+    use analysis::PlayoffSpot::{Division, Wildcard};
+
+    let api = load_api();
+    let schedule = load_schedule();
+    let points_from_spots = |abbrev| {
+        let analyzer = Analyzer::new(&api, api.get_team_by_abbrev(abbrev)).with_options(seeding_options());
+        analyzer.perform().points_from_spots.into_iter().collect::<Vec<_>>()
+    };
+
+    // MTL holds the last wildcard by 2 points over CBJ.
+    assert_eq!(
+        points_from_spots("MTL"),
+        vec![
+            (Division(1), 27),
+            (Division(2), 13),
+            (Division(3), 8),
+            (Wildcard(1), 1),
+            (Wildcard(2), -2)
+        ]
+    );
+    // BOS holds 2nd in the division by 5 points over TOR.
+    assert_eq!(points_from_spots("BOS")[1], (Division(2), -5));
+
+    let team = api.get_team_by_abbrev("MTL");
+    let analyzer = Analyzer::new(&api, team).with_options(seeding_options());
+    let an = analyzer.perform();
+    let doc = MarkdownGenerator::new(&api, &an, &schedule, team)
+        .with_options(generate::Options {
+            points_from_spots: true,
+            sections: vec![generate::PostSection::Standings],
+            ..Default::default()
+        })
+        .markdown()
+        .unwrap();
+    assert!(doc.as_str().contains(
+        "1st in division: +27; 2nd in division: +13; 3rd in division: +8; 1st WC: +1; 2nd WC: -2 (you hold it)."
+    ));
+}
+
+#[test]
+fn test_matchups_order() {
+    use rand::seq::SliceRandom;
+
+    let mut api = load_api();
+    let mut rng = rand::thread_rng();
+    api.games.games.shuffle(&mut rng);
+    api.results.as_mut().unwrap().games.shuffle(&mut rng);
+
+    let team = api.get_team_by_abbrev("MTL");
+    let analyzer = Analyzer::new(&api, team).with_options(test_options());
+    let an = analyzer.perform();
+    for matchups in &[&an.games, &an.results] {
+        assert!(matchups.len() > 1);
+        let keys: Vec<_> = matchups
+            .iter()
+            .map(|m| (m.game.game_date, &api.get_team_by_id(m.game.home_team().id).abbrev))
+            .collect();
+        let mut sorted = keys.clone();
+        sorted.sort();
+        assert_eq!(keys, sorted);
+    }
+}
+
+#[test]
+fn test_markdown_scoreboard() {
+    let api = load_api();
+    let schedule = load_schedule();
+    let team = api.get_team_by_abbrev("MTL");
+    let analyzer = Analyzer::new(&api, team).with_options(test_options());
+    let an = analyzer.perform();
+    let doc = MarkdownGenerator::new(&api, &an, &schedule, team)
+        .with_options(generate::Options {
+            sections: vec![generate::PostSection::Scoreboard],
+            ..Default::default()
+        })
+        .markdown()
+        .unwrap();
+    let md = doc.as_str();
+    assert!(md.contains("## Last night's scoreboard\n"));
+    assert!(!md.contains("Outside of town"));
+    // The team's own game is in bold, and every game is in the one table.
+    assert!(md.contains("|**2-4 [](/r/habs)MTL**|**Great**\n"));
+    assert!(md.contains("|2-3 [](/r/bluejackets)CBJ (OT)|Bad\n"));
+    assert_eq!(md.matches("Game|Score|Result").count(), 1);
+}
+
+#[test]
+fn test_markdown_schedule_unavailable() {
+    let api = load_api();
+    let team = api.get_team_by_abbrev("MTL");
+    let analyzer = Analyzer::new(&api, team).with_options(test_options());
+    let an = analyzer.perform();
+    let doc = MarkdownGenerator::new(&api, &an, &[], team)
+        .with_options(generate::Options {
+            sections: vec![generate::PostSection::Schedule],
+            target_odds: None,
+            ..Default::default()
+        })
+        .with_schedule_available(false)
+        .markdown()
+        .unwrap();
+    assert!(doc
+        .as_str()
+        .contains("## Upcoming schedule\nThe upcoming schedule is not available.\n"));
+    assert!(!doc.as_str().contains("|Away|"));
+}
+
+#[test]
+fn test_markdown_empty_tables() {
+    // A quiet day: no other games last night nor tonight, and the season is
+    // over for the team.
+    let api = load_api();
+    let team = api.get_team_by_abbrev("MTL");
+    let analyzer = Analyzer::new(&api, team).with_options(test_options());
+    let mut an = analyzer.perform();
+    an.results.clear();
+    an.games.clear();
+    let doc = MarkdownGenerator::new(&api, &an, &[], team)
+        .with_options(generate::Options {
+            sections: vec![
+                generate::PostSection::LastNight,
+                generate::PostSection::Tonight,
+                generate::PostSection::Schedule,
+            ],
+            target_odds: None,
+            ..Default::default()
+        })
+        .markdown()
+        .unwrap();
+    let md = doc.as_str();
+    assert_eq!(md.matches("* Outside of town\n\nNothing\n").count(), 2, "{}", md);
+    assert!(md.contains("## Upcoming schedule\nNothing\n"), "{}", md);
+    assert!(!md.contains("|Away|"));
+    assert_eq!(doc.validate(), vec![]);
+}
+
+#[test]
+fn test_record_for() {
+    let mut api = load_api();
+    let team = api.get_team_by_abbrev("MTL");
+    let record = api.record_for(team.id, false).unwrap();
+    assert_eq!((record.points, record.row, record.games_played), (81, 33, 70));
+    assert_eq!(api.get_points(team.id, false), Some(81));
+    assert!(api.record_for(team.id, true).unwrap().games_played < 70);
+
+    // Unknown teams, and yesterday's standings when they are missing.
+    assert!(api.record_for(9999, false).is_none());
+    assert_eq!(api.get_points(9999, false), None);
+    api.past_standings = None;
+    assert!(api.record_for(api.get_team_by_abbrev("MTL").id, true).is_none());
+}
+
+#[test]
+fn test_markdown_without_yesterday() {
+    let mut api = load_api();
+    api.past_standings = None;
+    api.results = None;
+    let schedule = load_schedule();
+    let team = api.get_team_by_abbrev("MTL");
+    assert_eq!(api.get_points(team.id, true), None);
+    assert_eq!(simulation::odds_for_team(&api, team, true, &test_params()), None);
+
+    let analyzer = Analyzer::new(&api, team).with_options(test_options());
+    let an = analyzer.perform();
+    assert!(an.my_result.is_none() && an.results.is_empty());
+    let doc = MarkdownGenerator::new(&api, &an, &schedule, team).markdown().unwrap();
+    let md = doc.as_str();
+    assert!(md.contains("## Last night's race\nLast night's results are not available."));
+    assert!(md.contains("Playoffs odds today: "));
+    assert!(!md.contains("since yesterday"));
+    assert!(!md.contains("Game|Score|Result"));
+}
+
+#[test]
+fn test_markdown() {
+    let api = load_api();
+    let schedule = load_schedule();
+    let team = api.get_team_by_abbrev("MTL");
+    let analyzer = Analyzer::new(&api, team).with_options(test_options());
+    let an = analyzer.perform();
+    let doc = MarkdownGenerator::new(&api, &an, &schedule, team).markdown().unwrap();
+    let md = doc.as_str();
+
+    for section in &[
+        "# Playoffs race!",
+        "## Last night's race",
+        "## Standings",
+        "## Playoffs matchups",
+        "## Tonight's race",
+        "## Upcoming schedule",
+        "### Disclaimer",
+    ] {
+        assert!(md.contains(section), "missing section {:?}", section);
+    }
+    assert!(md.contains("Holding on to a wildcard spot."));
+    assert!(md.contains("Game|Score|Result\n"));
+    assert!(md.contains("Playoffs odds today: "));
+    assert!(md.contains(" since yesterday, simulated)"));
+    assert!(md.contains("|L10|Streak|"));
+    // CAR is on a 4 game winning streak.
+    assert!(md.contains("|W4 🔥|"));
+    assert!(md.contains("[](/r/habs)MTL"));
+    assert!(md.contains("[](/r/tampabaylightning)TBL"));
+    assert!(md.contains("[](/r/rangers)NYR at [](/r/habs)MTL"));
+    assert!(!md.contains("[](/r/calgaryflames)CGY"));
+    assert!(!md.contains("Appendix"));
+    assert!(!md.contains("✅"));
+}
+
+#[test]
+fn test_next_game_preview() {
+    let api = load_api();
+    let schedule = load_schedule();
+    let past_schedule = nhlapi::schedule::parse_range(&fixture("schedule-MTL-2018-10-03-2019-03-14")).unwrap();
+    let team = api.get_team_by_abbrev("MTL");
+    let analyzer = Analyzer::new(&api, team).with_options(test_options());
+    let an = analyzer.perform();
+
+    let gen = MarkdownGenerator::new(&api, &an, &schedule, team).with_past_schedule(&past_schedule);
+    let preview = gen.make_next_game_preview().unwrap();
+    assert!(preview.starts_with("Next game: [](/r/habs)MTL at [](/r/tampabaylightning)TBL, "));
+    assert!(preview.contains("AMALIE Arena"));
+    assert!(preview.contains("[](/r/tampabaylightning)TBL is 52-13-4 with 108 points."));
+    // A loss, an overtime win and an overtime loss; the NYR game is ignored.
+    assert!(preview.ends_with("Season series so far: 1-1-1."));
+
+    let gen = MarkdownGenerator::new(&api, &an, &schedule, team);
+    assert!(gen
+        .make_next_game_preview()
+        .unwrap()
+        .ends_with("First meeting of the season."));
+
+    let gen = MarkdownGenerator::new(&api, &an, &[], team);
+    assert!(gen.make_next_game_preview().is_none());
+}
+
+#[test]
+fn test_expected_points() {
+    let api = load_api();
+    let team = api.get_team_by_abbrev("MTL");
+    let record = api.standings.iter().find(|r| r.team.id == team.id).unwrap();
+    let remaining = simulation::remaining_games(record) as f64;
+
+    // Sampling the outcomes keeps the points pace of the season.
+    let pace = record.points as f64 / record.games_played as f64;
+    let stats = simulation::expected_points(&api, team, &test_params());
+    assert!((stats.mean - (record.points as f64 + pace * remaining)).abs() < 0.5);
+    assert!(stats.std_dev > 0.0 && stats.std_dev < remaining);
+
+    let schedule = load_schedule();
+    let analyzer = Analyzer::new(&api, team).with_options(test_options());
+    let an = analyzer.perform();
+    let doc = MarkdownGenerator::new(&api, &an, &schedule, team)
+        .with_options(generate::Options {
+            projected_points: true,
+            ..Default::default()
+        })
+        .markdown()
+        .unwrap();
+    assert!(doc.as_str().contains("Projected final: "));
+    assert!(doc.as_str().contains(" pts\n"));
+}
+
+#[test]
+fn test_markdown_playoffs() {
+    let today = NaiveDate::from_ymd(2019, 4, 12);
+    let regular = load_api();
+    assert!(!regular.is_playoffs());
+
+    let results = nhlapi::schedule::parse_date(&fixture("schedule-2019-04-11"), &today.pred()).unwrap();
+    let games = nhlapi::schedule::parse_date(&fixture("schedule-2019-04-12"), &today).unwrap();
+    let api = Api::from_parts(
+        regular.teams,
+        regular.past_standings,
+        regular.standings,
+        Some(results),
+        games,
+    );
+    assert!(api.is_playoffs());
+
+    let team = api.get_team_by_abbrev("MTL");
+    let analyzer = Analyzer::new(&api, team).with_options(test_options());
+    let an = analyzer.perform();
+    let doc = MarkdownGenerator::new(&api, &an, &[], team).markdown().unwrap();
+    let md = doc.as_str();
+
+    assert!(md.starts_with("# Playoffs!\nRegular season complete.\n"));
+    assert!(
+        md.contains("[](/r/habs)MTL at [](/r/tampabaylightning)TBL|2-4 [](/r/tampabaylightning)TBL |TBL leads 1-0\n")
+    );
+    assert!(md.contains("[](/r/leafs)TOR at [](/r/bostonbruins)BOS|1-4 [](/r/leafs)TOR |TOR leads 1-0\n"));
+    assert!(md.contains("[](/r/habs)MTL at [](/r/tampabaylightning)TBL|Game 2|TBL leads 1-0|"));
+    assert!(!md.contains("Playoffs odds"));
+    assert!(!md.contains("## Standings"));
+}
+
+#[test]
+fn test_markdown_live_game() {
+    let mut api = load_api();
+    let schedule = load_schedule();
+    // The live line only depends on the state of the team's game.
+    let live = |api: &Api| {
+        let team = api.get_team_by_abbrev("MTL");
+        let analyzer = Analyzer::new(api, team).with_options(seeding_options());
+        let an = analyzer.perform();
+        MarkdownGenerator::new(api, &an, &schedule, team).make_live_block()
+    };
+    assert_eq!(live(&api), None);
+
+    let set_game = |api: &mut Api, period: u32, intermission: bool, away: u32, home: u32| {
+        let game = &mut api.games.games[0];
+        game.status.abstract_game_state = "Live".to_string();
+        game.linescore.current_period = period;
+        game.linescore.periods = (0..period)
+            .map(|_| nhlapi::schedule::Period {
+                period_type: "REGULAR".to_string(),
+            })
+            .collect();
+        game.linescore.intermission_info.in_intermission = intermission;
+        game.teams.away.score = away;
+        game.teams.home.score = home;
+    };
+
+    // MTL is the away team at TBL.
+    set_game(&mut api, 2, false, 2, 1);
+    assert_eq!(live(&api).unwrap(), "LIVE: 2-1 MTL, 2nd period");
+    set_game(&mut api, 2, true, 2, 3);
+    assert_eq!(live(&api).unwrap(), "LIVE: 3-2 TBL, 2nd intermission");
+    set_game(&mut api, 4, false, 3, 3);
+    assert_eq!(live(&api).unwrap(), "LIVE: 3-3, overtime");
+    set_game(&mut api, 5, false, 3, 3);
+    assert_eq!(live(&api).unwrap(), "LIVE: 3-3, shootout");
+}
+
+#[test]
+fn test_markdown_team_emoji() {
+    let api = load_api();
+    let schedule = load_schedule();
+    let team = api.get_team_by_abbrev("MTL");
+    let analyzer = Analyzer::new(&api, team).with_options(test_options());
+    let an = analyzer.perform();
+    let doc = MarkdownGenerator::new(&api, &an, &schedule, team)
+        .with_options(generate::Options {
+            team_emoji: true,
+            ..Default::default()
+        })
+        .markdown()
+        .unwrap();
+    let md = doc.as_str();
+
+    assert!(md.starts_with("# ⚜️ Playoffs race!\n"));
+    assert!(md.contains("[](/r/rangers)NYR at ⚜️ [](/r/habs)MTL"));
+    // Only the team of the post gets its emoji.
+    assert!(md.contains("|[](/r/tampabaylightning)TBL|"));
+}
+
+#[test]
+fn test_markdown_three_stars() {
+    let api = load_api();
+    let schedule = load_schedule();
+    let decisions = nhlapi::game::parse_decisions(&fixture("game-2018021041-feed")).unwrap();
+    let team = api.get_team_by_abbrev("MTL");
+    let analyzer = Analyzer::new(&api, team).with_options(test_options());
+    let an = analyzer.perform();
+    let options = generate::Options {
+        three_stars: true,
+        ..Default::default()
+    };
+    let stars = "Three stars: 1. Brendan Gallagher, 2. Max Domi, 3. Carey Price";
+
+    let gen = MarkdownGenerator::new(&api, &an, &schedule, team)
+        .with_options(options.clone())
+        .with_decisions(&decisions);
+    assert!(gen.markdown().unwrap().as_str().contains(stars));
+
+    // Omitted when the decisions were not fetched.
+    let gen = MarkdownGenerator::new(&api, &an, &schedule, team).with_options(options);
+    assert!(!gen.markdown().unwrap().as_str().contains("Three stars"));
+}
+
+#[test]
+fn test_markdown_starting_goalies() {
+    use generate::PostSection;
+
+    let api = load_api();
+    let schedule = load_schedule();
+    let starters = nhlapi::game::parse_starters(&fixture("game-2018021048-feed"))
+        .unwrap()
+        .unwrap();
+    let team = api.get_team_by_abbrev("MTL");
+    let analyzer = Analyzer::new(&api, team).with_options(test_options());
+    let an = analyzer.perform();
+    let options = generate::Options {
+        starting_goalies: true,
+        sections: vec![PostSection::Tonight],
+        ..Default::default()
+    };
+    let probable = "Probable: Carey Price vs Andrei Vasilevskiy.";
+
+    let gen = MarkdownGenerator::new(&api, &an, &schedule, team)
+        .with_options(options.clone())
+        .with_starters(&starters);
+    assert!(gen.make_next_game_preview().unwrap().ends_with(probable));
+    assert!(gen.markdown().unwrap().as_str().contains(&format!("{}\n\n", probable)));
+
+    // Omitted when they were not published, and for the other games.
+    let gen = MarkdownGenerator::new(&api, &an, &schedule, team).with_options(options.clone());
+    assert!(!gen.markdown().unwrap().as_str().contains("Probable"));
+    let mut other = starters.clone();
+    other.game_pk += 1;
+    let gen = MarkdownGenerator::new(&api, &an, &schedule, team)
+        .with_options(options)
+        .with_starters(&other);
+    assert!(gen
+        .make_next_game_preview()
+        .unwrap()
+        .ends_with("First meeting of the season."));
+}
+
+#[test]
+fn test_result_moods() {
+    let api = load_api();
+    let team = api.get_team_by_abbrev("MTL");
+    let analyzer = Analyzer::new(&api, team).with_options(test_options());
+    let an = analyzer.perform();
+    let mood = |abbrev| {
+        let team_id = api.get_team_by_abbrev(abbrev).id;
+        an.results
+            .iter()
+            .find(|m| m.game.home_team().id == team_id)
+            .unwrap()
+            .mood()
+    };
+
+    // MTL beat NYR.
+    assert_eq!(an.my_result.as_ref().unwrap().mood(), Mood::Great);
+    // PHI lost to DAL in a shootout. PHI is the ideal loser since DAL is in
+    // the other conference, but it still got a point.
+    assert_eq!(mood("PHI"), Mood::Good);
+    // NJD lost to FLA in regulation, so the result can't be just good.
+    assert_ne!(mood("NJD"), Mood::Good);
+}
+
+/// MTL-NYR from the fixtures with another final score, `period` 4 being
+/// overtime and 5 a shootout.
+fn mtl_nyr_with_score(api: &Api, home: u32, away: u32, period: u32) -> nhlapi::schedule::Game {
+    let mut game = api.results.as_ref().unwrap().games[0].clone();
+    assert!(game.linescore.has_periods());
+    game.teams.home.score = home;
+    game.teams.away.score = away;
+    game.linescore.current_period = period;
+    game
+}
+
+fn matchup(game: &nhlapi::schedule::Game, home_is_ideal_loser: bool, in_conference: bool) -> Matchup<'_> {
+    Matchup {
+        game,
+        is_result: true,
+        is_my_team_involed: false,
+        ideal_loser: if home_is_ideal_loser {
+            game.home_team()
+        } else {
+            game.away_team()
+        },
+        ideal_loser_in_conference: in_conference,
+        loser_odds: None,
+        win_loss_odds: None,
+    }
+}
+
+#[test]
+fn test_matchup_moods() {
+    let api = load_api();
+
+    let regulation = mtl_nyr_with_score(&api, 4, 1, 3);
+    let overtime = mtl_nyr_with_score(&api, 4, 3, 4);
+    let shootout = mtl_nyr_with_score(&api, 4, 3, 5);
+
+    // The ideal loser lost.
+    assert_eq!(matchup(&regulation, false, true).mood(), Mood::Great);
+    assert_eq!(matchup(&overtime, false, true).mood(), Mood::Good);
+    assert_eq!(matchup(&shootout, false, true).get_mood(), "Good");
+    // Its point doesn't matter outside of the conference.
+    assert_eq!(matchup(&overtime, false, false).get_mood(), "Great");
+    // The ideal loser won, in regulation or not.
+    assert_eq!(matchup(&regulation, true, true).get_mood(), "Bad");
+    assert_eq!(matchup(&overtime, true, true).mood(), Mood::Bad);
+    assert_eq!(matchup(&shootout, true, false).mood(), Mood::Bad);
+}
+
+#[test]
+fn test_matchup_cheer_for() {
+    let api = load_api();
+    let game = mtl_nyr_with_score(&api, 4, 1, 3);
+    assert_eq!(matchup(&game, true, true).cheer_for().id, game.away_team().id);
+    assert_eq!(matchup(&game, false, true).cheer_for().id, game.home_team().id);
+
+    // The analysis only ever picks one of the two teams as the ideal loser.
+    let team = api.get_team_by_abbrev("MTL");
+    let analyzer = Analyzer::new(&api, team).with_options(analysis::Options {
+        other_conference: true,
+        ..test_options()
+    });
+    let an = analyzer.perform();
+    let matchups = an
+        .my_result
+        .iter()
+        .chain(&an.results)
+        .chain(&an.my_game)
+        .chain(&an.games);
+    for m in matchups {
+        let cheer_for = m.cheer_for();
+        assert_ne!(cheer_for.id, m.ideal_loser.id);
+        assert!(cheer_for.id == m.game.home_team().id || cheer_for.id == m.game.away_team().id);
+    }
+}
+
+#[test]
+#[should_panic(expected = "invalid match loser")]
+fn test_matchup_cheer_for_other_team() {
+    let api = load_api();
+    let game = mtl_nyr_with_score(&api, 4, 1, 3);
+    let other = &api.results.as_ref().unwrap().games[1];
+    let m = Matchup {
+        ideal_loser: other.home_team(),
+        ..matchup(&game, true, true)
+    };
+    m.cheer_for();
+}
+
+#[test]
+fn test_odds_for_conference() {
+    let api = load_api();
+    let team = api.get_team_by_abbrev("MTL");
+    let odds = simulation::odds_for_conference(&api, team, false, &test_params()).unwrap();
+
+    assert_eq!(odds.len(), 16);
+    // Every simulated season sends exactly 8 teams to the playoffs.
+    let total: f64 = odds.iter().map(|(_, x)| x).sum();
+    assert!((total - 8.0).abs() < 1e-6);
+    let odds_of = |abbrev| {
+        let team_id = api.get_team_by_abbrev(abbrev).id;
+        odds.iter().find(|(id, _)| *id == team_id).unwrap().1
+    };
+    assert!(odds_of("TBL") > 0.99);
+    assert!(odds_of("OTT") < 0.01);
+    assert!(odds.windows(2).all(|w| w[0].1 >= w[1].1));
+}
+
+#[test]
+fn test_wins_needed() {
+    let api = load_api();
+    let params = test_params();
+    let wins_needed =
+        |abbrev, threshold| simulation::wins_needed(&api, api.get_team_by_abbrev(abbrev), threshold, &params);
+
+    // MTL has 12 games left.
+    let mtl = wins_needed("MTL", 0.9).unwrap();
+    assert!(mtl > 0 && mtl <= 12);
+    assert!(wins_needed("MTL", 0.5).unwrap() <= mtl);
+    assert_eq!(wins_needed("TBL", 0.9), Some(0));
+    assert_eq!(wins_needed("OTT", 0.5), None);
+}
+
+#[test]
+fn test_markdown_odds_paragraphs() {
+    let api = load_api();
+    let schedule = load_schedule();
+    let team = api.get_team_by_abbrev("MTL");
+    let analyzer = Analyzer::new(&api, team).with_options(test_options());
+    let an = analyzer.perform();
+    let doc = MarkdownGenerator::new(&api, &an, &schedule, team)
+        .with_options(generate::Options {
+            target_odds: Some(0.9),
+            pace_baseline: true,
+            likely_opponents: true,
+            ..Default::default()
+        })
+        .markdown()
+        .unwrap();
+    let md = doc.as_str();
+
+    assert!(md.contains(" of your last 12 and you're 90%+ to make it."));
+    assert!(md.contains("Naive pace says: IN (8th)"));
+    assert!(md.contains("Most likely first round opponents:\n\n* [](/r/tampabaylightning)TBL: "));
+}
+
+#[test]
+fn test_points_pace_projection() {
+    let api = load_api();
+    let projection = |abbrev| simulation::points_pace_projection(&api, api.get_team_by_abbrev(abbrev));
+
+    assert_eq!(
+        projection("TBL"),
+        simulation::PaceProjection {
+            makes_playoffs: true,
+            rank: 1
+        }
+    );
+    // MTL holds the last wildcard spot at its pace, one point ahead of CBJ.
+    assert_eq!(
+        projection("MTL"),
+        simulation::PaceProjection {
+            makes_playoffs: true,
+            rank: 8
+        }
+    );
+    assert!(!projection("CBJ").makes_playoffs);
+    assert_eq!(projection("OTT").rank, 16);
+}
+
+#[test]
+fn test_odds_for_league() {
+    let api = load_api();
+    let odds = simulation::odds_for_league(&api, &test_params());
+
+    assert_eq!(odds.len(), 31);
+    // Both conferences send 8 teams to the playoffs.
+    let total: f64 = odds.iter().map(|(_, x)| x).sum();
+    assert!((total - 16.0).abs() < 1e-6);
+    assert!(odds.windows(2).all(|w| w[0].1 >= w[1].1));
+
+    let table = generate::make_league_odds_table(&api, &odds).unwrap().to_string();
+    assert!(table.starts_with("Team|Conference|GP|Points|Odds\n"));
+    assert!(table.contains("\nMTL|Eastern|70|81|"));
+}
+
+#[test]
+fn test_first_round_opponents() {
+    let api = load_api();
+    let team = api.get_team_by_abbrev("MTL");
+    let params = test_params();
+    let opponents = simulation::first_round_opponents(&api, team, &params);
+
+    // MTL holds the second wildcard, which faces the best division leader.
+    assert_eq!(opponents[0].0, api.get_team_by_abbrev("TBL").id);
+    assert!(opponents.windows(2).all(|w| w[0].1 >= w[1].1));
+    assert!(opponents
+        .iter()
+        .all(|(id, _)| api.get_team_by_id(*id).conference.id == team.conference.id && *id != team.id));
+
+    // Every season where MTL makes the playoffs gives it one opponent.
+    let total: f64 = opponents.iter().map(|(_, x)| x).sum();
+    let odds = simulation::odds_for_team(&api, team, false, &params).unwrap();
+    assert!((total - odds).abs() < 0.02);
+}
+
+#[test]
+fn test_odds_with_results() {
+    let api = load_api();
+    let team = api.get_team_by_abbrev("MTL");
+    let wins = vec![(team.id, simulation::Event::Win); 8];
+    let losses = vec![(team.id, simulation::Event::Loss); 8];
+
+    let params = test_params();
+
+    let today = simulation::odds_for_team(&api, team, false, &params).unwrap();
+    assert!(simulation::odds_with_results(&api, team, &wins, &params) > today);
+    assert!(simulation::odds_with_results(&api, team, &losses, &params) < today);
+}
+
+#[test]
+fn test_sim_count() {
+    let api = load_api();
+    let team = api.get_team_by_abbrev("MTL");
+    let params = test_params();
+    let before = simulation::sim_count();
+    simulation::Simulation::new(&api, team, &api.standings, &params).run_for(100);
+    // Other tests may run simulations at the same time.
+    let count = simulation::sim_count() - before;
+    assert!(count.simulations >= 1);
+    assert!(count.seasons >= 100);
+}
+
+#[test]
+fn test_pinned_results() {
+    use simulation::{OddsMode, PinnedResult, Simulation};
+
+    let api = load_api();
+    let schedule = load_schedule();
+    let team = api.get_team_by_abbrev("MTL");
+    let pinned_games = |mtl_wins| {
+        schedule
+            .iter()
+            .flat_map(|date| &date.games)
+            .map(|game| {
+                let opponent = if game.home_team().id == team.id {
+                    api.get_team_by_id(game.away_team().id)
+                } else {
+                    api.get_team_by_id(game.home_team().id)
+                };
+                let (winner, loser) = if mtl_wins { (team, opponent) } else { (opponent, team) };
+                let result = PinnedResult {
+                    winner: winner.abbrev.clone(),
+                    loser: loser.abbrev.clone(),
+                    overtime: false,
+                };
+                (game.game_pk, result)
+            })
+            .collect()
+    };
+    let odds = |pinned| {
+        let params = simulation::Params {
+            odds_mode: OddsMode::Simulation,
+            pinned,
+            ..test_params()
+        };
+        simulation::odds_for_team(&api, team, false, &params).unwrap()
+    };
+
+    // Winning every one of its 12 remaining games is enough for MTL, and
+    // losing every one of them is not.
+    assert_eq!(odds(pinned_games(true)), 1.0);
+    assert_eq!(odds(pinned_games(false)), 0.0);
+
+    // A game only gets one result.
+    let params = test_params();
+    let mut sim = Simulation::new(&api, team, &api.standings, &params);
+    let game = &schedule[0].games[0];
+    assert!(sim.pin_result(game.game_pk, game.home_team().id, game.away_team().id, false));
+    assert!(!sim.pin_result(game.game_pk, game.away_team().id, game.home_team().id, false));
+}
+
+#[test]
+fn test_odds_mode() {
+    use simulation::{OddsMethod, OddsMode};
+
+    let api = load_api();
+    let params = |odds_mode| simulation::Params {
+        odds_mode,
+        ..test_params()
+    };
+    let odds = |abbrev, odds_mode| {
+        simulation::odds_and_method(&api, api.get_team_by_abbrev(abbrev), false, &params(odds_mode)).unwrap()
+    };
+
+    // TBL has clinched, MTL is still in the race.
+    assert_eq!(odds("TBL", OddsMode::Auto), (1.0, OddsMethod::Analytic));
+    assert_eq!(odds("TBL", OddsMode::Simulation).1, OddsMethod::Simulation);
+    assert_eq!(odds("MTL", OddsMode::Auto).1, OddsMethod::Simulation);
+
+    // Analytic odds of an open race follow the points pace.
+    let pace = simulation::points_pace_projection(&api, api.get_team_by_abbrev("MTL"));
+    let expected = if pace.makes_playoffs { 1.0 } else { 0.0 };
+    assert_eq!(odds("MTL", OddsMode::Analytic), (expected, OddsMethod::Analytic));
+
+    let schedule = load_schedule();
+    let team = api.get_team_by_abbrev("MTL");
+    let analyzer = Analyzer::new(&api, team).with_options(analysis::Options {
+        params: params(OddsMode::Analytic),
+        ..test_options()
+    });
+    let an = analyzer.perform();
+    let doc = MarkdownGenerator::new(&api, &an, &schedule, team).markdown().unwrap();
+    assert!(doc.as_str().contains(" since yesterday, analytic)"));
+}
+
+#[test]
+fn test_clinch_status() {
+    let mut api = load_api();
+    let status = |api: &playoffsbot::analysis::Api, abbrev| {
+        simulation::clinch_status(api, api.get_team_by_abbrev(abbrev), &api.standings)
+    };
+
+    // Only BOS and TOR can still catch TBL.
+    assert_eq!(status(&api, "TBL"), simulation::Clinch::Clinched);
+    assert_eq!(status(&api, "MTL"), simulation::Clinch::Undecided);
+    assert_eq!(status(&api, "OTT"), simulation::Clinch::Undecided);
+
+    // A few games from the end, OTT is all but out and BOS all but in.
+    let within =
+        |abbrev, buffer| simulation::clinch_status_within(&api, api.get_team_by_abbrev(abbrev), &api.standings, buffer);
+    assert_eq!(within("OTT", 1), simulation::Clinch::Undecided);
+    assert_eq!(within("OTT", 2), simulation::Clinch::Eliminated);
+    assert_eq!(within("BOS", 5), simulation::Clinch::Undecided);
+    assert_eq!(within("BOS", 6), simulation::Clinch::Clinched);
+    assert_eq!(within("MTL", 6), simulation::Clinch::Undecided);
+
+    // With 2 games left, OTT can't reach more than 64 points.
+    let ott = api.get_team_by_abbrev("OTT").id;
+    let record = api.standings.iter_mut().find(|r| r.team.id == ott).unwrap();
+    record.games_played = 80;
+    record.points = 60;
+    assert_eq!(status(&api, "OTT"), simulation::Clinch::Eliminated);
+
+    let params = test_params();
+    let ott = api.get_team_by_abbrev("OTT");
+    assert_eq!(simulation::odds_for_team(&api, ott, false, &params).unwrap(), 0.0);
+    let tbl = api.get_team_by_abbrev("TBL");
+    assert_eq!(simulation::odds_for_team(&api, tbl, false, &params).unwrap(), 1.0);
+
+    // With one spot for each division, BOS can still take TBL's.
+    let api = load_four_division_api();
+    assert_eq!(status(&api, "TBL"), simulation::Clinch::Undecided);
+    // But only four teams can pass it a few games from the end, so it is in
+    // the top 5 of the conference either way.
+    assert_eq!(
+        simulation::clinch_status_within(&api, api.get_team_by_abbrev("TBL"), &api.standings, 2),
+        simulation::Clinch::Clinched
+    );
+}
+
+#[test]
+fn test_division_leaders_tie() {
+    let mut api = load_api();
+    let tbl = api.get_team_by_abbrev("TBL").id;
+    let wsh = api.get_team_by_abbrev("WSH").id;
+    let top_seed = |api: &Api| {
+        let team = api.get_team_by_abbrev("MTL");
+        let analyzer = Analyzer::new(api, team).with_options(seeding_options());
+        let an = analyzer.perform();
+        an.playoffs[0].high_team.team.id
+    };
+    assert_eq!(top_seed(&api), tbl);
+
+    let set = |api: &mut Api, id, points, row, goals_scored| {
+        let record = api.standings.iter_mut().find(|r| r.team.id == id).unwrap();
+        record.points = points;
+        record.row = row;
+        record.goals_scored = goals_scored;
+        record.goals_against = 200;
+    };
+
+    // Tied in points, WSH has more regulation and overtime wins.
+    set(&mut api, tbl, 90, 40, 250);
+    set(&mut api, wsh, 90, 41, 220);
+    assert_eq!(top_seed(&api), wsh);
+
+    // Tied in ROW too, TBL has the better goal differential.
+    set(&mut api, wsh, 90, 40, 220);
+    assert_eq!(top_seed(&api), tbl);
+}
+
+#[test]
+fn test_standings_playoff_line() {
+    let api = load_api();
+    let schedule = load_schedule();
+    let team = api.get_team_by_abbrev("MTL");
+    let analyzer = Analyzer::new(&api, team).with_options(seeding_options());
+    let an = analyzer.perform();
+
+    // No line by default.
+    let gen = MarkdownGenerator::new(&api, &an, &schedule, team);
+    let table = gen
+        .make_standings_table(&an.wildcard_seed, Some(an.wildcard_spots))
+        .unwrap()
+        .to_string();
+    assert!(!table.contains("✅"));
+
+    let mut options = generate::Options::default();
+    options.playoff_line_points.insert("Eastern".to_string(), 96);
+    let gen = MarkdownGenerator::new(&api, &an, &schedule, team).with_options(options);
+    let table = gen
+        .make_standings_table(&an.wildcard_seed, Some(an.wildcard_spots))
+        .unwrap()
+        .to_string();
+    let row = |abbrev| {
+        table
+            .lines()
+            .find(|l| l.contains(&format!("){}", abbrev)))
+            .unwrap()
+            .to_string()
+    };
+    // CAR is on pace for 97 points, MTL for 95 and PHI for 86.
+    assert!(row("CAR").ends_with("|97 ✅"));
+    assert!(row("MTL").ends_with("|**95 🟡**"));
+    assert!(row("PHI").ends_with("|*86 ❌*"));
+}
+
+#[test]
+fn test_standings_cut_line() {
+    let api = load_api();
+    let schedule = load_schedule();
+    let team = api.get_team_by_abbrev("MTL");
+    let analyzer = Analyzer::new(&api, team).with_options(seeding_options());
+    let an = analyzer.perform();
+    let gen = MarkdownGenerator::new(&api, &an, &schedule, team);
+    let separator = "-|-|-|-|-|-|-|-|-|-";
+    let rows = |table: String| {
+        table
+            .lines()
+            .skip(2)
+            .filter(|l| !l.is_empty())
+            .map(|l| l.to_string())
+            .collect::<Vec<_>>()
+    };
+
+    // Two wildcards, the line falls after the 2nd team.
+    let table = rows(
+        gen.make_standings_table(&an.wildcard_seed, Some(an.wildcard_spots))
+            .unwrap()
+            .to_string(),
+    );
+    // Two wildcards, the separator and the two next teams.
+    assert_eq!(table.len(), 5);
+    assert_eq!(table[2], separator);
+    assert!(table[1].starts_with("**2**|**[](/r/habs)MTL**|"));
+    assert!(!table[0].contains("**"));
+    // The chasers are in italics, with their points from the cut line.
+    let cut_points = an.wildcard_seed[1].record.points;
+    let chaser = an.wildcard_seed[2].record;
+    assert!(table[3].starts_with("*3*|*[](/r/"));
+    assert!(table[3].contains(&format!(
+        "|*{} ({})*|",
+        chaser.points,
+        chaser.points as i32 - cut_points as i32
+    )));
+
+    // Top 4 format, the line falls after the 4th team with chasers below it.
+    let table = rows(
+        gen.make_standings_table(&an.wildcard_seed, Some(4))
+            .unwrap()
+            .to_string(),
+    );
+    assert_eq!(table[4], separator);
+    assert_eq!(table.iter().filter(|l| *l == separator).count(), 1);
+    assert!(table[5].starts_with("*5*|"));
+
+    // The team itself is always shown, even below the chasers.
+    let options = generate::Options {
+        chasers_shown: 0,
+        ..Default::default()
+    };
+    let gen_no_chasers = MarkdownGenerator::new(&api, &an, &schedule, team).with_options(options);
+    let table = rows(
+        gen_no_chasers
+            .make_standings_table(&an.wildcard_seed, Some(1))
+            .unwrap()
+            .to_string(),
+    );
+    assert_eq!(table.len(), 3);
+    assert_eq!(table[1], separator);
+    assert!(table[2].starts_with("**2**|**[](/r/habs)MTL**|"));
+
+    // Everyone is in, no line.
+    let table = rows(
+        gen.make_standings_table(&an.own_division_seed, None)
+            .unwrap()
+            .to_string(),
+    );
+    assert!(!table.iter().any(|l| l == separator));
+    let table = rows(
+        gen.make_standings_table(&an.own_division_seed, Some(3))
+            .unwrap()
+            .to_string(),
+    );
+    assert!(!table.iter().any(|l| l == separator));
+}
+
+#[test]
+fn test_ideal_loser_same_model_as_odds() {
+    let api = load_api();
+    let team = api.get_team_by_abbrev("MTL");
+    let params = simulation::Params {
+        seed: Some(2019),
+        ..test_params()
+    };
+    let conference = |id| api.get_team_by_id(id).conference.id == team.conference.id;
+    let game = api
+        .games
+        .games
+        .iter()
+        .find(|g| {
+            !g.is_final()
+                && g.home_team().id != team.id
+                && g.away_team().id != team.id
+                && conference(g.home_team().id)
+                && conference(g.away_team().id)
+        })
+        .unwrap();
+
+    // The headline odds are a blend of the odds with either team winning the
+    // game, as the simulations picking the ideal loser compute them.
+    let odds = |winner: u32, loser: u32| {
+        let mut sim = simulation::Simulation::today(&api, team, &params);
+        assert!(sim.pin_result(game.game_pk, winner, loser, false));
+        sim.run_for(params.times()) as f64 / params.times() as f64
+    };
+    let (home, away) = (game.home_team().id, game.away_team().id);
+    let home_win = odds(home, away);
+    let away_win = odds(away, home);
+    let headline = simulation::odds_for_team(&api, team, false, &params).unwrap();
+    assert!(
+        headline >= home_win.min(away_win),
+        "{} < {}",
+        headline,
+        home_win.min(away_win)
+    );
+    assert!(
+        headline <= home_win.max(away_win),
+        "{} > {}",
+        headline,
+        home_win.max(away_win)
+    );
+
+    // Seeded, the simulations picking the ideal loser are the same as above.
+    let ideal_loser = simulation::pick_ideal_loser(&api, team, false, game, &params);
+    let expected = if home_win > away_win { away } else { home };
+    assert_eq!(ideal_loser.id, expected);
+}
+
+#[test]
+fn test_explanations() {
+    let api = load_api();
+    let schedule = load_schedule();
+    let team = api.get_team_by_abbrev("MTL");
+    let analyzer = Analyzer::new(&api, team).with_options(test_options());
+    let an = analyzer.perform();
+
+    // The odds are kept for the games between two teams of the conference.
+    let simulated: Vec<_> = an
+        .results
+        .iter()
+        .chain(&an.games)
+        .filter(|m| m.loser_odds.is_some())
+        .collect();
+    assert!(!simulated.is_empty());
+    for m in &simulated {
+        let odds = m.loser_odds.unwrap();
+        assert_eq!(m.ideal_loser.id, odds.ideal_loser(m.game).id);
+    }
+
+    let gen = MarkdownGenerator::new(&api, &an, &schedule, team);
+    let explanations = gen.make_explanations();
+    assert_eq!(explanations.len(), an.results.len() + an.games.len());
+    // CBJ won at PIT last night.
+    let cbj_pit = explanations.iter().find(|e| e.starts_with("CBJ@PIT: ")).unwrap();
+    assert!(cbj_pit.contains("PIT loss → "), "{}", cbj_pit);
+    assert!(cbj_pit.contains("%, CBJ loss → "), "{}", cbj_pit);
+    assert!(cbj_pit.ends_with("⇒ cheer PIT"), "{}", cbj_pit);
+    // DAL is in the other conference, so PHI loses without a simulation.
+    assert!(explanations.contains(&"DAL@PHI: not simulated ⇒ cheer DAL".to_string()));
+}
+
+#[test]
+fn test_bracket_crossover() {
+    let labels = |api: &Api| -> Vec<(String, String)> {
+        let team = api.get_team_by_abbrev("MTL");
+        Analyzer::new(api, team)
+            .with_options(seeding_options())
+            .perform()
+            .playoffs
+            .iter()
+            .map(|pm| {
+                (
+                    format!("{} {}", pm.high_seed, pm.high_team.team.name),
+                    format!("{} {}", pm.low_seed, pm.low_team.team.name),
+                )
+            })
+            .collect()
+    };
+    // Tampa Bay has the best record, so it faces the second wildcard.
+    let api = load_api();
+    let first = labels(&api);
+    assert_eq!(first[0].0, "A1 Tampa Bay Lightning");
+    assert!(first[0].1.starts_with("WC2 "));
+    assert_eq!(first[1].0, "M1 Washington Capitals");
+    assert!(first[1].1.starts_with("WC1 "));
+    assert!(first[2].0.starts_with("A2 ") && first[2].1.starts_with("A3 "));
+    assert!(first[3].0.starts_with("M2 ") && first[3].1.starts_with("M3 "));
+
+    // With a better record than Tampa Bay, Washington faces the second
+    // wildcard instead.
+    let mut api = load_api();
+    let wsh = api.get_team_by_abbrev("WSH").id;
+    api.standings.iter_mut().find(|r| r.team.id == wsh).unwrap().points = 200;
+    let second = labels(&api);
+    assert_eq!(second[0], (first[1].0.clone(), first[0].1.clone()));
+    assert_eq!(second[1], (first[0].0.clone(), first[1].1.clone()));
+    assert_eq!(second[2..], first[2..]);
+
+    // The labels show in the post, for both conferences.
+    let api = load_api();
+    let team = api.get_team_by_abbrev("MTL");
+    let analyzer = Analyzer::new(&api, team).with_options(analysis::Options {
+        other_conference: true,
+        ..seeding_options()
+    });
+    let an = analyzer.perform();
+    let md = MarkdownGenerator::new(&api, &an, &[], team)
+        .with_options(generate::Options {
+            bracket_seeds: true,
+            both_conferences: true,
+            sections: vec![
+                generate::PostSection::Standings,
+                generate::PostSection::Bracket,
+                generate::PostSection::OtherConference,
+            ],
+            ..Default::default()
+        })
+        .markdown()
+        .unwrap()
+        .as_str()
+        .to_string();
+    for label in &["A1 ", "M1 ", "WC2 ", "P1 ", "C1 "] {
+        assert!(md.contains(label), "missing {:?}", label);
+    }
+}
+
+#[test]
+fn test_key_games() {
+    let api = load_api();
+    let team = api.get_team_by_abbrev("MTL");
+    assert!(Analyzer::new(&api, team)
+        .with_options(test_options())
+        .perform()
+        .key_games
+        .is_empty());
+
+    let analyzer = Analyzer::new(&api, team).with_options(analysis::Options {
+        around_the_league: 2,
+        ..test_options()
+    });
+    let an = analyzer.perform();
+    assert_eq!(an.key_games.len(), 2);
+    assert!(an.key_games[0].1 >= an.key_games[1].1);
+    assert!(an.key_games.iter().all(|&(_, importance)| importance > 0.0));
+
+    let md = MarkdownGenerator::new(&api, &an, &[], team)
+        .with_options(generate::Options {
+            sections: vec![generate::PostSection::Tonight],
+            ..Default::default()
+        })
+        .markdown()
+        .unwrap()
+        .as_str()
+        .to_string();
+    assert!(md.contains("Around the league:"));
+    assert_eq!(md.matches("% of playoffs odds at stake").count(), 2);
+}
+
+#[test]
+fn test_season_series_column() {
+    let api = load_api();
+    let schedule = load_schedule();
+    let past_schedule = nhlapi::schedule::parse_range(&fixture("schedule-MTL-2018-10-03-2019-03-14")).unwrap();
+    let team = api.get_team_by_abbrev("MTL");
+    let analyzer = Analyzer::new(&api, team).with_options(seeding_options());
+    let an = analyzer.perform();
+
+    let gen = MarkdownGenerator::new(&api, &an, &schedule, team).with_past_schedule(&past_schedule);
+    let table = gen
+        .make_standings_table(&an.own_division_seed, None)
+        .unwrap()
+        .to_string();
+    assert!(!table.contains("|vs"));
+
+    let options = generate::Options {
+        season_series: true,
+        ..Default::default()
+    };
+    let gen = MarkdownGenerator::new(&api, &an, &schedule, team)
+        .with_past_schedule(&past_schedule)
+        .with_options(options);
+    let division = gen
+        .make_standings_table(&an.own_division_seed, None)
+        .unwrap()
+        .to_string();
+    assert!(division.lines().next().unwrap().ends_with("|vs"));
+    assert!(division.contains("[](/r/tampabaylightning)TBL|69|52-13-4|108|48|7-2-1|W3 🔥|0.783|128|1-1-1\n"));
+    assert!(division.contains("[](/r/bostonbruins)BOS|69|42-17-10|94|38|7-1-2|W2|0.681|112|—\n"));
+
+    // The team's own row is left blank.
+    let wildcard = gen
+        .make_standings_table(&an.wildcard_seed, Some(an.wildcard_spots))
+        .unwrap()
+        .to_string();
+    assert!(
+        wildcard.contains("|**[](/r/habs)MTL**|**70**|**37-26-7**|**81**|**33**|**6-3-1**|**W1**|**0.579**|**95**|\n")
+    );
+    assert!(wildcard.contains("|[](/r/canes)CAR|69|38-25-6|82|35|8-2-0|W4 🔥|0.594|97|—\n"));
+}
+
+#[test]
+fn test_odds_footer() {
+    use generate::PostSection;
+    use simulation::{OddsMode, Params};
+
+    let api = load_api();
+    let team = api.get_team_by_abbrev("MTL");
+    // The footer only reads the parameters, the date and the clinch status.
+    let footer = |params: Params| {
+        let analyzer = Analyzer::new(&api, team).with_options(analysis::Options {
+            params,
+            ..seeding_options()
+        });
+        let an = analyzer.perform();
+        MarkdownGenerator::new(&api, &an, &[], team).make_odds_footer()
+    };
+
+    assert_eq!(
+        footer(test_params()),
+        "Simulated 5,000 seasons using season-long win/OT/loss rates as of Mar 15."
+    );
+    assert_eq!(
+        footer(Params {
+            form_weight: 0.3,
+            streak_weight: 0.5,
+            ..test_params()
+        }),
+        "Simulated 5,000 seasons using win/OT/loss rates weighing the last 10 games at 30% \
+         as of Mar 15, with a streak weight of 0.5."
+    );
+    assert_eq!(
+        footer(Params {
+            prior_games: 10.0,
+            ..test_params()
+        }),
+        "Simulated 5,000 seasons using season-long win/OT/loss rates as of Mar 15, \
+         with 10 games at the league average added to every record."
+    );
+    assert_eq!(
+        footer(Params {
+            odds_mode: OddsMode::Analytic,
+            ..test_params()
+        }),
+        "Odds from the clinch status and the points pace as of Mar 15, without simulations."
+    );
+
+    // It ends the disclaimer of the post.
+    let analyzer = Analyzer::new(&api, team).with_options(seeding_options());
+    let an = analyzer.perform();
+    let options = generate::Options {
+        odds_footer: true,
+        sections: vec![PostSection::Disclaimer],
+        ..Default::default()
+    };
+    let gen = MarkdownGenerator::new(&api, &an, &[], team).with_options(options);
+    let doc = gen.markdown().unwrap();
+    assert!(doc.as_str().ends_with(&format!("{}\n\n", gen.make_odds_footer())));
+}
+
+#[test]
+fn test_bracket_json() {
+    let api = load_api();
+    let team = api.get_team_by_abbrev("MTL");
+    let analyzer = Analyzer::new(&api, team).with_options(analysis::Options {
+        other_conference: true,
+        ..seeding_options()
+    });
+    let an = analyzer.perform();
+
+    let brackets = an.brackets(&api);
+    assert_eq!(brackets.len(), 2);
+    let east = &brackets[0];
+    assert_eq!(east.conference, "Eastern");
+    assert_eq!(east.matchups.len(), 4);
+    let high = &east.matchups[0].high;
+    assert_eq!(
+        (high.id, high.abbrev.as_str(), high.seed, high.points),
+        (14, "TBL", 1, 108)
+    );
+    assert_eq!(east.matchups[0].low.label.to_string(), "WC2");
+    // The Atlantic winner's side meets the Atlantic 2 vs 3.
+    assert_eq!(east.second_round, vec![(0, 2), (1, 3)]);
+
+    let json = serde_json::to_value(&brackets).unwrap();
+    assert_eq!(json[0]["matchups"][0]["high"]["label"], "A1");
+    assert_eq!(json[0]["matchups"][0]["high"]["abbrev"], "TBL");
+    assert_eq!(json[0]["second_round"][0], serde_json::json!([0, 2]));
+    assert_eq!(json[1]["conference"], "Western");
+}
+
+/// Whole post for MTL on the fixtures' date, with seeded simulations. Run
+/// with `UPDATE_GOLDEN=1` to write the new post after an intended change.
+#[test]
+fn test_markdown_golden() {
+    let path = format!("{}/tests/fixtures/golden-MTL-2019-03-15.md", env!("CARGO_MANIFEST_DIR"));
+    let api = load_api();
+    let schedule = load_schedule();
+    let team = api.get_team_by_abbrev("MTL");
+    let options = analysis::Options {
+        params: simulation::Params {
+            seed: Some(2019),
+            ..test_params()
+        },
+        ..test_options()
+    };
+    let analyzer = Analyzer::new(&api, team).with_options(options);
+    let an = analyzer.perform();
+    let doc = MarkdownGenerator::new(&api, &an, &schedule, team).markdown().unwrap();
+
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::write(&path, doc.as_str()).unwrap();
+    }
+    let golden = std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("error reading {}: {}", path, e));
+    assert!(
+        doc.as_str() == golden,
+        "the post differs from {}, run with UPDATE_GOLDEN=1 if intended:\n{}",
+        path,
+        doc.as_str()
+    );
+}